@@ -1,6 +1,8 @@
+use crate::election_keys::{rsa_public_key_from_seed, DEV_BALLOT_SEED, DEV_CANDIDATE_SEEDS};
 use node_template_runtime::{
-	pallet_voting_system::GenesisConfig as VotingSystemConfig, AccountId, AuraConfig,
-	GenesisConfig, GrandpaConfig, Signature, SudoConfig, SystemConfig, WASM_BINARY,
+	pallet_voting_system::{GenesisConfig as VotingSystemConfig, RevotePolicy},
+	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
+	SystemConfig, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -36,16 +38,26 @@ pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
 	(get_from_seed::<AuraId>(s), get_from_seed::<GrandpaId>(s))
 }
 
+/// Generate the development candidates, their accounts and RSA keys share the same seed, which
+/// is also their name.
+fn dev_candidates() -> Vec<(AccountId, String, Vec<u8>)> {
+	DEV_CANDIDATE_SEEDS
+		.iter()
+		.map(|seed| {
+			(
+				get_account_id_from_seed::<sr25519::Public>(seed),
+				seed.to_string(),
+				rsa_public_key_from_seed(seed),
+			)
+		})
+		.collect()
+}
+
 pub fn development_config() -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
 
-	let candidate1_account_id = get_account_id_from_seed::<sr25519::Public>("Candidate1");
-	let candidate2_account_id = get_account_id_from_seed::<sr25519::Public>("Candidate2");
-	let candidate3_account_id = get_account_id_from_seed::<sr25519::Public>("Candidate3");
-
-	let candidate1_public_key = vec![1, 2, 3];
-	let candidate2_public_key = vec![1, 2, 3];
-	let candidate3_public_key = vec![1, 2, 3];
+	let candidates = dev_candidates();
+	let ballot_public_key = rsa_public_key_from_seed(DEV_BALLOT_SEED);
 
 	Ok(ChainSpec::from_genesis(
 		// Name
@@ -61,16 +73,20 @@ pub fn development_config() -> Result<ChainSpec, String> {
 				// Central authority
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
 				// Candidates
-				vec![
-					(candidate1_account_id.clone(), candidate1_public_key.clone()),
-					(candidate2_account_id.clone(), candidate2_public_key.clone()),
-					(candidate3_account_id.clone(), candidate3_public_key.clone()),
-				],
+				candidates.clone(),
 				// Ballot public key
-				get_account_id_from_seed::<sr25519::Public>("ballot").to_string().into_bytes(),
+				ballot_public_key.clone(),
+				// Revote policy
+				RevotePolicy::LastVoteCounts,
 				// sudo account
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
 				// Pre-funded accounts
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
+					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
+				],
 				true,
 			)
 		},
@@ -91,13 +107,8 @@ pub fn development_config() -> Result<ChainSpec, String> {
 pub fn local_testnet_config() -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
 
-	let candidate1_account_id = get_account_id_from_seed::<sr25519::Public>("Candidate1");
-	let candidate2_account_id = get_account_id_from_seed::<sr25519::Public>("Candidate2");
-	let candidate3_account_id = get_account_id_from_seed::<sr25519::Public>("Candidate3");
-
-	let candidate1_public_key = vec![1, 2, 3];
-	let candidate2_public_key = vec![1, 2, 3];
-	let candidate3_public_key = vec![1, 2, 3];
+	let candidates = dev_candidates();
+	let ballot_public_key = rsa_public_key_from_seed(DEV_BALLOT_SEED);
 
 	Ok(ChainSpec::from_genesis(
 		// Name
@@ -113,15 +124,28 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 				// Central authority
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
 				// Candidates
-				vec![
-					(candidate1_account_id.clone(), candidate1_public_key.clone()),
-					(candidate2_account_id.clone(), candidate2_public_key.clone()),
-					(candidate3_account_id.clone(), candidate3_public_key.clone()),
-				],
+				candidates.clone(),
 				// Ballot public key
-				get_account_id_from_seed::<sr25519::Public>("ballot").to_string().into_bytes(),
+				ballot_public_key.clone(),
+				// Revote policy
+				RevotePolicy::LastVoteCounts,
 				// Sudo account
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// Pre-funded accounts
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+					get_account_id_from_seed::<sr25519::Public>("Charlie"),
+					get_account_id_from_seed::<sr25519::Public>("Dave"),
+					get_account_id_from_seed::<sr25519::Public>("Eve"),
+					get_account_id_from_seed::<sr25519::Public>("Ferdie"),
+					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
+					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
+					get_account_id_from_seed::<sr25519::Public>("Charlie//stash"),
+					get_account_id_from_seed::<sr25519::Public>("Dave//stash"),
+					get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
+					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
+				],
 				true,
 			)
 		},
@@ -140,13 +164,15 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 }
 
 /// Configure initial storage state for FRAME modules.
-fn testnet_genesis(
+pub fn testnet_genesis(
 	wasm_binary: &[u8],
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	central_authority: AccountId,
-	candidates: Vec<(AccountId, Vec<u8>)>,
+	candidates: Vec<(AccountId, String, Vec<u8>)>,
 	ballot_public_key: Vec<u8>,
+	revote_policy: RevotePolicy,
 	root_key: AccountId,
+	endowed_accounts: Vec<AccountId>,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -157,6 +183,10 @@ fn testnet_genesis(
 		aura: AuraConfig {
 			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
 		},
+		balances: BalancesConfig {
+			// Configure endowed accounts with initial balance of 1 << 60.
+			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
+		},
 		grandpa: GrandpaConfig {
 			authorities: initial_authorities.iter().map(|x| (x.1.clone(), 1)).collect(),
 		},
@@ -164,10 +194,12 @@ fn testnet_genesis(
 			// Assign network admin rights.
 			key: Some(root_key.clone()),
 		},
+		transaction_payment: Default::default(),
 		voting_system: VotingSystemConfig {
 			central_authority: Some(central_authority),
 			candidates,
 			ballot_public_key,
+			revote_policy,
 		},
 	}
 }
@@ -7,6 +7,11 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: RunCmd,
+
+	/// Development candidate this node signs for, one of `Candidate1`, `Candidate2` or
+	/// `Candidate3`. Only supported on the development chain.
+	#[arg(long, value_name = "SEED")]
+	pub dev_candidate: Option<String>,
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -50,4 +55,18 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Election administration utilities.
+	#[command(subcommand)]
+	Election(crate::election::ElectionCmd),
+
+	/// Export the election results.
+	ExportResults(crate::results::ExportResultsCmd),
+
+	/// Recount the ballots and verify the stored tally.
+	VerifyTally(crate::verify_tally::VerifyTallyCmd),
+
+	/// Voter client utilities for the blind signature voting flow.
+	#[command(subcommand)]
+	Voter(crate::voter::VoterCmd),
 }
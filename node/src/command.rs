@@ -194,10 +194,29 @@ pub fn run() -> sc_cli::Result<()> {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| cmd.run::<Block>(&config))
 		},
+		Some(Subcommand::Election(cmd)) => cmd.run(),
+		Some(Subcommand::ExportResults(cmd)) if cmd.snapshot.is_some() => cmd.run_snapshot(),
+		Some(Subcommand::ExportResults(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, .. } = service::new_partial(&config)?;
+				cmd.run(client, config.chain_spec.name().into())
+			})
+		},
+		Some(Subcommand::VerifyTally(cmd)) if cmd.snapshot.is_some() => cmd.run_snapshot(),
+		Some(Subcommand::VerifyTally(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, .. } = service::new_partial(&config)?;
+				cmd.run(client)
+			})
+		},
+		Some(Subcommand::Voter(cmd)) => cmd.run(),
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
+			let dev_candidate = cli.dev_candidate.clone();
 			runner.run_node_until_exit(|config| async move {
-				service::new_full(config).map_err(sc_cli::Error::Service)
+				service::new_full(config, dev_candidate).map_err(sc_cli::Error::Service)
 			})
 		},
 	}
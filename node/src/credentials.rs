@@ -0,0 +1,187 @@
+//! Credential management of the coercion resistant election mode.
+//!
+//! These commands are run by whoever holds the tally secret, usually the CA. The pallet
+//! `credentials` module describes the scheme:
+//!
+//! 1. `election credential-keygen` creates the tally secret and the tally key. The CA sets the key
+//!    with `set_credential_key` during the initialization phase.
+//! 2. `election issue-credentials` draws a credential for every voter registered in an
+//!    `export-state` snapshot taken once registration is done. Every voter gets their credential
+//!    file in private, the encrypted credentials are registered with `register_credential`.
+//! 3. `election tally-credentials` filters the credential ballots of an `export-state` snapshot
+//!    taken during counting and prints the tally and the transcript hash to submit with
+//!    `submit_credential_tally`. The transcript proving the tally is written to a file that is
+//!    published, anybody checks it with `verify-tally --credential-transcript`. Working on a
+//!    snapshot keeps the tally secret off any networked machine.
+//!
+//! Secrets, keys and credentials are stored as `0x` prefixed hex strings.
+
+use crate::voter::read_bytes;
+use codec::Encode;
+use node_template_runtime::{
+	pallet_voting_system::{
+		credentials::{self, GroupElement},
+		Voters,
+	},
+	Runtime, VotingSystem,
+};
+use sc_cli::Error;
+use sp_runtime::BuildStorage;
+use std::{
+	collections::BTreeMap,
+	path::{Path, PathBuf},
+};
+
+/// The `election credential-keygen` command
+#[derive(Debug, clap::Parser)]
+pub struct CredentialKeygenCmd {
+	/// Directory the tally secret and the tally key are written to.
+	#[arg(long, value_name = "DIR")]
+	output: PathBuf,
+}
+
+impl CredentialKeygenCmd {
+	pub fn run(&self) -> Result<(), Error> {
+		let secret_path = self.output.join("tally.secret");
+		if secret_path.exists() {
+			return Err(Error::Input(format!("{} already exists", secret_path.display())))
+		}
+
+		let secret = credentials::random_exponent(&mut rand::thread_rng());
+		let tally_key = credentials::public_key(&secret);
+
+		std::fs::create_dir_all(&self.output)?;
+		std::fs::write(&secret_path, format!("0x{}", hex::encode(&secret)))?;
+		std::fs::write(self.output.join("tally.key"), format!("0x{}", hex::encode(tally_key)))?;
+
+		println!("Tally key: 0x{}", hex::encode(tally_key));
+		println!("Tally secret written to {}, keep it offline", secret_path.display());
+		Ok(())
+	}
+}
+
+/// The `election issue-credentials` command
+#[derive(Debug, clap::Parser)]
+pub struct IssueCredentialsCmd {
+	/// Tally key written by `election credential-keygen`.
+	#[arg(long, value_name = "FILE")]
+	tally_key: PathBuf,
+
+	/// `export-state` snapshot holding the registered voters, a credential is issued to every one.
+	#[arg(long, value_name = "FILE")]
+	snapshot: PathBuf,
+
+	/// Directory the credentials and the registrations are written to.
+	#[arg(long, value_name = "DIR")]
+	output: PathBuf,
+}
+
+impl IssueCredentialsCmd {
+	pub fn run(&self) -> Result<(), Error> {
+		let tally_key = read_element(&self.tally_key)?;
+		let chain_spec = crate::chain_spec::ChainSpec::from_json_file(self.snapshot.clone())?;
+		let voters: Vec<_> = sp_io::TestExternalities::new(chain_spec.build_storage()?)
+			.execute_with(|| Voters::<Runtime>::iter_keys().collect());
+		let mut rng = rand::thread_rng();
+
+		std::fs::create_dir_all(&self.output)?;
+		let mut registrations = Vec::new();
+		for voter in &voters {
+			// Voter ids are hashes, the files and registrations use their hex encoding
+			let voter = format!("0x{}", hex::encode(voter));
+			let credential = credentials::new_credential(&mut rng);
+			let encrypted = credentials::encrypt(&tally_key, &credential, &mut rng)
+				.ok_or("The tally key is not a valid group element")?;
+
+			let path = self.output.join(format!("voter-{}.credential", voter));
+			std::fs::write(&path, format!("0x{}", hex::encode(credential)))?;
+			registrations.push(serde_json::json!({
+				"voter": voter,
+				"a": format!("0x{}", hex::encode(encrypted.a)),
+				"b": format!("0x{}", hex::encode(encrypted.b)),
+			}));
+		}
+
+		let registrations_path = self.output.join("registrations.json");
+		let json = serde_json::to_string_pretty(&registrations).map_err(|e| e.to_string())?;
+		std::fs::write(&registrations_path, json)?;
+
+		println!(
+			"Issued {} credentials, hand out the voter-<id>.credential files in private and \
+			 register the encrypted credentials in {}",
+			voters.len(),
+			registrations_path.display()
+		);
+		Ok(())
+	}
+}
+
+/// The `election tally-credentials` command
+#[derive(Debug, clap::Parser)]
+pub struct TallyCredentialsCmd {
+	/// Tally secret written by `election credential-keygen`.
+	#[arg(long, value_name = "FILE")]
+	secret: PathBuf,
+
+	/// `export-state` snapshot taken during the counting phase.
+	#[arg(long, value_name = "FILE")]
+	snapshot: PathBuf,
+
+	/// File the SCALE encoded transcript proving the tally is written to, to be published.
+	#[arg(long, value_name = "FILE")]
+	transcript: PathBuf,
+}
+
+impl TallyCredentialsCmd {
+	pub fn run(&self) -> Result<(), Error> {
+		let secret = read_bytes(&self.secret)?;
+		let chain_spec = crate::chain_spec::ChainSpec::from_json_file(self.snapshot.clone())?;
+		let storage = chain_spec.build_storage()?;
+
+		let (transcript, ballots, hash) =
+			sp_io::TestExternalities::new(storage).execute_with(|| {
+				if VotingSystem::credential_key() != Some(credentials::public_key(&secret)) {
+					return Err(Error::Input("The secret does not belong to the tally key".into()))
+				}
+
+				let (registered, ballots) = VotingSystem::credential_tally_inputs();
+				let transcript = credentials::prove_tally(
+					&secret,
+					&registered,
+					&ballots,
+					&mut rand::thread_rng(),
+				)
+				.ok_or("A stored credential is not a valid ciphertext")?;
+				let hash = VotingSystem::credential_transcript_hash(&transcript);
+				Ok((transcript, ballots.len() as u32, hash))
+			})?;
+
+		std::fs::write(&self.transcript, transcript.encode())?;
+
+		let mut tally = BTreeMap::<u32, u32>::new();
+		for ballot in &transcript.counted {
+			*tally.entry(ballot.commitment).or_default() += 1;
+		}
+		let counted = transcript.counted.len() as u32;
+		let tally: Vec<_> = tally.into_iter().collect();
+		let report = serde_json::json!({
+			"counted": counted,
+			"discarded": ballots - counted,
+			"tally": tally,
+			"transcript": hash,
+		});
+		println!("{}", serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?);
+		Ok(())
+	}
+}
+
+/// Read a group element, as written by the credential commands
+pub(crate) fn read_element(path: &Path) -> Result<GroupElement, Error> {
+	let element: GroupElement = read_bytes(path)?
+		.try_into()
+		.map_err(|_| format!("{} does not hold a group element", path.display()))?;
+	if !credentials::is_group_element(&element) {
+		return Err(format!("{} does not hold a group element", path.display()).into())
+	}
+	Ok(element)
+}
@@ -0,0 +1,420 @@
+//! Election administration commands.
+//!
+//! `election init` turns an election definition into a chain spec for a live chain. The definition
+//! is a TOML or JSON file, picked by its extension:
+//!
+//! ```toml
+//! name = "Student Council 2023"
+//! id = "student_council_2023"
+//! central_authority = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! tally_method = "plurality"
+//! revote_policy = { MaxRevotes = 3 }
+//!
+//! [[authorities]]
+//! aura = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! grandpa = "5FA9nQDVg267DEd8m1ZypXLBnvN7SFxYwV7ndqSYGiN9TTpu"
+//!
+//! [[candidates]]
+//! account = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty"
+//! name = "Bob"
+//! public_key = "bob.pub.der"
+//!
+//! [[candidates]]
+//! account = "5FLSigC9HGRKVhB9FiEo4Y3koPsNmBmLJbpXg2mp1hXcS59Y"
+//! name = "Charlie"
+//!
+//! [schedule]
+//! registration = "2023-03-01"
+//! voting = "2023-03-15"
+//! ```
+//!
+//! The revote policy is one of `"SingleVote"`, `"LastVoteCounts"`, the default, or
+//! `{ MaxRevotes = <n> }`.
+//!
+//! Candidates without a `public_key` file, and the ballot key when `ballot_key` is missing, get a
+//! freshly generated RSA key pair written to the output directory. Key file paths are relative to
+//! the definition file. The candidates, their names included, and the keys go into the voting
+//! system genesis config, checked with its `validate`, so a chain spec written by `election init`
+//! always starts.
+//!
+//! The voting system has no on-chain schedule, the CA advances the phases, and only counts votes by
+//! plurality. The schedule and the tally method are therefore only recorded in the chain spec
+//! properties, for the CA and the voters to look up.
+
+use crate::{
+	chain_spec::{testnet_genesis, ChainSpec},
+	credentials::{CredentialKeygenCmd, IssueCredentialsCmd, TallyCredentialsCmd},
+};
+use blind_rsa_signatures::KeyPair;
+use node_template_runtime::{
+	pallet_voting_system::{GenesisConfig as VotingSystemConfig, RevotePolicy},
+	AccountId, Runtime, WASM_BINARY,
+};
+use sc_cli::Error;
+use sc_service::{ChainType, Properties};
+use serde::Deserialize;
+use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+use sp_core::{crypto::Ss58Codec, ed25519, sr25519};
+use sp_finality_grandpa::AuthorityId as GrandpaId;
+use std::{
+	collections::BTreeMap,
+	path::{Path, PathBuf},
+};
+
+/// Tally methods supported by the voting system.
+const TALLY_METHODS: [&str; 1] = ["plurality"];
+
+/// The `election` command used to set up elections.
+#[derive(Debug, clap::Subcommand)]
+pub enum ElectionCmd {
+	/// Generate the chain spec and key material of an election.
+	Init(InitCmd),
+
+	/// Generate the tally key of a coercion resistant election.
+	CredentialKeygen(CredentialKeygenCmd),
+
+	/// Issue the voter credentials of a coercion resistant election.
+	IssueCredentials(IssueCredentialsCmd),
+
+	/// Filter and count the credential ballots of a coercion resistant election.
+	TallyCredentials(TallyCredentialsCmd),
+}
+
+impl ElectionCmd {
+	/// Run the election command
+	pub fn run(&self) -> Result<(), Error> {
+		match self {
+			ElectionCmd::Init(cmd) => cmd.run(),
+			ElectionCmd::CredentialKeygen(cmd) => cmd.run(),
+			ElectionCmd::IssueCredentials(cmd) => cmd.run(),
+			ElectionCmd::TallyCredentials(cmd) => cmd.run(),
+		}
+	}
+}
+
+/// The `election init` command
+#[derive(Debug, clap::Parser)]
+pub struct InitCmd {
+	/// Election definition, a `.toml` or `.json` file.
+	#[arg(value_name = "DEFINITION")]
+	definition: PathBuf,
+
+	/// Directory the chain spec and the generated keys are written to.
+	#[arg(long, value_name = "DIR")]
+	output: PathBuf,
+
+	/// Modulus size of the generated RSA keys.
+	#[arg(long, default_value_t = 2048)]
+	key_bits: usize,
+
+	/// Write the chain spec in raw format.
+	#[arg(long)]
+	raw: bool,
+}
+
+/// Election definition read by `election init`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ElectionDefinition {
+	/// Name of the election, used as the chain name
+	name: String,
+	/// Chain id, derived from the name when missing
+	id: Option<String>,
+	/// SS58 address of the central authority
+	central_authority: String,
+	/// SS58 address of the sudo account, the central authority when missing
+	sudo: Option<String>,
+	/// Block authoring and finality authorities
+	authorities: Vec<AuthorityDefinition>,
+	candidates: Vec<CandidateDefinition>,
+	/// DER encoded RSA public key of the ballots
+	ballot_key: Option<PathBuf>,
+	/// Dates of the election phases, keyed by phase
+	#[serde(default)]
+	schedule: BTreeMap<String, String>,
+	#[serde(default = "default_tally_method")]
+	tally_method: String,
+	#[serde(default)]
+	revote_policy: RevotePolicy,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AuthorityDefinition {
+	aura: String,
+	grandpa: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CandidateDefinition {
+	/// SS58 address of the candidate
+	account: String,
+	name: String,
+	/// DER encoded RSA public key of the candidate
+	public_key: Option<PathBuf>,
+}
+
+fn default_tally_method() -> String {
+	TALLY_METHODS[0].into()
+}
+
+impl InitCmd {
+	fn run(&self) -> Result<(), Error> {
+		let definition = self.read_definition()?;
+		let base_dir = self.definition.parent().unwrap_or_else(|| Path::new("."));
+		let key_dir = self.output.join("keys");
+
+		if !TALLY_METHODS.contains(&definition.tally_method.as_str()) {
+			return Err(Error::Input(format!(
+				"Unsupported tally method {}, supported are: {}",
+				definition.tally_method,
+				TALLY_METHODS.join(", ")
+			)))
+		}
+
+		let central_authority = parse_ss58::<AccountId>(&definition.central_authority)?;
+		let sudo = match &definition.sudo {
+			Some(sudo) => parse_ss58::<AccountId>(sudo)?,
+			None => central_authority.clone(),
+		};
+		// Election duties are free, the CA and sudo only pay for administration
+		let mut endowed_accounts = vec![central_authority.clone()];
+		if sudo != central_authority {
+			endowed_accounts.push(sudo.clone());
+		}
+		let authorities = definition
+			.authorities
+			.iter()
+			.map(|authority| {
+				let aura = parse_ss58::<sr25519::Public>(&authority.aura)?;
+				let grandpa = parse_ss58::<ed25519::Public>(&authority.grandpa)?;
+				Ok((AuraId::from(aura), GrandpaId::from(grandpa)))
+			})
+			.collect::<Result<Vec<_>, Error>>()?;
+		if authorities.is_empty() {
+			return Err(Error::Input("At least one authority is required".into()))
+		}
+
+		let mut candidates = Vec::new();
+		for candidate in &definition.candidates {
+			let account = parse_ss58::<AccountId>(&candidate.account)?;
+			let public_key = match &candidate.public_key {
+				Some(path) => std::fs::read(base_dir.join(path))?,
+				None => self.generate_key(&key_dir, &format!("candidate-{}", candidate.account))?,
+			};
+			candidates.push((account, candidate.name.clone(), public_key));
+		}
+		let ballot_public_key = match &definition.ballot_key {
+			Some(path) => std::fs::read(base_dir.join(path))?,
+			None => self.generate_key(&key_dir, "ballot")?,
+		};
+
+		VotingSystemConfig::<Runtime> {
+			central_authority: Some(central_authority.clone()),
+			candidates: candidates.clone(),
+			ballot_public_key: ballot_public_key.clone(),
+			revote_policy: definition.revote_policy.clone(),
+		}
+		.validate()
+		.map_err(|e| Error::Input(e.to_string()))?;
+
+		let wasm_binary = WASM_BINARY.ok_or("Wasm binary not available")?;
+		let id = definition.id.clone().unwrap_or_else(|| chain_id(&definition.name));
+		let properties = election_properties(&definition);
+		let revote_policy = definition.revote_policy.clone();
+		let chain_spec = ChainSpec::from_genesis(
+			&definition.name,
+			&id,
+			ChainType::Live,
+			move || {
+				testnet_genesis(
+					wasm_binary,
+					authorities.clone(),
+					central_authority.clone(),
+					candidates.clone(),
+					ballot_public_key.clone(),
+					revote_policy.clone(),
+					sudo.clone(),
+					endowed_accounts.clone(),
+					false,
+				)
+			},
+			vec![],
+			None,
+			None,
+			None,
+			Some(properties),
+			None,
+		);
+
+		std::fs::create_dir_all(&self.output)?;
+		let chain_spec_path = self.output.join("chain-spec.json");
+		std::fs::write(&chain_spec_path, chain_spec.as_json(self.raw)?)?;
+
+		println!("Chain spec written to {}", chain_spec_path.display());
+		Ok(())
+	}
+
+	fn read_definition(&self) -> Result<ElectionDefinition, Error> {
+		let content = std::fs::read_to_string(&self.definition)?;
+		let definition = match self.definition.extension().and_then(|ext| ext.to_str()) {
+			Some("toml") => toml::from_str(&content).map_err(|e| e.to_string()),
+			Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string()),
+			_ =>
+				return Err(Error::Input(
+					"The election definition must be a .toml or .json file".into(),
+				)),
+		};
+		definition
+			.map_err(|e| Error::Input(format!("Reading {}: {}", self.definition.display(), e)))
+	}
+
+	/// Generate an RSA key pair, write it to `dir` and return the DER encoded public key
+	fn generate_key(&self, dir: &Path, name: &str) -> Result<Vec<u8>, Error> {
+		let key_pair = KeyPair::generate(&mut rand::thread_rng(), self.key_bits)
+			.map_err(|e| format!("Generating the {} key: {:?}", name, e))?;
+		let secret = key_pair.sk.to_der().map_err(|e| format!("Encoding {}: {:?}", name, e))?;
+		let public = key_pair.pk.to_der().map_err(|e| format!("Encoding {}: {:?}", name, e))?;
+
+		std::fs::create_dir_all(dir)?;
+		std::fs::write(dir.join(format!("{}.der", name)), secret)?;
+		std::fs::write(dir.join(format!("{}.pub.der", name)), &public)?;
+
+		println!("Generated the {} key in {}", name, dir.display());
+		Ok(public)
+	}
+}
+
+/// Chain spec properties recording the parts of the definition the runtime does not store
+fn election_properties(definition: &ElectionDefinition) -> Properties {
+	let mut properties = Properties::new();
+	properties.insert(
+		"election".into(),
+		serde_json::json!({
+			"schedule": definition.schedule,
+			"tallyMethod": definition.tally_method,
+		}),
+	);
+	properties
+}
+
+/// Derive a chain id from the election name
+fn chain_id(name: &str) -> String {
+	name.chars()
+		.map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+		.collect()
+}
+
+fn parse_ss58<T: Ss58Codec>(address: &str) -> Result<T, Error> {
+	T::from_ss58check(address)
+		.map_err(|e| Error::Input(format!("Invalid address {}: {:?}", address, e)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use clap::Parser;
+
+	const TEST_KEYS: &str =
+		concat!(env!("CARGO_MANIFEST_DIR"), "/../pallets/voting-system/test-keys");
+
+	/// Directory holding the definition of an election with the test keys of Bob and Charlie and
+	/// the ballot key, `extra` is appended to the top of the definition
+	fn election_dir(name: &str, charlie_key: u32, extra: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("election-{}-{}", name, std::process::id()));
+		let _ = std::fs::remove_dir_all(&dir);
+		std::fs::create_dir_all(&dir).unwrap();
+		for key in [1, 2, 3] {
+			std::fs::copy(
+				format!("{}/key{}.pub.der", TEST_KEYS, key),
+				dir.join(format!("key{}.pub.der", key)),
+			)
+			.unwrap();
+		}
+
+		let definition = format!(
+			r#"name = "Student Council 2023"
+central_authority = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+ballot_key = "key3.pub.der"
+revote_policy = {{ MaxRevotes = 3 }}
+{}
+
+[[authorities]]
+aura = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+grandpa = "5FA9nQDVg267DEd8m1ZypXLBnvN7SFxYwV7ndqSYGiN9TTpu"
+
+[[candidates]]
+account = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty"
+name = "Bob"
+public_key = "key1.pub.der"
+
+[[candidates]]
+account = "5FLSigC9HGRKVhB9FiEo4Y3koPsNmBmLJbpXg2mp1hXcS59Y"
+name = "Charlie"
+public_key = "key{}.pub.der"
+
+[schedule]
+registration = "2023-03-01"
+voting = "2023-03-15"
+"#,
+			extra, charlie_key
+		);
+		std::fs::write(dir.join("election.toml"), definition).unwrap();
+		dir
+	}
+
+	fn init(dir: &Path) -> Result<(), Error> {
+		InitCmd::parse_from([
+			"init".into(),
+			dir.join("election.toml").display().to_string(),
+			"--output".into(),
+			dir.join("out").display().to_string(),
+		])
+		.run()
+	}
+
+	#[test]
+	fn init_writes_the_chain_spec_of_the_election() {
+		let dir = election_dir("init", 2, "");
+		init(&dir).unwrap();
+
+		let chain_spec = std::fs::read_to_string(dir.join("out/chain-spec.json")).unwrap();
+		let json: serde_json::Value = serde_json::from_str(&chain_spec).unwrap();
+		assert_eq!(json["name"], "Student Council 2023");
+		assert_eq!(json["id"], "student_council_2023");
+		assert_eq!(json["chainType"], "Live");
+		assert_eq!(
+			json["properties"]["election"],
+			serde_json::json!({
+				"schedule": { "registration": "2023-03-01", "voting": "2023-03-15" },
+				"tallyMethod": "plurality",
+			})
+		);
+		assert!(chain_spec.contains("\"Bob\"") && chain_spec.contains("\"Charlie\""));
+		// Every key was given, none is generated
+		assert!(!dir.join("out/keys").exists());
+
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn init_rejects_definitions_the_chain_would_not_start_with() {
+		// Charlie reuses the key of Bob
+		let dir = election_dir("duplicate-key", 1, "");
+		let error = init(&dir).unwrap_err().to_string();
+		assert!(error.contains("Charlie reuses the public key"), "{}", error);
+		assert!(!dir.join("out/chain-spec.json").exists());
+		std::fs::remove_dir_all(dir).unwrap();
+
+		let dir = election_dir("tally-method", 2, r#"tally_method = "borda""#);
+		let error = init(&dir).unwrap_err().to_string();
+		assert!(error.contains("Unsupported tally method borda"), "{}", error);
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn chain_ids_are_derived_from_the_name() {
+		assert_eq!(chain_id("Board & Council 2024"), "board___council_2024");
+	}
+}
@@ -0,0 +1,103 @@
+//! Deterministic RSA election keys for the development chains.
+//!
+//! Candidates sign blinded voter keys with RSA keys and the ballot key is an RSA key pair as well.
+//! For the development chains those keys are derived from well-known seeds, in the same way the
+//! sr25519 development accounts are, so the public keys in genesis always match the private keys
+//! written next to the chain data and handed to the offchain worker.
+
+use blind_rsa_signatures::KeyPair;
+use codec::Encode;
+use node_template_runtime::pallet_voting_system::{rsa_key_storage_key, KEY_TYPE};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use sp_core::offchain::{OffchainStorage, STORAGE_PREFIX};
+use sp_keystore::SyncCryptoStore;
+use sp_runtime::{traits::IdentifyAccount, MultiSigner};
+use std::path::Path;
+
+/// Modulus size of the generated development keys.
+pub const DEV_KEY_BITS: usize = 2048;
+
+/// Seeds of the development candidate keys, in candidate order.
+pub const DEV_CANDIDATE_SEEDS: [&str; 3] = ["Candidate1", "Candidate2", "Candidate3"];
+
+/// Seed of the development ballot key.
+pub const DEV_BALLOT_SEED: &str = "Ballot";
+
+/// Directory, relative to the chain config directory, the development keys are written to.
+pub const DEV_KEY_DIR: &str = "election-keys";
+
+/// Generate an RSA key pair from a seed.
+///
+/// The same seed always yields the same key pair.
+pub fn rsa_key_pair_from_seed(seed: &str) -> KeyPair {
+	let mut rng = ChaCha20Rng::from_seed(sp_core::blake2_256(seed.as_bytes()));
+	KeyPair::generate(&mut rng, DEV_KEY_BITS).expect("static key size is valid; qed")
+}
+
+/// DER encoded RSA public key generated from a seed.
+pub fn rsa_public_key_from_seed(seed: &str) -> Vec<u8> {
+	rsa_key_pair_from_seed(seed)
+		.pk
+		.to_der()
+		.expect("generated keys can be encoded; qed")
+}
+
+/// Write the development key pairs to `dir`.
+///
+/// Every seed gets a `<seed>.der` file holding the PKCS#8 private key and a `<seed>.pub.der` file
+/// holding the public key. Key pairs already written on an earlier start are not generated again.
+pub fn write_dev_keys(dir: &Path) -> Result<(), String> {
+	std::fs::create_dir_all(dir).map_err(|e| format!("Creating {}: {}", dir.display(), e))?;
+
+	for seed in DEV_CANDIDATE_SEEDS.iter().chain(std::iter::once(&DEV_BALLOT_SEED)) {
+		let name = seed.to_lowercase();
+		let files = [dir.join(format!("{}.der", name)), dir.join(format!("{}.pub.der", name))];
+		if files.iter().all(|path| path.exists()) {
+			continue
+		}
+
+		let key_pair = rsa_key_pair_from_seed(seed);
+		let secret = key_pair.sk.to_der().map_err(|e| format!("Encoding {}: {:?}", seed, e))?;
+		let public = key_pair.pk.to_der().map_err(|e| format!("Encoding {}: {:?}", seed, e))?;
+		for (path, bytes) in files.iter().zip([secret, public]) {
+			std::fs::write(path, bytes)
+				.map_err(|e| format!("Writing {}: {}", path.display(), e))?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Let the node sign for the development candidate derived from `seed`.
+///
+/// The candidate account key is added to the keystore under the voting system key type and its RSA
+/// private key to the persistent offchain storage, where the voting system offchain worker picks
+/// it up during the biased signer phase. The offchain storage outlives restarts, a key stored on
+/// an earlier start is kept.
+pub fn insert_dev_signing_key(
+	keystore: &dyn SyncCryptoStore,
+	offchain: &mut impl OffchainStorage,
+	seed: &str,
+) -> Result<(), String> {
+	if !DEV_CANDIDATE_SEEDS.contains(&seed) {
+		return Err(format!(
+			"Unknown development candidate {}, expected one of {}",
+			seed,
+			DEV_CANDIDATE_SEEDS.join(", ")
+		))
+	}
+
+	let public = keystore
+		.sr25519_generate_new(KEY_TYPE, Some(&format!("//{}", seed)))
+		.map_err(|e| format!("Inserting {} into the keystore: {}", seed, e))?;
+	let key = rsa_key_storage_key(&MultiSigner::from(public).into_account());
+	if offchain.get(STORAGE_PREFIX, &key).is_none() {
+		let secret = rsa_key_pair_from_seed(seed)
+			.sk
+			.to_der()
+			.map_err(|e| format!("Encoding {}: {:?}", seed, e))?;
+		offchain.set(STORAGE_PREFIX, &key, &secret.encode());
+	}
+
+	Ok(())
+}
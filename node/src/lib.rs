@@ -1,3 +1,9 @@
 pub mod chain_spec;
+pub mod credentials;
+pub mod election;
+pub mod election_keys;
+pub mod results;
 pub mod rpc;
 pub mod service;
+pub mod verify_tally;
+pub mod voter;
@@ -7,7 +7,13 @@ mod service;
 mod benchmarking;
 mod cli;
 mod command;
+mod credentials;
+mod election;
+mod election_keys;
+mod results;
 mod rpc;
+mod verify_tally;
+mod voter;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
@@ -0,0 +1,844 @@
+//! Export of the election results.
+//!
+//! The results are read from the voting system storage of a block and rendered in one of the
+//! export formats:
+//!
+//! - `json`: a single `results.json` document,
+//! - `csv`: `tally.csv`, `ballots.csv` and `timeline.csv`,
+//! - `eml`: `results.eml.xml`, a count message (EML 510) in the style of the OASIS Election Markup
+//!   Language. The voting system does not know the candidate a commitment stands for, so the
+//!   selections are identified by their commitment.
+//!
+//! The ballot list holds every entry of the ballot log: ballots replaced later and credential
+//! ballots included. The turnout counts them, and the voting accounts that cast them. Time-locked
+//! ballots are listed without their commitment until the beacon signature of the timelock round
+//! is submitted, then they are decrypted with it like the tally does.
+//!
+//! The same export is available from the `export-results` command and the
+//! `votingSystem_exportResults` RPC. The RPC reads the results through the voting system runtime
+//! API, the summary in one call and the ballot log in pages of `BALLOT_LOG_PAGE` entries, so it is
+//! served by every node.
+//!
+//! Voters check that their ballot made it into the ballot log with the
+//! `votingSystem_ballotInclusionProof` RPC. The log is a Merkle mountain range over the ballot
+//! receipts: hashing the receipt up the path to the peak of its mountain, and bagging that peak
+//! with the other peaks, must give the root published at the end of voting, see
+//! `verify_inclusion_proof`. The proof is served by the voting system runtime API and reads one
+//! node per level of the log, so the RPC is safe.
+
+use codec::Decode;
+use frame_support::traits::PalletInfoAccess;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc, types::ErrorObjectOwned};
+use node_template_runtime::{
+	opaque::Block,
+	pallet_voting_system::{
+		runtime_api::VotingSystemApi as VotingSystemRuntimeApi, timelock, Ballot, BallotInclusion,
+		BallotLogEntry, CredentialBallot, ElectionSummary, TimelockedBallot,
+	},
+	AccountId, BlockNumber, Hash, VotingSystem,
+};
+use sc_cli::{CliConfiguration, DatabaseParams, Error, SharedParams};
+use sc_client_api::StorageProvider;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	crypto::Ss58Codec,
+	storage::{Storage, StorageKey},
+	H256,
+};
+use sp_runtime::{traits::Block as BlockT, BuildStorage};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fmt::Write,
+	path::PathBuf,
+	sync::Arc,
+};
+
+/// Export formats of the election results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultsFormat {
+	/// A single JSON document.
+	Json,
+	/// One CSV file each for the tally, the ballots and the timeline.
+	Csv,
+	/// An EML count message.
+	Eml,
+}
+
+/// Results of an election, as stored at a block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElectionResults {
+	election: String,
+	/// Block or snapshot the results were read from
+	source: String,
+	phase: Option<String>,
+	/// Winning commitment, set once the ballots are counted
+	winner: Option<u32>,
+	tally: Vec<CommitmentVotes>,
+	turnout: Turnout,
+	ballots: Vec<BallotEntry>,
+	timeline: Vec<PhaseChange>,
+	/// Candidates excluded from signing after the biased signing deadline
+	excluded_candidates: Vec<ExcludedCandidate>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CommitmentVotes {
+	commitment: u32,
+	votes: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Turnout {
+	registered_voters: u64,
+	/// Ballots in the ballot log, including replaced ballots and credential ballots
+	ballots_cast: u64,
+	/// Ballots replaced later by the same voting account
+	replaced_ballots: u64,
+	credential_ballots: u64,
+	timelocked_ballots: u64,
+	/// Time-locked ballots the tally rejected because they did not decrypt to a commitment
+	rejected_ballots: u64,
+	/// Voting accounts that cast at least one ballot
+	voting_accounts: u64,
+	/// Voting accounts as a share of the registered voters, in percent
+	percentage: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BallotEntry {
+	/// Position of the ballot in the ballot log
+	index: u64,
+	/// Anonymous voting account the ballot was cast from
+	voter: String,
+	/// Whether the ballot was cast with an encrypted credential
+	credential: bool,
+	/// Whether the commitment was encrypted to the timelock round
+	timelocked: bool,
+	/// Commitment of the ballot, time-locked ballots only have one once they are decrypted
+	commitment: Option<u32>,
+	/// Nonce of the ballot, credential ballots have none
+	nonce: Option<u64>,
+	/// Block the ballot was cast in
+	block: BlockNumber,
+	/// Receipt of the ballot, as emitted when it was cast
+	receipt: String,
+	/// Whether the voting account cast a ballot replacing this one
+	replaced: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExcludedCandidate {
+	candidate: String,
+	/// Block the candidate was excluded at
+	block: BlockNumber,
+	/// Voters the candidate signed, of the registered voters
+	signed: u64,
+	voters: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhaseChange {
+	phase: String,
+	block: BlockNumber,
+}
+
+impl ElectionResults {
+	/// Read the results from the voting system storage
+	pub fn read(storage: Storage, election: String, source: String) -> Result<Self, String> {
+		sp_io::TestExternalities::new(storage).execute_with(|| {
+			let summary = VotingSystem::election_summary();
+			let log = read_ballot_log(summary.ballot_log_length, |start| {
+				Ok(VotingSystem::ballot_log_page(start))
+			})?;
+			Ok(Self::new(election, source, summary, log))
+		})
+	}
+
+	/// Results of an election from its summary and its ballot log
+	pub fn new(
+		election: String,
+		source: String,
+		summary: ElectionSummary<AccountId, BlockNumber>,
+		log: Vec<BallotLogEntry<AccountId, Hash, BlockNumber>>,
+	) -> Self {
+		let tally = summary
+			.tally
+			.into_iter()
+			.map(|(commitment, votes)| CommitmentVotes { commitment, votes })
+			.collect();
+
+		// A ballot is replaced by any later ballot of the same voting account, credential ballots
+		// are all kept for the tally to filter
+		let timelocked = summary.timelock_round.is_some();
+		let last_ballots: BTreeMap<_, _> =
+			log.iter().enumerate().map(|(index, entry)| (&entry.voter, index)).collect();
+		let ballots: Vec<_> = log
+			.iter()
+			.enumerate()
+			.filter_map(|(index, entry)| {
+				let (commitment, nonce) = if summary.credentials {
+					let ballot = CredentialBallot::decode(&mut &entry.ballot[..]).ok()?;
+					(Some(ballot.commitment), None)
+				} else if timelocked {
+					let ballot = TimelockedBallot::decode(&mut &entry.ballot[..]).ok()?;
+					let commitment = summary.beacon_signature.as_ref().and_then(|signature| {
+						timelock::decrypt(signature, &ballot.ciphertext)
+							.and_then(|message| timelock::message_commitment(&message))
+					});
+					(commitment, Some(ballot.nonce))
+				} else {
+					let ballot = Ballot::decode(&mut &entry.ballot[..]).ok()?;
+					(Some(ballot.commitment), Some(ballot.nonce))
+				};
+				Some(BallotEntry {
+					index: index as u64,
+					voter: entry.voter.to_ss58check(),
+					credential: summary.credentials,
+					timelocked,
+					commitment,
+					nonce,
+					block: entry.block,
+					receipt: format!("{:?}", entry.receipt),
+					replaced: !summary.credentials && last_ballots[&entry.voter] != index,
+				})
+			})
+			.collect();
+
+		let registered_voters = summary.registered_voters;
+		let voting_accounts = log.iter().map(|entry| &entry.voter).collect::<BTreeSet<_>>().len();
+		let voting_accounts = voting_accounts as u64;
+		let percentage = if registered_voters == 0 {
+			0.0
+		} else {
+			voting_accounts as f64 * 100.0 / registered_voters as f64
+		};
+		let turnout = Turnout {
+			registered_voters,
+			ballots_cast: ballots.len() as u64,
+			replaced_ballots: ballots.iter().filter(|ballot| ballot.replaced).count() as u64,
+			credential_ballots: ballots.iter().filter(|ballot| ballot.credential).count() as u64,
+			timelocked_ballots: ballots.iter().filter(|ballot| ballot.timelocked).count() as u64,
+			rejected_ballots: summary.rejected_ballots as u64,
+			voting_accounts,
+			percentage,
+		};
+
+		let timeline = summary
+			.timeline
+			.into_iter()
+			.map(|(phase, block)| PhaseChange { phase: format!("{:?}", phase), block })
+			.collect();
+
+		let excluded_candidates = summary
+			.excluded_candidates
+			.into_iter()
+			.map(|(candidate, exclusion)| ExcludedCandidate {
+				candidate: candidate.to_ss58check(),
+				block: exclusion.excluded,
+				signed: exclusion.signed,
+				voters: exclusion.voters,
+			})
+			.collect();
+
+		ElectionResults {
+			election,
+			source,
+			phase: summary.phase.map(|phase| format!("{:?}", phase)),
+			winner: summary.winner,
+			tally,
+			turnout,
+			ballots,
+			timeline,
+			excluded_candidates,
+		}
+	}
+
+	/// Render the results, returns the content of every file of the export by file name
+	pub fn render(&self, format: ResultsFormat) -> Result<BTreeMap<String, String>, String> {
+		let mut files = BTreeMap::new();
+		match format {
+			ResultsFormat::Json => {
+				let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+				files.insert("results.json".into(), json);
+			},
+			ResultsFormat::Csv => {
+				files.insert("tally.csv".into(), self.tally_csv());
+				files.insert("ballots.csv".into(), self.ballots_csv());
+				files.insert("timeline.csv".into(), self.timeline_csv());
+			},
+			ResultsFormat::Eml => {
+				files.insert("results.eml.xml".into(), self.eml());
+			},
+		}
+		Ok(files)
+	}
+
+	fn tally_csv(&self) -> String {
+		let mut csv = String::from("commitment,votes,winner\n");
+		for entry in &self.tally {
+			let winner = self.winner == Some(entry.commitment);
+			let _ = writeln!(csv, "{},{},{}", entry.commitment, entry.votes, winner);
+		}
+		csv
+	}
+
+	fn ballots_csv(&self) -> String {
+		let mut csv = String::from(
+			"index,voter,credential,timelocked,commitment,nonce,block,receipt,replaced\n",
+		);
+		for ballot in &self.ballots {
+			let commitment = ballot.commitment.map(|commitment| commitment.to_string());
+			let nonce = ballot.nonce.map(|nonce| nonce.to_string()).unwrap_or_default();
+			let _ = writeln!(
+				csv,
+				"{},{},{},{},{},{},{},{},{}",
+				ballot.index,
+				ballot.voter,
+				ballot.credential,
+				ballot.timelocked,
+				commitment.unwrap_or_default(),
+				nonce,
+				ballot.block,
+				ballot.receipt,
+				ballot.replaced
+			);
+		}
+		csv
+	}
+
+	fn timeline_csv(&self) -> String {
+		let mut csv = String::from("phase,block\n");
+		for change in &self.timeline {
+			let _ = writeln!(csv, "{},{}", change.phase, change.block);
+		}
+		csv
+	}
+
+	fn eml(&self) -> String {
+		let mut xml = String::new();
+		let _ = writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+		let _ = writeln!(
+			xml,
+			r#"<EML xmlns="urn:oasis:names:tc:evs:schema:eml" Id="510" SchemaVersion="5.0">"#
+		);
+		let _ = writeln!(xml, "  <Count>");
+		let _ = writeln!(
+			xml,
+			r#"    <EventIdentifier><EventName>{}</EventName></EventIdentifier>"#,
+			xml_escape(&self.election)
+		);
+		let _ = writeln!(xml, "    <Election>");
+		let _ = writeln!(
+			xml,
+			r#"      <ElectionIdentifier><ElectionName>{}</ElectionName></ElectionIdentifier>"#,
+			xml_escape(&self.election)
+		);
+		let _ = writeln!(xml, "      <Contests>");
+		let _ = writeln!(xml, "        <Contest>");
+		let _ = writeln!(xml, r#"          <ContestIdentifier Id="1"/>"#);
+		let _ = writeln!(xml, "          <TotalVotes>");
+		for entry in &self.tally {
+			let elected = if self.winner == Some(entry.commitment) { "yes" } else { "no" };
+			let _ = writeln!(xml, "            <Selection>");
+			let _ = writeln!(xml, "              <Candidate>");
+			let _ = writeln!(
+				xml,
+				r#"                <CandidateIdentifier Id="{}"/>"#,
+				entry.commitment
+			);
+			let _ = writeln!(xml, "                <Elected>{}</Elected>", elected);
+			let _ = writeln!(xml, "              </Candidate>");
+			let _ = writeln!(xml, "              <ValidVotes>{}</ValidVotes>", entry.votes);
+			let _ = writeln!(xml, "            </Selection>");
+		}
+		let counted: u32 = self.tally.iter().map(|entry| entry.votes).sum();
+		// Replaced ballots are not cast any more, credential ballots stay cast until the tally
+		// filters them
+		let cast = self.turnout.ballots_cast - self.turnout.replaced_ballots;
+		let _ = writeln!(xml, "            <Cast>{}</Cast>", cast);
+		if self.turnout.rejected_ballots > 0 {
+			let _ = writeln!(
+				xml,
+				"            <RejectedVotes>{}</RejectedVotes>",
+				self.turnout.rejected_ballots
+			);
+		}
+		let _ = writeln!(xml, "            <TotalCounted>{}</TotalCounted>", counted);
+		let _ = writeln!(xml, "          </TotalVotes>");
+		let _ = writeln!(xml, "        </Contest>");
+		let _ = writeln!(xml, "      </Contests>");
+		let _ = writeln!(xml, "    </Election>");
+		let _ = writeln!(xml, "  </Count>");
+		let _ = writeln!(xml, "</EML>");
+		xml
+	}
+}
+
+/// Read the `length` entries of the ballot log one page at a time
+pub fn read_ballot_log<F>(
+	length: u64,
+	mut page: F,
+) -> Result<Vec<BallotLogEntry<AccountId, Hash, BlockNumber>>, String>
+where
+	F: FnMut(u64) -> Result<Vec<BallotLogEntry<AccountId, Hash, BlockNumber>>, String>,
+{
+	let mut log = Vec::new();
+	while (log.len() as u64) < length {
+		let entries = page(log.len() as u64)?;
+		if entries.is_empty() {
+			return Err(format!("The ballot log ends at {} of {} entries", log.len(), length))
+		}
+		log.extend(entries);
+	}
+	Ok(log)
+}
+
+fn xml_escape(text: &str) -> String {
+	text.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+		.replace('\'', "&apos;")
+}
+
+/// Read the voting system storage of a block
+pub fn voting_system_storage<C, B>(
+	client: &C,
+	at: <Block as BlockT>::Hash,
+) -> Result<Storage, String>
+where
+	C: StorageProvider<Block, B>,
+	B: sc_client_api::Backend<Block>,
+{
+	let prefix = StorageKey(sp_core::twox_128(VotingSystem::name().as_bytes()).to_vec());
+	let top = client
+		.storage_pairs(at, &prefix)
+		.map_err(|e| format!("Reading the voting system storage: {}", e))?
+		.into_iter()
+		.map(|(key, value)| (key.0, value.0))
+		.collect();
+	Ok(Storage { top, children_default: Default::default() })
+}
+
+/// The `export-results` command used to export the results of an election.
+#[derive(Debug, clap::Parser)]
+pub struct ExportResultsCmd {
+	/// Export format.
+	#[arg(long, value_enum, default_value_t = ResultsFormat::Json)]
+	format: ResultsFormat,
+
+	/// Directory the exported files are written to.
+	#[arg(long, value_name = "DIR")]
+	output: PathBuf,
+
+	/// Export an `export-state` snapshot instead of the best block of the chain database.
+	#[arg(long, value_name = "FILE")]
+	pub snapshot: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	database_params: DatabaseParams,
+}
+
+impl ExportResultsCmd {
+	/// Export the results of the exported snapshot
+	pub fn run_snapshot(&self) -> Result<(), Error> {
+		let path = self.snapshot.as_ref().ok_or("No snapshot given")?;
+		let chain_spec = crate::chain_spec::ChainSpec::from_json_file(path.clone())?;
+		let storage = chain_spec.build_storage()?;
+		let source = format!("snapshot {}", path.display());
+
+		self.write(ElectionResults::read(storage, chain_spec.name().into(), source)?)
+	}
+
+	/// Export the results at the best block of the chain database
+	pub fn run<C, B>(&self, client: Arc<C>, election: String) -> Result<(), Error>
+	where
+		C: StorageProvider<Block, B> + HeaderBackend<Block>,
+		B: sc_client_api::Backend<Block>,
+	{
+		let info = client.info();
+		let storage = voting_system_storage(&*client, info.best_hash)?;
+		let source = format!("block #{} ({:?})", info.best_number, info.best_hash);
+
+		self.write(ElectionResults::read(storage, election, source)?)
+	}
+
+	fn write(&self, results: ElectionResults) -> Result<(), Error> {
+		std::fs::create_dir_all(&self.output)?;
+		for (name, content) in results.render(self.format)? {
+			let path = self.output.join(name);
+			std::fs::write(&path, content)?;
+			println!("Results written to {}", path.display());
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportResultsCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+/// Inclusion proof of a ballot in the ballot log, as returned by the RPC.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BallotInclusionProof {
+	/// Position of the ballot in the log
+	index: u64,
+	/// SS58 address of the voting account
+	voter: String,
+	/// SCALE encoded ballot
+	ballot: String,
+	block: BlockNumber,
+	receipt: H256,
+	/// Number of ballots in the log the proof leads to
+	length: u64,
+	/// Siblings on the path from the receipt to the peak of its mountain, lowest first
+	path: Vec<H256>,
+	/// Peaks of the other mountains, highest mountain first
+	peaks: Vec<H256>,
+	/// Root the proof leads to
+	root: H256,
+	/// Whether `root` is the root published at the end of voting or the current one
+	published: bool,
+}
+
+/// Voting system RPC methods.
+#[rpc(client, server)]
+pub trait VotingSystemApi<BlockHash> {
+	/// Export the election results at the given block, or the best block. Returns the content of
+	/// every file of the export by file name.
+	#[method(name = "votingSystem_exportResults")]
+	fn export_results(
+		&self,
+		format: ResultsFormat,
+		at: Option<BlockHash>,
+	) -> RpcResult<BTreeMap<String, String>>;
+
+	/// Prove that the ballot with the given receipt is part of the ballot log at the given block,
+	/// or the best block. Returns nothing when the log holds no such ballot.
+	#[method(name = "votingSystem_ballotInclusionProof")]
+	fn ballot_inclusion_proof(
+		&self,
+		receipt: H256,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<BallotInclusionProof>>;
+}
+
+/// Implementation of the voting system RPC methods.
+pub struct VotingSystemRpc<C> {
+	client: Arc<C>,
+	election: String,
+}
+
+impl<C> VotingSystemRpc<C> {
+	/// Create new `VotingSystemRpc` with the given reference to the client.
+	pub fn new(client: Arc<C>, election: String) -> Self {
+		Self { client, election }
+	}
+}
+
+impl<C> VotingSystemApiServer<<Block as BlockT>::Hash> for VotingSystemRpc<C>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: VotingSystemRuntimeApi<Block, AccountId, Hash, BlockNumber>,
+{
+	fn export_results(
+		&self,
+		format: ResultsFormat,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<BTreeMap<String, String>> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let api = self.client.runtime_api();
+		let summary = api.election_summary(at).map_err(runtime_api_error).map_err(rpc_error)?;
+		let log = read_ballot_log(summary.ballot_log_length, |start| {
+			api.ballot_log_page(at, start).map_err(runtime_api_error)
+		})
+		.map_err(rpc_error)?;
+
+		let results =
+			ElectionResults::new(self.election.clone(), format!("{:?}", at), summary, log);
+		results.render(format).map_err(rpc_error)
+	}
+
+	fn ballot_inclusion_proof(
+		&self,
+		receipt: H256,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<BallotInclusionProof>> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let inclusion = self
+			.client
+			.runtime_api()
+			.ballot_inclusion(at, receipt)
+			.map_err(runtime_api_error)
+			.map_err(rpc_error)?;
+
+		Ok(inclusion.map(|BallotInclusion { entry, proof, root, published }| {
+			BallotInclusionProof {
+				index: proof.index,
+				voter: entry.voter.to_ss58check(),
+				ballot: format!("0x{}", hex::encode(entry.ballot)),
+				block: entry.block,
+				receipt: entry.receipt,
+				length: proof.length,
+				path: proof.path,
+				peaks: proof.peaks,
+				root,
+				published,
+			}
+		}))
+	}
+}
+
+fn rpc_error(message: String) -> ErrorObjectOwned {
+	ErrorObjectOwned::owned(1, message, None::<()>)
+}
+
+fn runtime_api_error(error: sp_api::ApiError) -> String {
+	format!("Calling the voting system runtime API: {}", error)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use node_template_runtime::pallet_voting_system::{
+		credentials::Ciphertext, ElectionPhase, Exclusion,
+	};
+
+	fn account(byte: u8) -> AccountId {
+		AccountId::new([byte; 32])
+	}
+
+	fn entry(
+		voter: u8,
+		ballot: Vec<u8>,
+		block: BlockNumber,
+	) -> BallotLogEntry<AccountId, Hash, BlockNumber> {
+		BallotLogEntry {
+			voter: account(voter),
+			ballot,
+			receipt: H256::repeat_byte(block as u8),
+			block,
+		}
+	}
+
+	fn ballot(commitment: u32, nonce: u64) -> Vec<u8> {
+		Ballot { commitment, signature: vec![], nonce }.encode()
+	}
+
+	fn summary(credentials: bool, length: u64) -> ElectionSummary<AccountId, BlockNumber> {
+		ElectionSummary {
+			phase: Some(ElectionPhase::Completed),
+			winner: Some(2),
+			tally: vec![(1, 0), (2, 2)],
+			registered_voters: 4,
+			credentials,
+			timelock_round: None,
+			beacon_signature: None,
+			rejected_ballots: 0,
+			ballot_log_length: length,
+			timeline: vec![(ElectionPhase::Voting, 10), (ElectionPhase::Counting, 20)],
+			excluded_candidates: vec![(
+				account(9),
+				Exclusion { excluded: 5, signed: 1, voters: 4 },
+			)],
+		}
+	}
+
+	/// Voter 1 votes for 1 and replaces it with 2, voter 2 votes for 2
+	fn results() -> ElectionResults {
+		let log = vec![
+			entry(1, ballot(1, 1), 11),
+			entry(2, ballot(2, 1), 12),
+			entry(1, ballot(2, 2), 13),
+		];
+		ElectionResults::new("Board & <Council>".into(), "block #20".into(), summary(false, 3), log)
+	}
+
+	#[test]
+	fn json_holds_the_log_and_the_turnout() {
+		let files = results().render(ResultsFormat::Json).unwrap();
+		assert_eq!(files.keys().collect::<Vec<_>>(), vec!["results.json"]);
+
+		let json: serde_json::Value = serde_json::from_str(&files["results.json"]).unwrap();
+		assert_eq!(json["election"], "Board & <Council>");
+		assert_eq!(json["phase"], "Completed");
+		assert_eq!(json["winner"], 2);
+		assert_eq!(json["tally"][1], serde_json::json!({ "commitment": 2, "votes": 2 }));
+		assert_eq!(
+			json["turnout"],
+			serde_json::json!({
+				"registeredVoters": 4,
+				"ballotsCast": 3,
+				"replacedBallots": 1,
+				"credentialBallots": 0,
+				"timelockedBallots": 0,
+				"rejectedBallots": 0,
+				"votingAccounts": 2,
+				"percentage": 50.0,
+			})
+		);
+		let ballots = json["ballots"].as_array().unwrap();
+		assert_eq!(ballots.len(), 3);
+		assert_eq!(ballots[0]["replaced"], true);
+		assert_eq!(ballots[2]["replaced"], false);
+		assert_eq!(ballots[2]["nonce"], 2);
+		assert_eq!(ballots[2]["voter"], account(1).to_ss58check());
+		assert_eq!(json["timeline"][1], serde_json::json!({ "phase": "Counting", "block": 20 }));
+		assert_eq!(json["excludedCandidates"][0]["signed"], 1);
+	}
+
+	#[test]
+	fn csv_has_one_line_per_row() {
+		let files = results().render(ResultsFormat::Csv).unwrap();
+		assert_eq!(
+			files.keys().collect::<Vec<_>>(),
+			vec!["ballots.csv", "tally.csv", "timeline.csv"]
+		);
+
+		assert_eq!(files["tally.csv"], "commitment,votes,winner\n1,0,false\n2,2,true\n");
+		assert_eq!(files["timeline.csv"], "phase,block\nVoting,10\nCounting,20\n");
+		let ballots: Vec<_> = files["ballots.csv"].lines().collect();
+		assert_eq!(ballots.len(), 4);
+		assert_eq!(
+			ballots[0],
+			"index,voter,credential,timelocked,commitment,nonce,block,receipt,replaced"
+		);
+		assert_eq!(
+			ballots[1],
+			format!(
+				"0,{},false,false,1,1,11,{:?},true",
+				account(1).to_ss58check(),
+				H256::repeat_byte(11)
+			)
+		);
+		assert!(ballots[3].ends_with(",false"));
+	}
+
+	#[test]
+	fn eml_counts_the_ballots_still_cast() {
+		let files = results().render(ResultsFormat::Eml).unwrap();
+		let xml = &files["results.eml.xml"];
+
+		assert!(xml.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+		assert!(xml.contains("<EventName>Board &amp; &lt;Council&gt;</EventName>"));
+		assert!(xml.contains(r#"<CandidateIdentifier Id="2"/>"#));
+		assert_eq!(xml.matches("<Elected>yes</Elected>").count(), 1);
+		// The replaced ballot of voter 1 is no longer cast
+		assert!(xml.contains("<Cast>2</Cast>"));
+		assert!(xml.contains("<TotalCounted>2</TotalCounted>"));
+		assert!(xml.trim_end().ends_with("</EML>"));
+	}
+
+	#[test]
+	fn credential_ballots_are_listed_and_never_replaced() {
+		let credential_ballot = |commitment| {
+			CredentialBallot {
+				commitment,
+				credential: Ciphertext { a: [1; 256], b: [2; 256] },
+				signature: vec![],
+			}
+			.encode()
+		};
+		let log = vec![entry(1, credential_ballot(2), 11), entry(1, credential_ballot(1), 12)];
+		let results =
+			ElectionResults::new("Board".into(), "block #20".into(), summary(true, 2), log);
+
+		let csv = &results.render(ResultsFormat::Csv).unwrap()["ballots.csv"];
+		let voter = account(1).to_ss58check();
+		assert_eq!(
+			csv.lines().skip(1).collect::<Vec<_>>(),
+			vec![
+				format!("0,{},true,false,2,,11,{:?},false", voter, H256::repeat_byte(11)),
+				format!("1,{},true,false,1,,12,{:?},false", voter, H256::repeat_byte(12)),
+			]
+		);
+
+		let json = &results.render(ResultsFormat::Json).unwrap()["results.json"];
+		let json: serde_json::Value = serde_json::from_str(json).unwrap();
+		assert_eq!(json["turnout"]["ballotsCast"], 2);
+		assert_eq!(json["turnout"]["credentialBallots"], 2);
+		assert_eq!(json["turnout"]["replacedBallots"], 0);
+		assert_eq!(json["turnout"]["votingAccounts"], 1);
+		assert_eq!(json["ballots"][0]["nonce"], serde_json::Value::Null);
+	}
+
+	#[test]
+	fn timelocked_ballots_are_decrypted_with_the_beacon_signature() {
+		let secret = [7; 32];
+		let public_key = timelock::public_key(&secret).unwrap();
+		let timelocked_ballot = |commitment| {
+			let message = timelock::commitment_message(commitment);
+			let ciphertext =
+				timelock::encrypt(&public_key, 42, &message, &mut rand::thread_rng()).unwrap();
+			TimelockedBallot { ciphertext, signature: vec![], nonce: 1 }.encode()
+		};
+		let log = vec![entry(1, timelocked_ballot(2), 11), entry(2, timelocked_ballot(1), 12)];
+		let mut summary = summary(false, 2);
+		summary.timelock_round = Some(42);
+
+		// Before the beacon signed the round nobody knows the commitments
+		let locked =
+			ElectionResults::new("Board".into(), "block #15".into(), summary.clone(), log.clone());
+		let csv = &locked.render(ResultsFormat::Csv).unwrap()["ballots.csv"];
+		assert_eq!(
+			csv.lines().nth(1).unwrap(),
+			format!(
+				"0,{},false,true,,1,11,{:?},false",
+				account(1).to_ss58check(),
+				H256::repeat_byte(11)
+			)
+		);
+
+		summary.beacon_signature = Some(timelock::sign(&secret, 42).unwrap().to_vec());
+		summary.rejected_ballots = 1;
+		let results = ElectionResults::new("Board".into(), "block #20".into(), summary, log);
+		let json = &results.render(ResultsFormat::Json).unwrap()["results.json"];
+		let json: serde_json::Value = serde_json::from_str(json).unwrap();
+		assert_eq!(json["ballots"][0]["commitment"], 2);
+		assert_eq!(json["ballots"][1]["commitment"], 1);
+		assert_eq!(json["ballots"][1]["timelocked"], true);
+		assert_eq!(json["turnout"]["timelockedBallots"], 2);
+		let xml = &results.render(ResultsFormat::Eml).unwrap()["results.eml.xml"];
+		assert!(xml.contains("<RejectedVotes>1</RejectedVotes>"));
+	}
+
+	#[test]
+	fn ballot_log_is_read_page_by_page() {
+		let log: Vec<_> =
+			(0..5).map(|block| entry(1, ballot(1, block as u64 + 1), block)).collect();
+		let mut pages = 0;
+		let read = read_ballot_log(5, |start| {
+			pages += 1;
+			Ok(log.iter().skip(start as usize).take(2).cloned().collect())
+		})
+		.unwrap();
+		assert_eq!((read, pages), (log.clone(), 3));
+
+		assert!(read_ballot_log(6, |start| {
+			Ok(log.iter().skip(start as usize).take(2).cloned().collect())
+		})
+		.is_err());
+	}
+}
@@ -1,12 +1,12 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
 use node_template_runtime::{self, opaque::Block, RuntimeApi};
-use sc_client_api::BlockBackend;
+use sc_client_api::{Backend, BlockBackend};
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
 pub use sc_executor::NativeElseWasmExecutor;
 use sc_finality_grandpa::SharedVoterState;
 use sc_keystore::LocalKeystore;
-use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
+use sc_service::{error::Error as ServiceError, ChainType, Configuration, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
 use std::{sync::Arc, time::Duration};
@@ -156,7 +156,13 @@ fn remote_keystore(_url: &str) -> Result<Arc<LocalKeystore>, &'static str> {
 }
 
 /// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+///
+/// `dev_candidate` is the seed of the development candidate the node signs for, see
+/// `election_keys::DEV_CANDIDATE_SEEDS`.
+pub fn new_full(
+	mut config: Configuration,
+	dev_candidate: Option<String>,
+) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -168,6 +174,34 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		other: (block_import, grandpa_link, mut telemetry),
 	} = new_partial(&config)?;
 
+	// The development chain derives its election keys from well-known seeds and writes the
+	// private keys next to the chain data so candidates and the CA can use them. A node started
+	// with `--dev-candidate` lets its offchain worker sign for that candidate
+	if config.chain_spec.chain_type() == ChainType::Development {
+		if let Some(base_path) = &config.base_path {
+			let key_dir = base_path
+				.config_dir(config.chain_spec.id())
+				.join(crate::election_keys::DEV_KEY_DIR);
+			crate::election_keys::write_dev_keys(&key_dir).map_err(ServiceError::Other)?;
+			log::info!("🔑 Development election keys written to {}", key_dir.display());
+		}
+		if let (Some(seed), Some(mut offchain_storage)) =
+			(&dev_candidate, backend.offchain_storage())
+		{
+			crate::election_keys::insert_dev_signing_key(
+				&*keystore_container.sync_keystore(),
+				&mut offchain_storage,
+				seed,
+			)
+			.map_err(ServiceError::Other)?;
+			log::info!("🔑 Signing for the development candidate {}", seed);
+		}
+	} else if dev_candidate.is_some() {
+		return Err(ServiceError::Other(
+			"--dev-candidate is only supported on the development chain".into(),
+		))
+	}
+
 	if let Some(url) = &config.keystore_remote {
 		match remote_keystore(url) {
 			Ok(k) => keystore_container.set_remote_keystore(k),
@@ -223,10 +257,15 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
+		let election = config.chain_spec.name().to_string();
 
 		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				election: election.clone(),
+			};
 			crate::rpc::create_full(deps).map_err(Into::into)
 		})
 	};
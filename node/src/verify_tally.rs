@@ -0,0 +1,423 @@
+//! Independent verification of the election tally.
+//!
+//! `tally_step` counts every stored ballot and picks the winner on chain. `verify-tally` repeats
+//! that work from the voting system storage, read from the chain database or from an
+//! `export-state` snapshot, without trusting the result:
+//!
+//! - the signature set kept with every ballot is verified again against the candidate keys,
+//! - the votes of the valid ballots are counted and compared with `CandidateVoteCount`,
+//! - the winner of the recount is compared with the stored winner.
+//!
+//! The tally of a coercion resistant election is counted from the credential ballots by the holder
+//! of the tally secret, without the secret it can not be recounted. Instead the transcript written
+//! by `election tally-credentials` is checked against the hash submitted with the tally and
+//! verified against the stored credentials and credential ballots, and the votes it proves are
+//! compared. Without a valid transcript the tally can not be verified and the verification fails.
+//!
+//! The report is written as JSON and signed with the sr25519 key of the observer. The signature
+//! covers the compact JSON encoding of the `report` field.
+
+use crate::{results::voting_system_storage, service::FullClient};
+use codec::Decode;
+use node_template_runtime::{
+	pallet_voting_system::{
+		credentials::{self, TallyTranscript},
+		Ballots, CandidateVoteCount,
+	},
+	Runtime, VotingSystem,
+};
+use sc_cli::{CliConfiguration, DatabaseParams, Error, SharedParams};
+use serde::Serialize;
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	crypto::{Pair as _, Ss58Codec},
+	sr25519,
+	storage::Storage,
+};
+use sp_runtime::BuildStorage;
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+	sync::Arc,
+};
+
+/// The `verify-tally` command used to audit the tally of an election.
+#[derive(Debug, clap::Parser)]
+pub struct VerifyTallyCmd {
+	/// Verify an `export-state` snapshot instead of the best block of the chain database.
+	#[arg(long, value_name = "FILE")]
+	pub snapshot: Option<PathBuf>,
+
+	/// Secret URI of the sr25519 key signing the report.
+	#[arg(long)]
+	suri: String,
+
+	/// File the report is written to, printed when missing.
+	#[arg(long, value_name = "FILE")]
+	output: Option<PathBuf>,
+
+	/// Transcript proving the tally of a coercion resistant election, written by `election
+	/// tally-credentials`. Required to verify the tally of such an election.
+	#[arg(long, value_name = "FILE")]
+	credential_transcript: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	database_params: DatabaseParams,
+}
+
+/// Result of the tally verification.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TallyReport {
+	/// Block or snapshot the storage was read from
+	source: String,
+	phase: Option<String>,
+	ballots: u32,
+	invalid_ballots: Vec<InvalidBallot>,
+	/// Credential ballots counted according to the verified transcript
+	kept_credential_ballots: Option<u32>,
+	/// Votes per commitment counted from the valid ballots
+	recounted_votes: BTreeMap<u32, u32>,
+	/// Votes per commitment stored in `CandidateVoteCount`
+	on_chain_votes: BTreeMap<u32, u32>,
+	/// Commitment with the most votes in the recount, the lowest one on a tie
+	recounted_winner: u32,
+	on_chain_winner: Option<u32>,
+	discrepancies: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InvalidBallot {
+	voter: String,
+	commitment: u32,
+	reason: String,
+}
+
+#[derive(Serialize)]
+struct SignedReport {
+	report: TallyReport,
+	/// SS58 address of the observer
+	signer: String,
+	signature: String,
+}
+
+impl VerifyTallyCmd {
+	/// Verify the tally of the exported snapshot
+	pub fn run_snapshot(&self) -> Result<(), Error> {
+		let path = self.snapshot.as_ref().ok_or("No snapshot given")?;
+		let chain_spec = crate::chain_spec::ChainSpec::from_json_file(path.clone())?;
+		let storage = chain_spec.build_storage()?;
+
+		self.verify(storage, format!("snapshot {}", path.display()))
+	}
+
+	/// Verify the tally at the best block of the chain database
+	pub fn run(&self, client: Arc<FullClient>) -> Result<(), Error> {
+		let info = client.info();
+		let storage = voting_system_storage(&*client, info.best_hash)?;
+
+		self.verify(storage, format!("block #{} ({:?})", info.best_number, info.best_hash))
+	}
+
+	fn verify(&self, storage: Storage, source: String) -> Result<(), Error> {
+		let pair = sr25519::Pair::from_string(&self.suri, None)
+			.map_err(|e| Error::Input(format!("Invalid secret URI: {:?}", e)))?;
+
+		let transcript = match &self.credential_transcript {
+			Some(path) => Some(
+				TallyTranscript::decode(&mut std::fs::read(path)?.as_slice())
+					.map_err(|e| format!("Invalid transcript in {}: {}", path.display(), e))?,
+			),
+			None => None,
+		};
+
+		let report =
+			sp_io::TestExternalities::new(storage).execute_with(|| recount(source, transcript));
+		let discrepancies = report.discrepancies.len();
+
+		let signature = pair.sign(&serde_json::to_vec(&report).map_err(|e| e.to_string())?);
+		let signed = SignedReport {
+			report,
+			signer: pair.public().to_ss58check(),
+			signature: format!("0x{}", hex::encode(signature)),
+		};
+		let json = serde_json::to_string_pretty(&signed).map_err(|e| e.to_string())?;
+		match &self.output {
+			Some(path) => std::fs::write(path, json)?,
+			None => println!("{}", json),
+		}
+
+		if discrepancies > 0 {
+			return Err(format!("The tally has {} discrepancies", discrepancies).into())
+		}
+		Ok(())
+	}
+}
+
+/// Recount the ballots in the current externalities and compare the result with the stored tally
+fn recount(source: String, transcript: Option<TallyTranscript>) -> TallyReport {
+	let mut ballots = 0;
+	let mut invalid_ballots = Vec::new();
+	let mut recounted_votes = BTreeMap::<u32, u32>::new();
+	for (voter, ballot) in Ballots::<Runtime>::iter() {
+		ballots += 1;
+		match VotingSystem::verify_ballot(&voter, &ballot) {
+			Ok(()) => *recounted_votes.entry(ballot.commitment).or_default() += 1,
+			Err(e) => invalid_ballots.push(InvalidBallot {
+				voter: voter.to_ss58check(),
+				commitment: ballot.commitment,
+				reason: format!("{:?}", e),
+			}),
+		}
+	}
+
+	let mut discrepancies = Vec::new();
+	for ballot in &invalid_ballots {
+		discrepancies.push(format!("Ballot of {} is invalid: {}", ballot.voter, ballot.reason));
+	}
+
+	// Credential ballots can only be filtered with the tally secret, see `election
+	// tally-credentials`. Once the tally is submitted its transcript tells which votes count
+	let credential_mode = VotingSystem::credential_key().is_some();
+	let submitted = VotingSystem::credential_tally_transcript().is_some();
+	let kept_credential_ballots = if credential_mode && submitted {
+		verify_transcript(transcript, &mut recounted_votes, &mut discrepancies)
+	} else {
+		None
+	};
+
+	// Same rule as the pallet: the most votes win, a tie goes to the lowest commitment and
+	// commitment 0 wins when no vote was counted. The map iterates the commitments in ascending
+	// order, so only a strictly larger count replaces the winner
+	let (recounted_winner, _) =
+		recounted_votes.iter().fold((0, 0), |(winner, most), (commitment, votes)| {
+			if *votes > most {
+				(*commitment, *votes)
+			} else {
+				(winner, most)
+			}
+		});
+
+	let on_chain_votes: BTreeMap<u32, u32> = CandidateVoteCount::<Runtime>::iter().collect();
+	let on_chain_winner = VotingSystem::get_winner();
+
+	// Before the last tally step there is no complete stored tally to compare with, an
+	// unverified credential tally is already a discrepancy
+	let comparable = !credential_mode || kept_credential_ballots.is_some();
+	if let Some(winner) = on_chain_winner.filter(|_| comparable) {
+		let commitments = recounted_votes.keys().chain(on_chain_votes.keys());
+		for commitment in commitments.collect::<BTreeSet<_>>() {
+			let recounted = recounted_votes.get(commitment).copied().unwrap_or(0);
+			let on_chain = on_chain_votes.get(commitment).copied().unwrap_or(0);
+			if recounted != on_chain {
+				discrepancies.push(format!(
+					"Commitment {} has {} votes on chain but {} in the recount",
+					commitment, on_chain, recounted
+				));
+			}
+		}
+		if winner != recounted_winner {
+			discrepancies.push(format!(
+				"Commitment {} won on chain but {} won the recount",
+				winner, recounted_winner
+			));
+		}
+	}
+
+	TallyReport {
+		source,
+		phase: VotingSystem::get_phase().map(|phase| format!("{:?}", phase)),
+		ballots,
+		invalid_ballots,
+		kept_credential_ballots,
+		recounted_votes,
+		on_chain_votes,
+		recounted_winner,
+		on_chain_winner,
+		discrepancies,
+	}
+}
+
+/// Verify the transcript of the credential tally and count the votes it proves. Returns the number
+/// of counted credential ballots, `None` when the tally can not be verified
+fn verify_transcript(
+	transcript: Option<TallyTranscript>,
+	recounted_votes: &mut BTreeMap<u32, u32>,
+	discrepancies: &mut Vec<String>,
+) -> Option<u32> {
+	let transcript = match transcript {
+		Some(transcript) => transcript,
+		None => {
+			discrepancies.push(
+				"The credential tally can not be verified without its transcript, pass \
+				 --credential-transcript"
+					.into(),
+			);
+			return None
+		},
+	};
+	if VotingSystem::credential_tally_transcript() !=
+		Some(VotingSystem::credential_transcript_hash(&transcript))
+	{
+		discrepancies.push("The transcript is not the one submitted with the tally".into());
+		return None
+	}
+
+	let tally_key = VotingSystem::credential_key()?;
+	let (registered, ballots) = VotingSystem::credential_tally_inputs();
+	match credentials::verify_tally(&tally_key, &registered, &ballots, &transcript) {
+		Ok(tally) => {
+			let mut kept = 0;
+			for (commitment, votes) in tally {
+				*recounted_votes.entry(commitment).or_default() += votes;
+				kept += votes;
+			}
+			Some(kept)
+		},
+		Err(e) => {
+			discrepancies.push(format!("The transcript does not prove the tally: {:?}", e));
+			None
+		},
+	}
+}
+
+impl CliConfiguration for VerifyTallyCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use clap::Parser;
+	use codec::Encode;
+	use node_template_runtime::{
+		pallet_voting_system::{
+			Ballot, BlindSignature, CandidatesCount, ElectionPhase, Phase, VoterWinner,
+		},
+		AccountId,
+	};
+
+	fn account(byte: u8) -> AccountId {
+		AccountId::new([byte; 32])
+	}
+
+	/// Storage of a counted election without candidate signatures, voters 1 and 2 voted for
+	/// commitment 1. The stored tally is `on_chain_votes` and the stored winner `winner`
+	fn counted_election(on_chain_votes: &[(u32, u32)], winner: u32) -> Storage {
+		let mut storage = Storage::default();
+		let mut insert = |key: Vec<u8>, value: Vec<u8>| storage.top.insert(key, value);
+
+		insert(Phase::<Runtime>::hashed_key().to_vec(), ElectionPhase::Counting.encode());
+		// Without candidates the empty signature set is the valid one
+		insert(CandidatesCount::<Runtime>::hashed_key().to_vec(), 0u64.encode());
+		for voter in [1, 2] {
+			let ballot = Ballot {
+				commitment: 1,
+				signature: Vec::<(AccountId, BlindSignature)>::new().encode(),
+				nonce: 1,
+			};
+			insert(Ballots::<Runtime>::hashed_key_for(account(voter)), ballot.encode());
+		}
+		for (commitment, votes) in on_chain_votes {
+			insert(CandidateVoteCount::<Runtime>::hashed_key_for(commitment), votes.encode());
+		}
+		insert(VoterWinner::<Runtime>::hashed_key().to_vec(), winner.encode());
+		storage
+	}
+
+	fn report_dir(name: &str) -> PathBuf {
+		let dir =
+			std::env::temp_dir().join(format!("verify-tally-{}-{}", name, std::process::id()));
+		let _ = std::fs::remove_dir_all(&dir);
+		std::fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	fn command(output: &std::path::Path) -> VerifyTallyCmd {
+		VerifyTallyCmd::parse_from([
+			"verify-tally".into(),
+			"--suri".into(),
+			"//Alice".into(),
+			"--output".into(),
+			output.display().to_string(),
+		])
+	}
+
+	fn read_report(path: &std::path::Path) -> serde_json::Value {
+		serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+	}
+
+	#[test]
+	fn a_matching_tally_verifies() {
+		let dir = report_dir("match");
+		let output = dir.join("report.json");
+		command(&output).verify(counted_election(&[(1, 2)], 1), "test".into()).unwrap();
+
+		let report = &read_report(&output)["report"];
+		assert_eq!(report["ballots"], 2);
+		assert_eq!(report["recountedVotes"], serde_json::json!({ "1": 2 }));
+		assert_eq!(report["recountedWinner"], 1);
+		assert_eq!(report["discrepancies"], serde_json::json!([]));
+
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn a_tally_that_does_not_match_the_recount_fails() {
+		let dir = report_dir("mismatch");
+		let output = dir.join("report.json");
+		let result = command(&output).verify(counted_election(&[(1, 1), (2, 1)], 2), "test".into());
+		assert!(result.unwrap_err().to_string().contains("The tally has 3 discrepancies"));
+
+		// The report is written even though the verification failed
+		let report = &read_report(&output)["report"];
+		assert_eq!(report["onChainWinner"], 2);
+		assert_eq!(report["recountedWinner"], 1);
+		assert_eq!(
+			report["discrepancies"],
+			serde_json::json!([
+				"Commitment 1 has 1 votes on chain but 2 in the recount",
+				"Commitment 2 has 1 votes on chain but 0 in the recount",
+				"Commitment 2 won on chain but 1 won the recount",
+			])
+		);
+
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn reports_are_signed_with_the_suri_key() {
+		let dir = report_dir("signature");
+		let output = dir.join("report.json");
+		let storage = counted_election(&[(1, 2)], 1);
+		command(&output).verify(storage.clone(), "test".into()).unwrap();
+
+		let signed = read_report(&output);
+		let alice = sr25519::Pair::from_string("//Alice", None).unwrap().public();
+		assert_eq!(signed["signer"], alice.to_ss58check());
+
+		// The signature covers the compact JSON of the report as recounted from the storage
+		let report =
+			sp_io::TestExternalities::new(storage).execute_with(|| recount("test".into(), None));
+		let message = serde_json::to_vec(&report).unwrap();
+		let signature = hex::decode(&signed["signature"].as_str().unwrap()[2..]).unwrap();
+		let signature = sr25519::Signature::from_slice(&signature).unwrap();
+		assert!(<sr25519::Pair as sp_core::Pair>::verify(&signature, &message, &alice));
+		let bob = sr25519::Pair::from_string("//Bob", None).unwrap().public();
+		assert!(!<sr25519::Pair as sp_core::Pair>::verify(&signature, &message, &bob));
+
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+}
@@ -0,0 +1,648 @@
+//! Client side of the blind signature voting flow.
+//!
+//! A voter votes from a fresh voting account, the message every candidate blind signs is the SCALE
+//! encoded account id of that voting account, the same message the `vote` call verifies the
+//! signatures against. The flow is:
+//!
+//! 1. `keygen` creates the voting account.
+//! 2. `blind` blinds the voting account id for every candidate key. A message blinded for one key
+//!    can only be unblinded from a signature with that key, so there is one message per candidate,
+//!    which the CA registers with `register_blinded_messages` once biased signing started.
+//! 3. `unblind` turns the blinded signature a candidate stored in `BlindedSignatures` into a
+//!    signature over the voting account id.
+//! 4. `build-ballot` collects the candidate signatures into the `signature_set` of the `vote` call.
+//! 5. `verify` checks a signature set against the candidate public keys before it is submitted.
+//!
+//! In coercion resistant elections the ballot also carries an encrypted credential. The registrar
+//! hands every voter a real credential, `fake-credential` draws fake ones to give to a coercer and
+//! `encrypt-credential` encrypts either for the `vote_with_credential` call.
+//!
+//! In timelock elections the commitment is encrypted to the timelock round of the beacon with
+//! `encrypt-ballot` for the `vote_timelocked` call, and nobody can read it before the beacon
+//! signed the round.
+//!
+//! Every command works on local files. Files holding raw bytes may also hold the bytes as a `0x`
+//! prefixed hex string, as copied from a block explorer or the polkadot.js apps storage page.
+
+use crate::credentials::read_element;
+use blind_rsa_signatures::{MessageRandomizer, Options, PublicKey, Secret};
+use codec::{Decode, Encode};
+use frame_support::traits::Get;
+use node_template_runtime::{
+	pallet_voting_system::{
+		credentials,
+		timelock::{self, BeaconPublicKey},
+		BlindSignature,
+	},
+	AccountId, DrandQuicknetPublicKey,
+};
+use sc_cli::Error;
+use sp_core::{
+	crypto::{Pair as _, Ss58Codec},
+	sr25519,
+};
+use std::path::{Path, PathBuf};
+
+/// The `voter` command used to take part in an election as a voter.
+#[derive(Debug, clap::Subcommand)]
+pub enum VoterCmd {
+	/// Generate a new voting account.
+	Keygen(KeygenCmd),
+
+	/// Blind the voting account id for every candidate key.
+	Blind(BlindCmd),
+
+	/// Unblind a candidates blinded signature.
+	Unblind(UnblindCmd),
+
+	/// Build the signature set of the `vote` call from the unblinded signatures.
+	BuildBallot(BuildBallotCmd),
+
+	/// Verify a signature set against the candidate public keys.
+	Verify(VerifyCmd),
+
+	/// Draw a fake credential for a coercion resistant election.
+	FakeCredential(FakeCredentialCmd),
+
+	/// Encrypt a real or fake credential for the `vote_with_credential` call.
+	EncryptCredential(EncryptCredentialCmd),
+
+	/// Encrypt a commitment to the timelock round for the `vote_timelocked` call.
+	EncryptBallot(EncryptBallotCmd),
+}
+
+impl VoterCmd {
+	/// Run the voter command
+	pub fn run(&self) -> Result<(), Error> {
+		match self {
+			VoterCmd::Keygen(cmd) => cmd.run(),
+			VoterCmd::Blind(cmd) => cmd.run(),
+			VoterCmd::Unblind(cmd) => cmd.run(),
+			VoterCmd::BuildBallot(cmd) => cmd.run(),
+			VoterCmd::Verify(cmd) => cmd.run(),
+			VoterCmd::FakeCredential(cmd) => cmd.run(),
+			VoterCmd::EncryptCredential(cmd) => cmd.run(),
+			VoterCmd::EncryptBallot(cmd) => cmd.run(),
+		}
+	}
+}
+
+/// The `voter keygen` command
+#[derive(Debug, clap::Parser)]
+pub struct KeygenCmd {
+	/// File the secret phrase of the voting account is written to.
+	#[arg(long, value_name = "FILE")]
+	output: PathBuf,
+}
+
+impl KeygenCmd {
+	fn run(&self) -> Result<(), Error> {
+		if self.output.exists() {
+			return Err(Error::Input(format!("{} already exists", self.output.display())))
+		}
+
+		let (pair, phrase, _) = sr25519::Pair::generate_with_phrase(None);
+		std::fs::write(&self.output, phrase)?;
+
+		println!("Voting account: {}", AccountId::from(pair.public()).to_ss58check());
+		println!("Secret phrase written to {}", self.output.display());
+		Ok(())
+	}
+}
+
+/// The `voter blind` command
+#[derive(Debug, clap::Parser)]
+pub struct BlindCmd {
+	/// File holding the secret phrase or URI of the voting account.
+	#[arg(long, value_name = "FILE")]
+	voting_key: PathBuf,
+
+	/// DER encoded RSA public key of a candidate, one per candidate.
+	#[arg(long, value_name = "CANDIDATE=FILE", required = true)]
+	candidate_key: Vec<String>,
+
+	/// File the SCALE encoded blinded messages are written to, the `messages` argument of
+	/// `register_blinded_messages`. The blinding secrets are written next to it with a `.secret`
+	/// extension.
+	#[arg(long, value_name = "FILE")]
+	output: PathBuf,
+}
+
+impl BlindCmd {
+	fn run(&self) -> Result<(), Error> {
+		let message = voting_message(&self.voting_key)?;
+
+		let mut messages = Vec::new();
+		let mut secrets = Vec::new();
+		for arg in &self.candidate_key {
+			let (candidate, path) = parse_candidate_file(arg)?;
+			let public_key = read_public_key(&path)?;
+
+			// Every candidate key gets its own blinding, the secrets must not be reused
+			let blinding = public_key
+				.blind(&mut rand::thread_rng(), &message, true, &Options::default())
+				.map_err(|e| format!("Blinding the voting account: {:?}", e))?;
+			println!(
+				"Blinded message for {}: 0x{}",
+				candidate.to_ss58check(),
+				hex::encode(blinding.blind_msg.as_slice())
+			);
+			messages.push((candidate.clone(), blinding.blind_msg.to_vec()));
+			secrets.push((
+				candidate,
+				BlindingSecret {
+					secret: blinding.secret.to_vec(),
+					msg_randomizer: blinding.msg_randomizer.map(|randomizer| randomizer.0),
+				},
+			));
+		}
+
+		// The CA registers exactly one message of every candidate
+		let mut candidates: Vec<_> = messages.iter().map(|(id, _)| id).collect();
+		candidates.sort();
+		candidates.dedup();
+		if candidates.len() != messages.len() {
+			return Err(Error::Input("Every candidate key may only be given once".into()))
+		}
+
+		let secret_path = self.output.with_extension("secret");
+		let encoded = messages.encode();
+		std::fs::write(&self.output, &encoded)?;
+		std::fs::write(&secret_path, secrets.encode())?;
+
+		println!("Blinded messages: 0x{}", hex::encode(&encoded));
+		println!(
+			"Blinded messages written to {}, keep {} private",
+			self.output.display(),
+			secret_path.display()
+		);
+		Ok(())
+	}
+}
+
+/// The `voter unblind` command
+#[derive(Debug, clap::Parser)]
+pub struct UnblindCmd {
+	/// File holding the secret phrase or URI of the voting account.
+	#[arg(long, value_name = "FILE")]
+	voting_key: PathBuf,
+
+	/// DER encoded RSA public key of the candidate.
+	#[arg(long, value_name = "CANDIDATE=FILE")]
+	candidate_key: String,
+
+	/// Blinding secrets written by `voter blind`.
+	#[arg(long, value_name = "FILE")]
+	secret: PathBuf,
+
+	/// Blinded signature of the candidate, as stored in `BlindedSignatures`.
+	#[arg(long, value_name = "FILE")]
+	blinded_signature: PathBuf,
+
+	/// File the SCALE encoded signature is written to.
+	#[arg(long, value_name = "FILE")]
+	output: PathBuf,
+}
+
+impl UnblindCmd {
+	fn run(&self) -> Result<(), Error> {
+		let message = voting_message(&self.voting_key)?;
+		let (candidate, path) = parse_candidate_file(&self.candidate_key)?;
+		let public_key = read_public_key(&path)?;
+		let secrets =
+			Vec::<(AccountId, BlindingSecret)>::decode(&mut &read_bytes(&self.secret)?[..])
+				.map_err(|e| format!("Decoding {}: {}", self.secret.display(), e))?;
+		// The signature only unblinds with the secret of the message blinded for this candidate
+		let secret = secrets
+			.into_iter()
+			.find(|(id, _)| id == &candidate)
+			.map(|(_, secret)| secret)
+			.ok_or_else(|| format!("No blinding secret for {}", candidate.to_ss58check()))?;
+		let blinded_signature =
+			blind_rsa_signatures::BlindSignature::new(read_bytes(&self.blinded_signature)?);
+		let msg_randomizer = secret.msg_randomizer.map(MessageRandomizer::from);
+
+		// Finalizing also verifies the signature, a wrong key or secret is caught here
+		let signature = public_key
+			.finalize(
+				&blinded_signature,
+				&Secret::new(secret.secret),
+				msg_randomizer,
+				&message,
+				&Options::default(),
+			)
+			.map_err(|e| format!("Unblinding the signature: {:?}", e))?;
+
+		let signature = BlindSignature {
+			signature: signature.to_vec(),
+			msg_randomizer: secret.msg_randomizer.unwrap_or_default(),
+		};
+		std::fs::write(&self.output, signature.encode())?;
+
+		println!("Signature written to {}", self.output.display());
+		Ok(())
+	}
+}
+
+/// The `voter build-ballot` command
+#[derive(Debug, clap::Parser)]
+pub struct BuildBallotCmd {
+	/// Unblinded signature of a candidate, one per candidate.
+	#[arg(long, value_name = "CANDIDATE=FILE", required = true)]
+	signature: Vec<String>,
+
+	/// File the SCALE encoded signature set is written to.
+	#[arg(long, value_name = "FILE")]
+	output: PathBuf,
+}
+
+impl BuildBallotCmd {
+	fn run(&self) -> Result<(), Error> {
+		let mut signature_set = Vec::new();
+		for arg in &self.signature {
+			let (candidate, path) = parse_candidate_file(arg)?;
+			let signature = BlindSignature::decode(&mut &read_bytes(&path)?[..])
+				.map_err(|e| format!("Decoding {}: {}", path.display(), e))?;
+			signature_set.push((candidate, signature));
+		}
+
+		// The `vote` call rejects signature sets signed twice by the same candidate
+		let mut candidates: Vec<_> = signature_set.iter().map(|(id, _)| id).collect();
+		candidates.sort();
+		candidates.dedup();
+		if candidates.len() != signature_set.len() {
+			return Err(Error::Input("Every candidate may only sign once".into()))
+		}
+
+		let encoded = signature_set.encode();
+		std::fs::write(&self.output, &encoded)?;
+
+		println!("Signature set: 0x{}", hex::encode(&encoded));
+		println!("Signature set written to {}", self.output.display());
+		Ok(())
+	}
+}
+
+/// The `voter verify` command
+#[derive(Debug, clap::Parser)]
+pub struct VerifyCmd {
+	/// File holding the secret phrase or URI of the voting account.
+	#[arg(long, value_name = "FILE")]
+	voting_key: PathBuf,
+
+	/// Signature set written by `voter build-ballot`.
+	#[arg(long, value_name = "FILE")]
+	ballot: PathBuf,
+
+	/// DER encoded RSA public key of a candidate, one per candidate.
+	#[arg(long, value_name = "CANDIDATE=FILE", required = true)]
+	candidate_key: Vec<String>,
+}
+
+impl VerifyCmd {
+	fn run(&self) -> Result<(), Error> {
+		let message = voting_message(&self.voting_key)?;
+		let signature_set =
+			Vec::<(AccountId, BlindSignature)>::decode(&mut &read_bytes(&self.ballot)?[..])
+				.map_err(|e| format!("Decoding {}: {}", self.ballot.display(), e))?;
+
+		let mut candidate_keys = Vec::new();
+		for arg in &self.candidate_key {
+			let (candidate, path) = parse_candidate_file(arg)?;
+			candidate_keys.push((candidate, read_public_key(&path)?));
+		}
+
+		// The `vote` call expects exactly one signature of every candidate
+		if signature_set.len() != candidate_keys.len() {
+			return Err(Error::Input(format!(
+				"The signature set holds {} signatures but there are {} candidates",
+				signature_set.len(),
+				candidate_keys.len()
+			)))
+		}
+
+		for (candidate, public_key) in &candidate_keys {
+			let signature = signature_set
+				.iter()
+				.find(|(id, _)| id == candidate)
+				.map(|(_, signature)| signature)
+				.ok_or_else(|| format!("Missing the signature of {}", candidate.to_ss58check()))?;
+			public_key
+				.verify(
+					&blind_rsa_signatures::Signature::new(signature.signature.clone()),
+					Some(MessageRandomizer::from(signature.msg_randomizer)),
+					&message,
+					&Options::default(),
+				)
+				.map_err(|_| format!("Invalid signature of {}", candidate.to_ss58check()))?;
+		}
+
+		println!("All {} signatures are valid", signature_set.len());
+		Ok(())
+	}
+}
+
+/// The `voter fake-credential` command
+#[derive(Debug, clap::Parser)]
+pub struct FakeCredentialCmd {
+	/// File the fake credential is written to.
+	#[arg(long, value_name = "FILE")]
+	output: PathBuf,
+}
+
+impl FakeCredentialCmd {
+	fn run(&self) -> Result<(), Error> {
+		// Fake credentials are drawn exactly like the real ones, nobody can tell them apart
+		let credential = credentials::new_credential(&mut rand::thread_rng());
+		std::fs::write(&self.output, format!("0x{}", hex::encode(credential)))?;
+
+		println!("Fake credential written to {}", self.output.display());
+		Ok(())
+	}
+}
+
+/// The `voter encrypt-credential` command
+#[derive(Debug, clap::Parser)]
+pub struct EncryptCredentialCmd {
+	/// Real credential from the registrar or fake credential from `voter fake-credential`.
+	#[arg(long, value_name = "FILE")]
+	credential: PathBuf,
+
+	/// Tally key of the election, as set with `set_credential_key`.
+	#[arg(long, value_name = "FILE")]
+	tally_key: PathBuf,
+
+	/// File the SCALE encoded encrypted credential is written to.
+	#[arg(long, value_name = "FILE")]
+	output: PathBuf,
+}
+
+impl EncryptCredentialCmd {
+	fn run(&self) -> Result<(), Error> {
+		let credential = read_element(&self.credential)?;
+		let tally_key = read_element(&self.tally_key)?;
+
+		// Every encryption is freshly randomized, ballots cast with the same credential can not
+		// be linked
+		let encrypted = credentials::encrypt(&tally_key, &credential, &mut rand::thread_rng())
+			.ok_or("Encrypting the credential")?;
+		std::fs::write(&self.output, encrypted.encode())?;
+
+		println!("Encrypted credential a: 0x{}", hex::encode(encrypted.a));
+		println!("Encrypted credential b: 0x{}", hex::encode(encrypted.b));
+		println!("Encrypted credential written to {}", self.output.display());
+		Ok(())
+	}
+}
+
+/// The `voter encrypt-ballot` command
+#[derive(Debug, clap::Parser)]
+pub struct EncryptBallotCmd {
+	/// Commitment of the ballot.
+	#[arg(long)]
+	commitment: u32,
+
+	/// Timelock round of the election, as set with `set_timelock_round`.
+	#[arg(long)]
+	round: u64,
+
+	/// Compressed public key of the beacon, the drand quicknet key of the runtime by default.
+	#[arg(long, value_name = "FILE")]
+	beacon_key: Option<PathBuf>,
+
+	/// File the SCALE encoded time-locked ballot is written to, the `ciphertext` argument of
+	/// `vote_timelocked`.
+	#[arg(long, value_name = "FILE")]
+	output: PathBuf,
+}
+
+impl EncryptBallotCmd {
+	fn run(&self) -> Result<(), Error> {
+		let beacon_key: BeaconPublicKey = match &self.beacon_key {
+			Some(path) => read_bytes(path)?
+				.try_into()
+				.map_err(|_| format!("{} is not a compressed G2 point", path.display()))?,
+			None => DrandQuicknetPublicKey::get(),
+		};
+
+		let message = timelock::commitment_message(self.commitment);
+		let ciphertext =
+			timelock::encrypt(&beacon_key, self.round, &message, &mut rand::thread_rng())
+				.ok_or("Encrypting the ballot, the beacon key is not a point of G2")?;
+		std::fs::write(&self.output, ciphertext.encode())?;
+
+		println!("Time-locked ballot: 0x{}", hex::encode(ciphertext.encode()));
+		println!("Time-locked ballot written to {}", self.output.display());
+		Ok(())
+	}
+}
+
+/// Blinding secret of `voter blind` for one candidate, needed to unblind their signature.
+#[derive(Encode, Decode)]
+struct BlindingSecret {
+	secret: Vec<u8>,
+	msg_randomizer: Option<[u8; 32]>,
+}
+
+/// Message blind signed by the candidates, the encoded account id of the voting account.
+fn voting_message(voting_key: &Path) -> Result<Vec<u8>, Error> {
+	let suri = std::fs::read_to_string(voting_key)?;
+	let pair = sr25519::Pair::from_string(suri.trim(), None)
+		.map_err(|e| format!("Reading {}: {:?}", voting_key.display(), e))?;
+	Ok(AccountId::from(pair.public()).encode())
+}
+
+/// Read a DER encoded RSA public key.
+fn read_public_key(path: &Path) -> Result<PublicKey, Error> {
+	PublicKey::from_der(&read_bytes(path)?)
+		.map_err(|e| format!("Reading {}: {:?}", path.display(), e).into())
+}
+
+/// Read a file holding raw bytes or a `0x` prefixed hex string.
+pub(crate) fn read_bytes(path: &Path) -> Result<Vec<u8>, Error> {
+	let bytes = std::fs::read(path)?;
+	match std::str::from_utf8(&bytes).map(str::trim) {
+		Ok(text) if text.starts_with("0x") => hex::decode(&text[2..])
+			.map_err(|e| format!("Decoding {}: {}", path.display(), e).into()),
+		_ => Ok(bytes),
+	}
+}
+
+/// Parse a `CANDIDATE=FILE` argument, the candidate being an SS58 address.
+fn parse_candidate_file(arg: &str) -> Result<(AccountId, PathBuf), Error> {
+	let (candidate, path) = arg
+		.split_once('=')
+		.ok_or_else(|| Error::Input(format!("Expected CANDIDATE=FILE, got {}", arg)))?;
+	let candidate = AccountId::from_ss58check(candidate)
+		.map_err(|e| Error::Input(format!("Invalid candidate {}: {:?}", candidate, e)))?;
+	Ok((candidate, PathBuf::from(path)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use blind_rsa_signatures::SecretKey;
+	use clap::Parser;
+
+	const TEST_KEYS: &str =
+		concat!(env!("CARGO_MANIFEST_DIR"), "/../pallets/voting-system/test-keys");
+
+	/// Empty directory for the files of one test
+	fn test_dir(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("voter-{}-{}", name, std::process::id()));
+		let _ = std::fs::remove_dir_all(&dir);
+		std::fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	fn address(suri: &str) -> String {
+		AccountId::from(sr25519::Pair::from_string(suri, None).unwrap().public()).to_ss58check()
+	}
+
+	fn key_file(index: u32, public: bool) -> String {
+		let extension = if public { "pub.der" } else { "der" };
+		format!("{}/key{}.{}", TEST_KEYS, index, extension)
+	}
+
+	fn path(dir: &Path, name: &str) -> String {
+		dir.join(name).display().to_string()
+	}
+
+	/// Blind sign every blinded message with the test key of its candidate, as the candidates do
+	/// during biased signing
+	fn sign_messages(dir: &Path, candidates: &[(String, u32)]) {
+		let messages = Vec::<(AccountId, Vec<u8>)>::decode(
+			&mut &std::fs::read(dir.join("messages.scale")).unwrap()[..],
+		)
+		.unwrap();
+		for (address, key) in candidates {
+			let (_, message) =
+				messages.iter().find(|(id, _)| &id.to_ss58check() == address).unwrap();
+			let secret_key =
+				SecretKey::from_der(&std::fs::read(key_file(*key, false)).unwrap()).unwrap();
+			let signature = secret_key
+				.blind_sign(&mut rand::thread_rng(), message, &Options::default())
+				.unwrap();
+			std::fs::write(dir.join(format!("blinded-{}", key)), signature.to_vec()).unwrap();
+		}
+	}
+
+	#[test]
+	fn signature_sets_round_trip_from_keygen_to_verify() {
+		let dir = test_dir("round-trip");
+		let candidates = [(address("//Bob"), 1), (address("//Charlie"), 2)];
+		let candidate_keys: Vec<_> = candidates
+			.iter()
+			.flat_map(|(address, key)| {
+				["--candidate-key".into(), format!("{}={}", address, key_file(*key, true))]
+			})
+			.collect();
+
+		KeygenCmd::parse_from(["keygen", "--output", &path(&dir, "voting-key")])
+			.run()
+			.unwrap();
+		// The voting account is never overwritten
+		assert!(KeygenCmd::parse_from(["keygen", "--output", &path(&dir, "voting-key")])
+			.run()
+			.is_err());
+
+		let mut args = vec!["blind".into(), "--voting-key".into(), path(&dir, "voting-key")];
+		args.extend(candidate_keys.clone());
+		args.extend(["--output".into(), path(&dir, "messages.scale")]);
+		BlindCmd::parse_from(args).run().unwrap();
+		assert!(dir.join("messages.secret").exists());
+
+		sign_messages(&dir, &candidates);
+		for (address, key) in &candidates {
+			UnblindCmd::parse_from([
+				"unblind".into(),
+				"--voting-key".into(),
+				path(&dir, "voting-key"),
+				"--candidate-key".into(),
+				format!("{}={}", address, key_file(*key, true)),
+				"--secret".into(),
+				path(&dir, "messages.secret"),
+				"--blinded-signature".into(),
+				path(&dir, &format!("blinded-{}", key)),
+				"--output".into(),
+				path(&dir, &format!("signature-{}", key)),
+			])
+			.run()
+			.unwrap();
+		}
+
+		let mut args = vec!["build-ballot".to_string()];
+		for (address, key) in &candidates {
+			args.push("--signature".into());
+			args.push(format!("{}={}", address, path(&dir, &format!("signature-{}", key))));
+		}
+		args.extend(["--output".into(), path(&dir, "ballot.scale")]);
+		BuildBallotCmd::parse_from(args).run().unwrap();
+
+		let verify = |voting_key: &str, candidate_keys: &[String]| {
+			let mut args = vec!["verify".into(), "--voting-key".into(), path(&dir, voting_key)];
+			args.extend(["--ballot".into(), path(&dir, "ballot.scale")]);
+			args.extend(candidate_keys.iter().cloned());
+			VerifyCmd::parse_from(args).run()
+		};
+		verify("voting-key", &candidate_keys).unwrap();
+
+		// The signatures are bound to the candidate keys and to the voting account
+		let swapped: Vec<_> = candidates
+			.iter()
+			.zip([2, 1])
+			.flat_map(|((address, _), key)| {
+				["--candidate-key".into(), format!("{}={}", address, key_file(key, true))]
+			})
+			.collect();
+		assert!(verify("voting-key", &swapped).is_err());
+		KeygenCmd::parse_from(["keygen", "--output", &path(&dir, "other-key")])
+			.run()
+			.unwrap();
+		assert!(verify("other-key", &candidate_keys).is_err());
+		// The set holds exactly one signature of every candidate
+		assert!(verify("voting-key", &candidate_keys[..2]).is_err());
+
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn signatures_only_unblind_with_the_key_they_were_blinded_for() {
+		let dir = test_dir("unblind");
+		let candidates = [(address("//Bob"), 1), (address("//Charlie"), 2)];
+
+		KeygenCmd::parse_from(["keygen", "--output", &path(&dir, "voting-key")])
+			.run()
+			.unwrap();
+		let mut args = vec!["blind".into(), "--voting-key".into(), path(&dir, "voting-key")];
+		for (address, key) in &candidates {
+			args.push("--candidate-key".into());
+			args.push(format!("{}={}", address, key_file(*key, true)));
+		}
+		args.extend(["--output".into(), path(&dir, "messages.scale")]);
+		BlindCmd::parse_from(args).run().unwrap();
+		sign_messages(&dir, &candidates);
+
+		// The signature of Charlie does not unblind with the secret of Bob
+		let unblind = |address: &str, key: u32, blinded: u32| {
+			UnblindCmd::parse_from([
+				"unblind".into(),
+				"--voting-key".into(),
+				path(&dir, "voting-key"),
+				"--candidate-key".into(),
+				format!("{}={}", address, key_file(key, true)),
+				"--secret".into(),
+				path(&dir, "messages.secret"),
+				"--blinded-signature".into(),
+				path(&dir, &format!("blinded-{}", blinded)),
+				"--output".into(),
+				path(&dir, "signature"),
+			])
+			.run()
+		};
+		assert!(unblind(&candidates[0].0, 1, 2).is_err());
+		unblind(&candidates[0].0, 1, 1).unwrap();
+
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+}
@@ -1,20 +1,549 @@
-//! Benchmarking setup for pallet-template
+//! Benchmarks of the voting system calls.
+//!
+//! Every benchmark sets up the storage the call needs directly, so it does not depend on the
+//! genesis of the chain it runs on. Candidates that sign ballots use the RSA test keys of the
+//! pallet, which bounds the signature sets to `MAX_SIGNERS` candidates. Calls walking every
+//! candidate are benchmarked with more candidates holding placeholder keys.
 
 use super::*;
 
-#[allow(unused)]
-use crate::Pallet as Template;
-use frame_benchmarking::{benchmarks, whitelisted_caller};
+use crate::{
+	credentials::{self, Ciphertext},
+	timelock::{self, TimelockBeacon},
+	Pallet as VotingSystem,
+};
+use codec::Encode;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_support::{inherent::Vec, sp_std::vec, traits::Get, BoundedVec};
 use frame_system::RawOrigin;
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use scale_info::prelude::string::String;
+
+const SEED: u32 = 0;
+
+/// Candidates with a test key pair, the signature sets hold at most this many signatures
+const MAX_SIGNERS: u32 = 10;
+
+/// Candidates the calls walking every candidate are benchmarked with
+const MAX_CANDIDATES: u32 = 100;
+
+/// Test key pair of a nominee, no candidate of the benchmarks uses it
+const NOMINEE_KEY: usize = 10;
+
+/// Test key pair of the ballot key
+const BALLOT_KEY: usize = 11;
+
+/// DER encoded 2048 bit RSA key pairs, candidate `i` signs with key pair `i`
+const KEYS: [(&[u8], &[u8]); 12] = [
+	(include_bytes!("../test-keys/key1.pub.der"), include_bytes!("../test-keys/key1.der")),
+	(include_bytes!("../test-keys/key2.pub.der"), include_bytes!("../test-keys/key2.der")),
+	(include_bytes!("../test-keys/key3.pub.der"), include_bytes!("../test-keys/key3.der")),
+	(include_bytes!("../test-keys/key4.pub.der"), include_bytes!("../test-keys/key4.der")),
+	(include_bytes!("../test-keys/key5.pub.der"), include_bytes!("../test-keys/key5.der")),
+	(include_bytes!("../test-keys/key6.pub.der"), include_bytes!("../test-keys/key6.der")),
+	(include_bytes!("../test-keys/key7.pub.der"), include_bytes!("../test-keys/key7.der")),
+	(include_bytes!("../test-keys/key8.pub.der"), include_bytes!("../test-keys/key8.der")),
+	(include_bytes!("../test-keys/key9.pub.der"), include_bytes!("../test-keys/key9.der")),
+	(include_bytes!("../test-keys/key10.pub.der"), include_bytes!("../test-keys/key10.der")),
+	(include_bytes!("../test-keys/key11.pub.der"), include_bytes!("../test-keys/key11.der")),
+	(include_bytes!("../test-keys/key12.pub.der"), include_bytes!("../test-keys/key12.der")),
+];
+
+fn ca<T: Config>() -> T::AccountId {
+	account("ca", 0, SEED)
+}
+
+/// Start a fresh election in `phase`, run by `ca`
+fn set_election<T: Config>(phase: ElectionPhase) {
+	frame_system::Pallet::<T>::set_block_number(1u32.into());
+	CentralAuthority::<T>::put(ca::<T>());
+	PhaseChanges::<T>::insert(phase.clone(), frame_system::Pallet::<T>::block_number());
+	Phase::<T>::put(phase);
+	BallotKeys::<T>::put(BallotKey { public: KEYS[BALLOT_KEY].0.to_vec(), private: Vec::new() });
+	let _ = Candidates::<T>::clear(u32::MAX, None);
+	let _ = Voters::<T>::clear(u32::MAX, None);
+}
+
+/// Register `c` candidates, the first `MAX_SIGNERS` with a test key pair and the others with a
+/// distinct placeholder key
+fn set_candidates<T: Config>(c: u32) -> Vec<T::AccountId> {
+	let candidates: Vec<T::AccountId> = (0..c).map(|i| account("candidate", i, SEED)).collect();
+	for (i, candidate) in candidates.iter().enumerate() {
+		let pubkey =
+			if i < MAX_SIGNERS as usize { KEYS[i].0.to_vec() } else { (i as u32).encode() };
+		Candidates::<T>::insert(candidate, Candidate { name: String::from("Candidate"), pubkey });
+	}
+	CandidatesCount::<T>::put(c as u64);
+	candidates
+}
+
+/// Register a voter with payloads of `MaxPayloadLength` bytes
+fn register_voter<T: Config>(index: u32) -> VoterId<T> {
+	let voter = new_voter::<T>(index);
+	let voter_id = VotingSystem::<T>::voter_id(&voter.blinded_pubkey);
+	Voters::<T>::insert(voter_id, voter);
+	voter_id
+}
+
+fn new_voter<T: Config>(index: u32) -> Voter {
+	let length = T::MaxPayloadLength::get() as usize;
+	let mut blinded_pubkey = vec![0; length];
+	blinded_pubkey[..4].copy_from_slice(&index.to_le_bytes());
+	Voter {
+		blinded_pubkey,
+		is_eligible: true,
+		signed_blinded_pubkey: vec![1; length],
+		personal_data_hash: vec![2; length],
+	}
+}
+
+/// Message a candidate blind signs for a voter, any number below the modulus of the test keys
+fn blinded_message<T: Config>() -> BoundedVec<u8, T::SignatureLength> {
+	BoundedVec::try_from(vec![1; 256]).unwrap()
+}
+
+/// Run the blind signature flow of `voter` with every candidate, as the voter client does
+fn signature_set<T: Config>(
+	voter: &T::AccountId,
+	candidates: &[T::AccountId],
+) -> Vec<(T::AccountId, BlindSignature)> {
+	let mut rng = ChaCha20Rng::seed_from_u64(0);
+	let options = blind_rsa_signatures::Options::default();
+	let message = voter.encode();
+	candidates
+		.iter()
+		.enumerate()
+		.map(|(i, candidate)| {
+			let public_key = blind_rsa_signatures::PublicKey::from_der(KEYS[i].0).unwrap();
+			let secret_key = blind_rsa_signatures::SecretKey::from_der(KEYS[i].1).unwrap();
+			let blinding = public_key.blind(&mut rng, &message, true, &options).unwrap();
+			let msg_randomizer = blinding.msg_randomizer.as_ref().unwrap().0;
+			let blind_signature =
+				secret_key.blind_sign(&mut rng, &blinding.blind_msg, &options).unwrap();
+			let signature = public_key
+				.finalize(
+					&blind_signature,
+					&blinding.secret,
+					Some(blind_rsa_signatures::MessageRandomizer::from(msg_randomizer)),
+					&message,
+					&options,
+				)
+				.unwrap();
+			(candidate.clone(), BlindSignature { signature: signature.to_vec(), msg_randomizer })
+		})
+		.collect()
+}
+
+/// Make the next ballot complete every level of the ballot log, the most nodes a ballot writes
+fn fill_ballot_log<T: Config>() {
+	BallotLogLength::<T>::put(u32::MAX as u64);
+}
+
+fn tally_key(rng: &mut ChaCha20Rng) -> credentials::GroupElement {
+	credentials::public_key(&credentials::random_exponent(rng))
+}
 
 benchmarks! {
-	do_something {
-		let s in 0 .. 100;
+	change_phase {
+		let c in 2 .. MAX_CANDIDATES;
+
+		// Starting biased signing commits to every candidate key
+		set_election::<T>(ElectionPhase::Registration);
+		set_candidates::<T>(c);
+	}: _(RawOrigin::Signed(ca::<T>()))
+	verify {
+		assert_eq!(VotingSystem::<T>::get_phase(), Some(ElectionPhase::BiasedSigner));
+	}
+
+	add_voter {
+		set_election::<T>(ElectionPhase::Registration);
+		let voter = new_voter::<T>(0);
+		let voter_id = VotingSystem::<T>::voter_id(&voter.blinded_pubkey);
+	}: _(
+		RawOrigin::Signed(ca::<T>()),
+		voter.blinded_pubkey,
+		voter.signed_blinded_pubkey,
+		voter.personal_data_hash,
+		true
+	)
+	verify {
+		assert!(Voters::<T>::contains_key(voter_id));
+	}
+
+	update_candidate_info {
+		let c in 1 .. MAX_CANDIDATES;
+
+		// The new key is compared with the key of every other candidate
+		set_election::<T>(ElectionPhase::Registration);
+		let candidate = set_candidates::<T>(c)[0].clone();
+		let pubkey = KEYS[NOMINEE_KEY].0.to_vec();
+	}: _(
+		RawOrigin::Signed(candidate.clone()),
+		candidate.clone(),
+		String::from("Candidate"),
+		pubkey.clone()
+	)
+	verify {
+		assert_eq!(Candidates::<T>::get(&candidate).unwrap().pubkey, pubkey);
+	}
+
+	biased_signing {
+		set_election::<T>(ElectionPhase::BiasedSigner);
+		let candidate = set_candidates::<T>(1)[0].clone();
+		let voter = register_voter::<T>(0);
+		BlindedMessages::<T>::insert(voter, &candidate, blinded_message::<T>());
+		let (_, signature) = VotingSystem::<T>::sign_pending_voters(
+			&candidate,
+			KEYS[0].1,
+			&mut ChaCha20Rng::seed_from_u64(0),
+		)
+		.into_iter()
+		.next()
+		.unwrap();
+	}: _(RawOrigin::Signed(candidate.clone()), candidate.clone(), voter, signature)
+	verify {
+		assert!(BlindedSignatures::<T>::contains_key(voter, &candidate));
+	}
+
+	add_voters {
+		let n in 1 .. T::MaxBatchSize::get();
+
+		set_election::<T>(ElectionPhase::Registration);
+		let voters: Vec<_> = (0..n).map(new_voter::<T>).collect();
+		let voters = BoundedVec::try_from(voters).unwrap();
+	}: _(RawOrigin::Signed(ca::<T>()), voters)
+	verify {
+		assert_eq!(Voters::<T>::count(), n);
+	}
+
+	biased_signing_batch {
+		let n in 1 .. T::MaxBatchSize::get();
+
+		set_election::<T>(ElectionPhase::BiasedSigner);
+		let candidate = set_candidates::<T>(1)[0].clone();
+		for index in 0..n {
+			let voter = register_voter::<T>(index);
+			BlindedMessages::<T>::insert(voter, &candidate, blinded_message::<T>());
+		}
+		let signatures = VotingSystem::<T>::sign_pending_voters(
+			&candidate,
+			KEYS[0].1,
+			&mut ChaCha20Rng::seed_from_u64(0),
+		);
+	}: _(RawOrigin::Signed(candidate.clone()), candidate.clone(), signatures)
+	verify {
+		assert_eq!(SignedVoterCount::<T>::get(&candidate), n as u64);
+	}
+
+	nominate_candidate {
+		let c in 1 .. MAX_CANDIDATES;
+
+		set_election::<T>(ElectionPhase::Registration);
+		set_candidates::<T>(c);
+		let nominee: T::AccountId = whitelisted_caller();
+	}: _(RawOrigin::Signed(nominee.clone()), String::from("Nominee"), KEYS[NOMINEE_KEY].0.to_vec())
+	verify {
+		assert!(CandidateNominations::<T>::contains_key(&nominee));
+	}
+
+	approve_candidate {
+		let c in 1 .. MAX_CANDIDATES;
+
+		set_election::<T>(ElectionPhase::Registration);
+		set_candidates::<T>(c);
+		let nominee: T::AccountId = whitelisted_caller();
+		CandidateNominations::<T>::insert(
+			&nominee,
+			Candidate { name: String::from("Nominee"), pubkey: KEYS[NOMINEE_KEY].0.to_vec() },
+		);
+	}: _(RawOrigin::Signed(ca::<T>()), nominee.clone())
+	verify {
+		assert!(Candidates::<T>::contains_key(&nominee));
+	}
+
+	withdraw_candidate {
+		set_election::<T>(ElectionPhase::Registration);
+		let candidate = set_candidates::<T>(2)[0].clone();
+	}: _(RawOrigin::Signed(candidate.clone()))
+	verify {
+		assert!(!Candidates::<T>::contains_key(&candidate));
+	}
+
+	set_revote_policy {
+		set_election::<T>(ElectionPhase::Registration);
+	}: _(RawOrigin::Signed(ca::<T>()), RevotePolicy::MaxRevotes(3))
+	verify {
+		assert_eq!(VotingSystem::<T>::revote_policy(), RevotePolicy::MaxRevotes(3));
+	}
+
+	set_credential_key {
+		set_election::<T>(ElectionPhase::Initialization);
+		TimelockRound::<T>::kill();
+		let key = tally_key(&mut ChaCha20Rng::seed_from_u64(0));
+	}: _(RawOrigin::Signed(ca::<T>()), Some(key))
+	verify {
+		assert_eq!(VotingSystem::<T>::credential_key(), Some(key));
+	}
+
+	register_credential {
+		set_election::<T>(ElectionPhase::Registration);
+		let mut rng = ChaCha20Rng::seed_from_u64(0);
+		let key = tally_key(&mut rng);
+		CredentialKey::<T>::put(key);
+		let voter = register_voter::<T>(0);
+		let credential = credentials::new_credential(&mut rng);
+		let credential = credentials::encrypt(&key, &credential, &mut rng).unwrap();
+	}: _(RawOrigin::Signed(ca::<T>()), voter, credential)
+	verify {
+		assert!(VoterCredentials::<T>::contains_key(voter));
+	}
+
+	vote {
+		let n in 1 .. MAX_SIGNERS;
+
+		set_election::<T>(ElectionPhase::Voting);
+		let candidates = set_candidates::<T>(n);
+		fill_ballot_log::<T>();
+		let voter: T::AccountId = whitelisted_caller();
+		let signature_set = signature_set::<T>(&voter, &candidates);
+	}: _(RawOrigin::Signed(voter.clone()), 1, signature_set)
+	verify {
+		assert!(Ballots::<T>::contains_key(&voter));
+	}
+
+	vote_timelocked {
+		let n in 1 .. MAX_SIGNERS;
+
+		set_election::<T>(ElectionPhase::Voting);
+		let candidates = set_candidates::<T>(n);
+		fill_ballot_log::<T>();
+		let round = 1;
+		TimelockRound::<T>::put(round);
+		let ciphertext = timelock::encrypt(
+			&T::Beacon::public_key().unwrap(),
+			round,
+			&timelock::commitment_message(1),
+			&mut ChaCha20Rng::seed_from_u64(0),
+		)
+		.unwrap();
+		let voter: T::AccountId = whitelisted_caller();
+		let signature_set = signature_set::<T>(&voter, &candidates);
+	}: _(RawOrigin::Signed(voter.clone()), ciphertext, signature_set)
+	verify {
+		assert!(TimelockedBallots::<T>::contains_key(&voter));
+	}
+
+	vote_with_credential {
+		let n in 1 .. MAX_SIGNERS;
+
+		set_election::<T>(ElectionPhase::Voting);
+		let candidates = set_candidates::<T>(n);
+		fill_ballot_log::<T>();
+		let mut rng = ChaCha20Rng::seed_from_u64(0);
+		let key = tally_key(&mut rng);
+		CredentialKey::<T>::put(key);
+		let credential = credentials::new_credential(&mut rng);
+		let credential = credentials::encrypt(&key, &credential, &mut rng).unwrap();
+		let voter: T::AccountId = whitelisted_caller();
+		let signature_set = signature_set::<T>(&voter, &candidates);
+	}: _(RawOrigin::Signed(voter), 1, credential, signature_set)
+	verify {
+		assert_eq!(VotingSystem::<T>::credential_ballot_count(), 1);
+	}
+
+	reveal_ballot_key {
+		set_election::<T>(ElectionPhase::Counting);
+		TimelockRound::<T>::kill();
+		CredentialKey::<T>::kill();
+	}: _(RawOrigin::Signed(ca::<T>()), KEYS[BALLOT_KEY].1.to_vec())
+	verify {
+		assert!(VotingSystem::<T>::tally_open());
+	}
+
+	tally_step {
+		let n in 1 .. T::MaxBatchSize::get();
+
+		set_election::<T>(ElectionPhase::Counting);
+		TimelockRound::<T>::kill();
+		CredentialKey::<T>::kill();
+		BallotKeys::<T>::put(BallotKey {
+			public: KEYS[BALLOT_KEY].0.to_vec(),
+			private: KEYS[BALLOT_KEY].1.to_vec(),
+		});
+		// Every ballot is for another commitment, so every ballot takes the lead
+		for index in 0..n {
+			let voter: T::AccountId = account("voter", index, SEED);
+			let ballot = Ballot { commitment: n - index, signature: Vec::new(), nonce: 1 };
+			Ballots::<T>::insert(voter, ballot);
+		}
+		let caller: T::AccountId = whitelisted_caller();
+	}: _(RawOrigin::Signed(caller), n)
+	verify {
+		assert!(VotingSystem::<T>::winner().is_some());
+	}
+
+	tally_timelocked_step {
+		let n in 1 .. T::MaxBatchSize::get();
+
+		set_election::<T>(ElectionPhase::Counting);
+		let round = 1;
+		TimelockRound::<T>::put(round);
+		let public_key = T::Beacon::public_key().unwrap();
+		let mut rng = ChaCha20Rng::seed_from_u64(0);
+		for index in 0..n {
+			let voter: T::AccountId = account("voter", index, SEED);
+			let message = timelock::commitment_message(n - index);
+			let ciphertext = timelock::encrypt(&public_key, round, &message, &mut rng).unwrap();
+			TimelockedBallots::<T>::insert(
+				voter,
+				TimelockedBallot { ciphertext, signature: Vec::new(), nonce: 1 },
+			);
+		}
+		// Only the beacon can sign the round. Decrypting with the signature of another key costs
+		// the same pairing and multiplication, and rejects the ballot instead of counting it
+		let signature = timelock::sign(&[1; 32], round).unwrap().to_vec();
+		BeaconSignature::<T>::put(BoundedVec::try_from(signature).unwrap());
+		let caller: T::AccountId = whitelisted_caller();
+	}: _(RawOrigin::Signed(caller), n)
+	verify {
+		assert!(VotingSystem::<T>::winner().is_some());
+	}
+
+	set_timelock_round {
+		set_election::<T>(ElectionPhase::Initialization);
+		CredentialKey::<T>::kill();
+	}: _(RawOrigin::Signed(ca::<T>()), Some(1))
+	verify {
+		assert_eq!(VotingSystem::<T>::timelock_round(), Some(1));
+	}
+
+	submit_beacon_signature {
+		set_election::<T>(ElectionPhase::Counting);
+		let round = 1;
+		TimelockRound::<T>::put(round);
+		BeaconSignature::<T>::kill();
+		// Only the beacon can sign the round, a signature of another key goes through the same
+		// verification and is rejected by it
+		let signature = timelock::sign(&[1; 32], round).unwrap().to_vec();
+		let valid = T::Beacon::verify(round, &signature);
 		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+	}: {
+		let result = VotingSystem::<T>::submit_beacon_signature(
+			RawOrigin::Signed(caller).into(),
+			round,
+			signature,
+		);
+		assert_eq!(result.is_ok(), valid);
+	}
+
+	submit_credential_tally {
+		let n in 1 .. T::MaxBatchSize::get();
+
+		set_election::<T>(ElectionPhase::Counting);
+		let mut rng = ChaCha20Rng::seed_from_u64(0);
+		CredentialKey::<T>::put(tally_key(&mut rng));
+		CredentialBallotCount::<T>::put(n);
+		for index in 0..n {
+			let credential = Ciphertext { a: [1; 256], b: [2; 256] };
+			VoterCredentials::<T>::insert(register_voter::<T>(index), credential);
+		}
+		let tally: Vec<_> = (0..n).map(|commitment| (commitment, 1)).collect();
+		let tally = BoundedVec::try_from(tally).unwrap();
+	}: _(RawOrigin::Signed(ca::<T>()), tally, Default::default())
+	verify {
+		assert!(VotingSystem::<T>::winner().is_some());
+	}
+
+	pause {
+		set_election::<T>(ElectionPhase::Voting);
+		Paused::<T>::kill();
+	}: _(RawOrigin::Signed(ca::<T>()))
+	verify {
+		assert!(VotingSystem::<T>::paused());
+	}
+
+	unpause {
+		set_election::<T>(ElectionPhase::Voting);
+		Paused::<T>::put(true);
+	}: _(RawOrigin::Signed(ca::<T>()))
+	verify {
+		assert!(!VotingSystem::<T>::paused());
+	}
+
+	cancel_election {
+		set_election::<T>(ElectionPhase::Voting);
+		let reason = BoundedVec::try_from(vec![0; T::MaxPayloadLength::get() as usize]).unwrap();
+	}: _(RawOrigin::Signed(ca::<T>()), reason)
+	verify {
+		assert_eq!(VotingSystem::<T>::get_phase(), Some(ElectionPhase::Cancelled));
+	}
+
+	file_dispute {
+		set_election::<T>(ElectionPhase::Counting);
+		TallyCountedAt::<T>::put(frame_system::Pallet::<T>::block_number());
+		// A credential voter is only found in the ballot log, after every other check
+		CredentialKey::<T>::put(tally_key(&mut ChaCha20Rng::seed_from_u64(0)));
+		let filer: T::AccountId = whitelisted_caller();
+		BallotLogVoters::<T>::insert(&filer, ());
+		let evidence = BoundedVec::try_from(vec![0; T::MaxPayloadLength::get() as usize]).unwrap();
+	}: _(RawOrigin::Signed(filer.clone()), evidence)
+	verify {
+		assert!(Disputes::<T>::contains_key(&filer));
+	}
+
+	resolve_dispute {
+		set_election::<T>(ElectionPhase::Counting);
+		let filer: T::AccountId = whitelisted_caller();
+		let evidence = BoundedVec::try_from(vec![0; T::MaxPayloadLength::get() as usize]).unwrap();
+		let filed = frame_system::Pallet::<T>::block_number();
+		Disputes::<T>::insert(&filer, Dispute { evidence, filed, resolution: None });
+		OpenDisputes::<T>::put(1);
+		// Upholding the dispute also cancels the election
+	}: _(RawOrigin::Signed(ca::<T>()), filer, DisputeResolution::Upheld)
+	verify {
+		assert_eq!(VotingSystem::<T>::get_phase(), Some(ElectionPhase::Cancelled));
+	}
+
+	exclude_candidate {
+		set_election::<T>(ElectionPhase::BiasedSigner);
+		let candidate = set_candidates::<T>(2)[0].clone();
+		register_voter::<T>(0);
+		BlindedMessageCount::<T>::put(1);
+		frame_system::Pallet::<T>::set_block_number(
+			frame_system::Pallet::<T>::block_number() + T::BiasedSignerPeriod::get(),
+		);
+	}: _(RawOrigin::Signed(ca::<T>()), candidate.clone())
+	verify {
+		assert!(ExcludedCandidates::<T>::contains_key(&candidate));
+	}
+
+	register_blinded_messages {
+		let n in 1 .. T::MaxBatchSize::get();
+
+		set_election::<T>(ElectionPhase::BiasedSigner);
+		let candidates = set_candidates::<T>(n);
+		let voter = register_voter::<T>(0);
+		let message =
+			BoundedVec::try_from(vec![1; T::SignatureLength::get() as usize]).unwrap();
+		let messages: Vec<_> =
+			candidates.into_iter().map(|candidate| (candidate, message.clone())).collect();
+		let messages = BoundedVec::try_from(messages).unwrap();
+	}: _(RawOrigin::Signed(ca::<T>()), voter, messages)
+	verify {
+		assert_eq!(VotingSystem::<T>::blinded_message_count(), 1);
+	}
+
+	drop_voter {
+		set_election::<T>(ElectionPhase::BiasedSigner);
+		let voter = register_voter::<T>(0);
+		VoterCredentials::<T>::insert(voter, Ciphertext { a: [1; 256], b: [2; 256] });
+		frame_system::Pallet::<T>::set_block_number(
+			frame_system::Pallet::<T>::block_number() + T::BiasedSignerPeriod::get(),
+		);
+	}: _(RawOrigin::Signed(ca::<T>()), voter)
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert!(!Voters::<T>::contains_key(voter));
 	}
 
-	impl_benchmark_test_suite!(Template, crate::mock::new_test_ext(), crate::mock::Test);
+	impl_benchmark_test_suite!(VotingSystem, crate::mock::new_benchmark_ext(), crate::mock::Test);
 }
@@ -0,0 +1,766 @@
+//! Voting credentials of the coercion resistant election mode.
+//!
+//! Blind signatures keep a ballot from being linked to a voter, but a coercer watching the voting
+//! account of a voter still sees every ballot cast from it. The coercion resistant mode, modelled
+//! after Juels, Catalano and Jakobsson (JCJ), has every ballot carry an ElGamal encrypted
+//! credential:
+//!
+//! - The registrar hands every voter a secret credential and registers its encryption under the
+//!   tally key in `VoterCredentials`.
+//! - A coerced voter gives the coercer a fake credential, or votes with it in front of the coercer.
+//!   Fake credentials are random group elements just like the real ones, and without the tally key
+//!   their encryptions can not be told apart.
+//! - The ballot that counts is cast with the real credential, from the same or any other voting
+//!   account.
+//! - When counting, the holder of the tally key keeps only the last ballot of every credential and
+//!   drops the ballots whose credential matches none of the registered ones. Every step is proven
+//!   in a [`TallyTranscript`], whose hash is submitted with the tally so anybody can check the
+//!   tally with `verify-tally`.
+//!
+//! The tally follows JCJ as implemented by Civitas, with a single holder of the tally secret:
+//!
+//! 1. Duplicate elimination. The credential of every ballot is raised to a secret blinding exponent
+//!    and decrypted, giving a tag that is equal for equal credentials and reveals nothing else. Of
+//!    the ballots with equal tags only the one cast last is kept.
+//! 2. Mixing. The kept ballots, with their commitment encrypted as `g^commitment`, and the
+//!    registered credentials are shuffled and re-encrypted, so nobody can tell which ballot was
+//!    kept in the end or which voter cast it.
+//! 3. Invalid credential elimination. The mixed ballot and registered credentials are tagged with a
+//!    fresh blinding exponent. A mixed ballot counts when its tag is the tag of a registered
+//!    credential, its commitment is then decrypted.
+//!
+//! Blinding and decryption are proven with Chaum-Pedersen proofs of equal discrete logarithms and
+//! the mixes with the cut-and-choose shuffle proof of Sako and Kilian, all made non-interactive
+//! with Fiat-Shamir. Comparing tags instead of running a plaintext equivalence test on every pair
+//! keeps the tally linear in the number of ballots.
+//!
+//! The group is the order `q` subgroup of the 2048 bit MODP group of RFC 3526, generated by 2.
+//! Elements are encoded as 256 byte big endian integers.
+
+use blind_rsa_signatures::reexports::rsa::BigUint;
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+	inherent::Vec,
+	sp_std::collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	RuntimeDebug,
+};
+use rand_chacha::rand_core::RngCore;
+use scale_info::TypeInfo;
+
+/// Size of an encoded group element.
+pub const ELEMENT_BYTES: usize = 256;
+
+/// Size of the random secret exponents.
+const EXPONENT_BYTES: usize = 32;
+
+/// Size of the encoded re-encryption exponents of a shuffle.
+pub const SHUFFLE_EXPONENT_BYTES: usize = 64;
+
+/// Rounds of the shuffle proof, a forged shuffle passes with probability `2^-SHUFFLE_ROUNDS`.
+pub const SHUFFLE_ROUNDS: usize = 80;
+
+/// Big endian encoded re-encryption exponent of a shuffle.
+pub type ShuffleExponent = [u8; SHUFFLE_EXPONENT_BYTES];
+
+/// Big endian encoded element of the group, used for credentials and the tally key.
+pub type GroupElement = [u8; ELEMENT_BYTES];
+
+/// The 2048 bit MODP prime `p = 2q + 1` of RFC 3526.
+const MODULUS: GroupElement = [
+	0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xc9, 0x0f, 0xda, 0xa2, 0x21, 0x68, 0xc2, 0x34,
+	0xc4, 0xc6, 0x62, 0x8b, 0x80, 0xdc, 0x1c, 0xd1, 0x29, 0x02, 0x4e, 0x08, 0x8a, 0x67, 0xcc, 0x74,
+	0x02, 0x0b, 0xbe, 0xa6, 0x3b, 0x13, 0x9b, 0x22, 0x51, 0x4a, 0x08, 0x79, 0x8e, 0x34, 0x04, 0xdd,
+	0xef, 0x95, 0x19, 0xb3, 0xcd, 0x3a, 0x43, 0x1b, 0x30, 0x2b, 0x0a, 0x6d, 0xf2, 0x5f, 0x14, 0x37,
+	0x4f, 0xe1, 0x35, 0x6d, 0x6d, 0x51, 0xc2, 0x45, 0xe4, 0x85, 0xb5, 0x76, 0x62, 0x5e, 0x7e, 0xc6,
+	0xf4, 0x4c, 0x42, 0xe9, 0xa6, 0x37, 0xed, 0x6b, 0x0b, 0xff, 0x5c, 0xb6, 0xf4, 0x06, 0xb7, 0xed,
+	0xee, 0x38, 0x6b, 0xfb, 0x5a, 0x89, 0x9f, 0xa5, 0xae, 0x9f, 0x24, 0x11, 0x7c, 0x4b, 0x1f, 0xe6,
+	0x49, 0x28, 0x66, 0x51, 0xec, 0xe4, 0x5b, 0x3d, 0xc2, 0x00, 0x7c, 0xb8, 0xa1, 0x63, 0xbf, 0x05,
+	0x98, 0xda, 0x48, 0x36, 0x1c, 0x55, 0xd3, 0x9a, 0x69, 0x16, 0x3f, 0xa8, 0xfd, 0x24, 0xcf, 0x5f,
+	0x83, 0x65, 0x5d, 0x23, 0xdc, 0xa3, 0xad, 0x96, 0x1c, 0x62, 0xf3, 0x56, 0x20, 0x85, 0x52, 0xbb,
+	0x9e, 0xd5, 0x29, 0x07, 0x70, 0x96, 0x96, 0x6d, 0x67, 0x0c, 0x35, 0x4e, 0x4a, 0xbc, 0x98, 0x04,
+	0xf1, 0x74, 0x6c, 0x08, 0xca, 0x18, 0x21, 0x7c, 0x32, 0x90, 0x5e, 0x46, 0x2e, 0x36, 0xce, 0x3b,
+	0xe3, 0x9e, 0x77, 0x2c, 0x18, 0x0e, 0x86, 0x03, 0x9b, 0x27, 0x83, 0xa2, 0xec, 0x07, 0xa2, 0x8f,
+	0xb5, 0xc5, 0x5d, 0xf0, 0x6f, 0x4c, 0x52, 0xc9, 0xde, 0x2b, 0xcb, 0xf6, 0x95, 0x58, 0x17, 0x18,
+	0x39, 0x95, 0x49, 0x7c, 0xea, 0x95, 0x6a, 0xe5, 0x15, 0xd2, 0x26, 0x18, 0x98, 0xfa, 0x05, 0x10,
+	0x15, 0x72, 0x8e, 0x5a, 0x8a, 0xac, 0xaa, 0x68, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+];
+
+/// ElGamal encryption `(g^r, m * h^r)` of the group element `m` under the tally key `h`.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Ciphertext {
+	pub a: GroupElement,
+	pub b: GroupElement,
+}
+
+fn modulus() -> BigUint {
+	BigUint::from_bytes_be(&MODULUS)
+}
+
+fn subgroup_order() -> BigUint {
+	(modulus() - BigUint::from(1u32)) >> 1
+}
+
+fn generator() -> BigUint {
+	BigUint::from(2u32)
+}
+
+/// Decode a group element, `None` when it is not an element of the order `q` subgroup
+fn decode_element(element: &GroupElement) -> Option<BigUint> {
+	let x = BigUint::from_bytes_be(element);
+	let one = BigUint::from(1u32);
+	if x < one || x >= modulus() {
+		return None
+	}
+	// A safe prime group only has the subgroups of order 1, 2, q and 2q
+	if x.modpow(&subgroup_order(), &modulus()) != one {
+		return None
+	}
+	Some(x)
+}
+
+fn encode_element(x: &BigUint) -> GroupElement {
+	let bytes = x.to_bytes_be();
+	let mut element = [0u8; ELEMENT_BYTES];
+	element[ELEMENT_BYTES - bytes.len()..].copy_from_slice(&bytes);
+	element
+}
+
+fn inverse(x: &BigUint) -> BigUint {
+	let p = modulus();
+	x.modpow(&(&p - BigUint::from(2u32)), &p)
+}
+
+/// Check that `element` is a valid credential or tally key
+pub fn is_group_element(element: &GroupElement) -> bool {
+	decode_element(element).is_some()
+}
+
+/// Check that both parts of a ciphertext are group elements
+pub fn is_valid_ciphertext(ciphertext: &Ciphertext) -> bool {
+	is_group_element(&ciphertext.a) && is_group_element(&ciphertext.b)
+}
+
+/// Draw a random secret exponent, used for tally keys, credentials and encryption randomness
+pub fn random_exponent(rng: &mut impl RngCore) -> Vec<u8> {
+	let mut exponent = [0u8; EXPONENT_BYTES];
+	rng.fill_bytes(&mut exponent);
+	exponent.to_vec()
+}
+
+/// Raise the generator to the secret `exponent`, giving the tally key of a tally secret
+pub fn public_key(exponent: &[u8]) -> GroupElement {
+	encode_element(&generator().modpow(&BigUint::from_bytes_be(exponent), &modulus()))
+}
+
+/// Draw a new credential. Real and fake credentials are drawn the same way, a fake credential is
+/// simply one the registrar never registered
+pub fn new_credential(rng: &mut impl RngCore) -> GroupElement {
+	public_key(&random_exponent(rng))
+}
+
+/// Encrypt a credential under the tally key, `None` when either is not a group element
+pub fn encrypt(
+	tally_key: &GroupElement,
+	credential: &GroupElement,
+	rng: &mut impl RngCore,
+) -> Option<Ciphertext> {
+	let p = modulus();
+	let h = decode_element(tally_key)?;
+	let m = decode_element(credential)?;
+	let r = BigUint::from_bytes_be(&random_exponent(rng));
+
+	let a = generator().modpow(&r, &p);
+	let b = (m * h.modpow(&r, &p)) % &p;
+	Some(Ciphertext { a: encode_element(&a), b: encode_element(&b) })
+}
+
+/// Raise the generator to `commitment`, its encoding in the ballot mix
+fn commitment_element(commitment: u32) -> BigUint {
+	generator().modpow(&BigUint::from(commitment), &modulus())
+}
+
+/// Decode an integer below the modulus, without checking it lies in the subgroup
+fn decode_residue(element: &GroupElement) -> Option<BigUint> {
+	let x = BigUint::from_bytes_be(element);
+	(x >= BigUint::from(1u32) && x < modulus()).then_some(x)
+}
+
+/// Draw a uniform non zero exponent modulo `q`, used where a proof must not leak the secret
+fn random_scalar(rng: &mut impl RngCore) -> BigUint {
+	let mut bytes = [0u8; ELEMENT_BYTES + 32];
+	rng.fill_bytes(&mut bytes);
+	let x = BigUint::from_bytes_be(&bytes) % subgroup_order();
+	if x == BigUint::from(0u32) {
+		BigUint::from(1u32)
+	} else {
+		x
+	}
+}
+
+/// Draw an integer of `bits` random bits
+fn random_bits(bits: usize, rng: &mut impl RngCore) -> BigUint {
+	let mut bytes = Vec::new();
+	bytes.resize((bits + 7) / 8, 0u8);
+	rng.fill_bytes(&mut bytes);
+	BigUint::from_bytes_be(&bytes) >> (bytes.len() * 8 - bits)
+}
+
+fn encode_shuffle_exponent(x: &BigUint) -> ShuffleExponent {
+	let bytes = x.to_bytes_be();
+	let mut exponent = [0u8; SHUFFLE_EXPONENT_BYTES];
+	exponent[SHUFFLE_EXPONENT_BYTES - bytes.len()..].copy_from_slice(&bytes);
+	exponent
+}
+
+/// Non-interactive Chaum-Pedersen proof that `power_i = base_i ^ e` for one secret `e` and every
+/// `(base_i, power_i)` pair of a statement.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct EqualityProof {
+	pub challenge: [u8; 32],
+	pub response: GroupElement,
+}
+
+/// Fiat-Shamir challenge of an equality proof, binding the whole statement
+fn equality_challenge(statement: &[(BigUint, BigUint)], commitments: &[GroupElement]) -> [u8; 32] {
+	let statement: Vec<_> = statement
+		.iter()
+		.map(|(base, power)| (encode_element(base), encode_element(power)))
+		.collect();
+	sp_io::hashing::blake2_256(&(b"voting-system/equal-logs", statement, commitments).encode())
+}
+
+fn prove_equal_logs(
+	statement: &[(BigUint, BigUint)],
+	exponent: &BigUint,
+	rng: &mut impl RngCore,
+) -> EqualityProof {
+	let p = modulus();
+	let w = random_scalar(rng);
+	let commitments: Vec<_> =
+		statement.iter().map(|(base, _)| encode_element(&base.modpow(&w, &p))).collect();
+	let challenge = equality_challenge(statement, &commitments);
+	let response = (w + BigUint::from_bytes_be(&challenge) * exponent) % subgroup_order();
+	EqualityProof { challenge, response: encode_element(&response) }
+}
+
+fn verify_equal_logs(statement: &[(BigUint, BigUint)], proof: &EqualityProof) -> bool {
+	let p = modulus();
+	let response = BigUint::from_bytes_be(&proof.response);
+	if response >= subgroup_order() {
+		return false
+	}
+	let challenge = BigUint::from_bytes_be(&proof.challenge);
+	// base^response = commitment * power^challenge for the honest commitment
+	let commitments: Vec<_> = statement
+		.iter()
+		.map(|(base, power)| {
+			let commitment = base.modpow(&response, &p) * inverse(power).modpow(&challenge, &p);
+			encode_element(&(commitment % &p))
+		})
+		.collect();
+	equality_challenge(statement, &commitments) == proof.challenge
+}
+
+/// Statement of a decryption: `h = g^x` and `b / plaintext = a^x`
+fn decryption_statement(
+	tally_key: &BigUint,
+	ciphertext: (&BigUint, &BigUint),
+	plaintext: &BigUint,
+) -> Vec<(BigUint, BigUint)> {
+	let (a, b) = ciphertext;
+	let shared = (b * inverse(plaintext)) % modulus();
+	Vec::from([(generator(), tally_key.clone()), (a.clone(), shared)])
+}
+
+/// Tag of an encrypted credential: the credential raised to the blinding exponent of a tally
+/// stage, with the proofs of blinding and decrypting it.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct CredentialTag {
+	/// The ciphertext raised to the blinding exponent
+	pub blinded: Ciphertext,
+	/// Proves the blinding exponent is the exponent of the blinding key
+	pub blinding_proof: EqualityProof,
+	/// Decryption of `blinded`
+	pub tag: GroupElement,
+	pub decryption_proof: EqualityProof,
+}
+
+fn tag_credential(
+	tally_secret: &BigUint,
+	blinding: &BigUint,
+	ciphertext: &Ciphertext,
+	rng: &mut impl RngCore,
+) -> Option<CredentialTag> {
+	let p = modulus();
+	let a = decode_element(&ciphertext.a)?;
+	let b = decode_element(&ciphertext.b)?;
+	let blinding_key = generator().modpow(blinding, &p);
+	let blinded_a = a.modpow(blinding, &p);
+	let blinded_b = b.modpow(blinding, &p);
+	let blinding_proof = prove_equal_logs(
+		&[(generator(), blinding_key), (a, blinded_a.clone()), (b, blinded_b.clone())],
+		blinding,
+		rng,
+	);
+
+	let tag = (&blinded_b * inverse(&blinded_a.modpow(tally_secret, &p))) % &p;
+	let tally_key = generator().modpow(tally_secret, &p);
+	let decryption_proof = prove_equal_logs(
+		&decryption_statement(&tally_key, (&blinded_a, &blinded_b), &tag),
+		tally_secret,
+		rng,
+	);
+	Some(CredentialTag {
+		blinded: Ciphertext { a: encode_element(&blinded_a), b: encode_element(&blinded_b) },
+		blinding_proof,
+		tag: encode_element(&tag),
+		decryption_proof,
+	})
+}
+
+/// Check the tag of `ciphertext` under the blinding key and return it
+fn verify_tag(
+	tally_key: &BigUint,
+	blinding_key: &BigUint,
+	ciphertext: &Ciphertext,
+	tag: &CredentialTag,
+) -> Option<GroupElement> {
+	let a = decode_element(&ciphertext.a)?;
+	let b = decode_element(&ciphertext.b)?;
+	let blinded_a = decode_element(&tag.blinded.a)?;
+	let blinded_b = decode_element(&tag.blinded.b)?;
+	let value = decode_element(&tag.tag)?;
+	let blinded =
+		[(generator(), blinding_key.clone()), (a, blinded_a.clone()), (b, blinded_b.clone())];
+	let decrypted = decryption_statement(tally_key, (&blinded_a, &blinded_b), &value);
+	(verify_equal_logs(&blinded, &tag.blinding_proof) &&
+		verify_equal_logs(&decrypted, &tag.decryption_proof))
+	.then_some(tag.tag)
+}
+
+/// Tag every credential of a tally stage, returning the blinding key and the tags
+fn tag_credentials<'a>(
+	tally_secret: &BigUint,
+	credentials: impl Iterator<Item = &'a Ciphertext>,
+	rng: &mut impl RngCore,
+) -> Option<(GroupElement, Vec<CredentialTag>)> {
+	let blinding = random_scalar(rng);
+	let tags = credentials
+		.map(|credential| tag_credential(tally_secret, &blinding, credential, rng))
+		.collect::<Option<Vec<_>>>()?;
+	Some((encode_element(&generator().modpow(&blinding, &modulus())), tags))
+}
+
+/// Check the tags of a tally stage and return them, `None` when a tag is invalid. The blinding
+/// key must not be 1, which would give every credential the same tag
+fn verify_tags<'a>(
+	tally_key: &BigUint,
+	blinding_key: &GroupElement,
+	credentials: impl ExactSizeIterator<Item = &'a Ciphertext>,
+	tags: &[CredentialTag],
+) -> Option<Vec<GroupElement>> {
+	let blinding_key = decode_element(blinding_key).filter(|key| *key != BigUint::from(1u32))?;
+	if credentials.len() != tags.len() {
+		return None
+	}
+	credentials
+		.zip(tags)
+		.map(|(credential, tag)| verify_tag(tally_key, &blinding_key, credential, tag))
+		.collect()
+}
+
+/// Round of the shuffle proof: an intermediate shuffle of the input and the opening of the step
+/// from the input to it, or from it to the output, as chosen by the challenge bit of the round.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ShuffleRound {
+	pub shuffled: Vec<Vec<Ciphertext>>,
+	/// Position every item of the opened step was taken from
+	pub permutation: Vec<u32>,
+	/// Re-encryption exponents of every ciphertext of the opened step
+	pub exponents: Vec<Vec<ShuffleExponent>>,
+}
+
+/// Sako-Kilian proof that a list of ciphertext tuples is a re-encrypted permutation of another.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ShuffleProof {
+	pub rounds: Vec<ShuffleRound>,
+}
+
+/// Re-encrypt `(a, b)` to `(a * g^r, b * h^r)`, `None` when it is out of range
+fn reencrypt(tally_key: &BigUint, ciphertext: &Ciphertext, r: &BigUint) -> Option<Ciphertext> {
+	let p = modulus();
+	let a = decode_residue(&ciphertext.a)? * generator().modpow(r, &p) % &p;
+	let b = decode_residue(&ciphertext.b)? * tally_key.modpow(r, &p) % &p;
+	Some(Ciphertext { a: encode_element(&a), b: encode_element(&b) })
+}
+
+/// Apply a shuffle step, item `i` of the result re-encrypts item `permutation[i]` of `items`
+fn apply_shuffle(
+	tally_key: &BigUint,
+	items: &[Vec<Ciphertext>],
+	permutation: &[u32],
+	exponents: &[Vec<BigUint>],
+) -> Option<Vec<Vec<Ciphertext>>> {
+	permutation
+		.iter()
+		.zip(exponents)
+		.map(|(from, exponents)| {
+			let item = items.get(*from as usize)?;
+			if item.len() != exponents.len() {
+				return None
+			}
+			item.iter().zip(exponents).map(|(c, r)| reencrypt(tally_key, c, r)).collect()
+		})
+		.collect()
+}
+
+fn random_permutation(n: usize, rng: &mut impl RngCore) -> Vec<u32> {
+	let mut permutation: Vec<u32> = (0..n as u32).collect();
+	for i in (1..n).rev() {
+		let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+		permutation.swap(i, j);
+	}
+	permutation
+}
+
+/// Fiat-Shamir challenge bits of a shuffle proof, one per round
+fn shuffle_challenge(
+	input: &[Vec<Ciphertext>],
+	output: &[Vec<Ciphertext>],
+	shuffled: &[&Vec<Vec<Ciphertext>>],
+) -> Vec<bool> {
+	let hash =
+		sp_io::hashing::blake2_256(&(b"voting-system/shuffle", input, output, shuffled).encode());
+	(0..SHUFFLE_ROUNDS)
+		.map(|round| hash[round / 8] >> (round % 8) & 1 == 1)
+		.collect()
+}
+
+/// Shuffle and re-encrypt `input`, returning the output with the proof of the shuffle.
+///
+/// The output takes its exponents from `[2^448, 2^448 + 2^256)`, the intermediate shuffles from
+/// `[0, 2^384)`, so the exponents of the step from an intermediate shuffle to the output stay
+/// positive and hide the output exponents. The short exponents keep the proof fast.
+fn shuffle(
+	tally_key: &BigUint,
+	input: &[Vec<Ciphertext>],
+	rng: &mut impl RngCore,
+) -> Option<(Vec<Vec<Ciphertext>>, ShuffleProof)> {
+	let n = input.len();
+	let width = |i: usize| input[i].len();
+	let offset = BigUint::from(1u32) << 448;
+
+	let permutation = random_permutation(n, rng);
+	let exponents: Vec<Vec<BigUint>> = permutation
+		.iter()
+		.map(|from| (0..width(*from as usize)).map(|_| &offset + random_bits(256, rng)).collect())
+		.collect();
+	let output = apply_shuffle(tally_key, input, &permutation, &exponents)?;
+
+	let mut rounds = Vec::new();
+	for _ in 0..SHUFFLE_ROUNDS {
+		let round_permutation = random_permutation(n, rng);
+		let round_exponents: Vec<Vec<BigUint>> = round_permutation
+			.iter()
+			.map(|from| (0..width(*from as usize)).map(|_| random_bits(384, rng)).collect())
+			.collect();
+		let shuffled = apply_shuffle(tally_key, input, &round_permutation, &round_exponents)?;
+		rounds.push((shuffled, round_permutation, round_exponents));
+	}
+
+	let shuffled: Vec<_> = rounds.iter().map(|(shuffled, _, _)| shuffled).collect();
+	let challenge = shuffle_challenge(input, &output, &shuffled);
+
+	let encode = |exponents: Vec<Vec<BigUint>>| -> Vec<Vec<ShuffleExponent>> {
+		exponents
+			.iter()
+			.map(|item| item.iter().map(encode_shuffle_exponent).collect())
+			.collect()
+	};
+	let rounds = rounds
+		.into_iter()
+		.zip(challenge)
+		.map(|((shuffled, round_permutation, round_exponents), to_output)| {
+			if !to_output {
+				return ShuffleRound {
+					shuffled,
+					permutation: round_permutation,
+					exponents: encode(round_exponents),
+				}
+			}
+			// Output item i is input item permutation[i], which is shuffled item
+			// position[permutation[i]]
+			let mut position = Vec::new();
+			position.resize(n, 0u32);
+			for (i, from) in round_permutation.iter().enumerate() {
+				position[*from as usize] = i as u32;
+			}
+			let step: Vec<u32> = permutation.iter().map(|from| position[*from as usize]).collect();
+			let step_exponents = step
+				.iter()
+				.zip(&exponents)
+				.map(|(from, item)| {
+					item.iter()
+						.zip(&round_exponents[*from as usize])
+						.map(|(total, done)| total - done)
+						.collect()
+				})
+				.collect();
+			ShuffleRound { shuffled, permutation: step, exponents: encode(step_exponents) }
+		})
+		.collect();
+	Some((output, ShuffleProof { rounds }))
+}
+
+/// Check that `output` is a re-encrypted permutation of `input`
+fn verify_shuffle(
+	tally_key: &BigUint,
+	input: &[Vec<Ciphertext>],
+	output: &[Vec<Ciphertext>],
+	proof: &ShuffleProof,
+) -> bool {
+	let n = input.len();
+	if output.len() != n || proof.rounds.len() != SHUFFLE_ROUNDS {
+		return false
+	}
+	let shuffled: Vec<_> = proof.rounds.iter().map(|round| &round.shuffled).collect();
+	let challenge = shuffle_challenge(input, output, &shuffled);
+
+	proof.rounds.iter().zip(challenge).all(|(round, to_output)| {
+		let (from, to) =
+			if to_output { (&round.shuffled[..], output) } else { (input, &round.shuffled[..]) };
+		// Every item is taken exactly once
+		let taken: BTreeSet<_> = round.permutation.iter().collect();
+		if from.len() != n || round.permutation.len() != n || taken.len() != n {
+			return false
+		}
+		let exponents: Vec<Vec<BigUint>> = round
+			.exponents
+			.iter()
+			.map(|item| item.iter().map(|r| BigUint::from_bytes_be(r)).collect())
+			.collect();
+		round.exponents.len() == n &&
+			apply_shuffle(tally_key, from, &round.permutation, &exponents).as_deref() == Some(to)
+	})
+}
+
+/// Ballot counted by the tally, with the decryption of its commitment.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct CountedBallot {
+	/// Position of the ballot in the ballot mix
+	pub index: u32,
+	pub commitment: u32,
+	pub decryption_proof: EqualityProof,
+}
+
+/// Proof that a credential tally counts the last ballot of every registered credential, see the
+/// module docs. The hash of the transcript is submitted with the tally.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct TallyTranscript {
+	/// Blinding key of the duplicate elimination
+	pub duplicate_key: GroupElement,
+	/// Tags of the ballot credentials in casting order
+	pub duplicate_tags: Vec<CredentialTag>,
+	/// Ballots left after the duplicate elimination, mixed, as (commitment, credential) pairs
+	pub mixed_ballots: Vec<Vec<Ciphertext>>,
+	pub ballot_shuffle: ShuffleProof,
+	/// Registered credentials, mixed
+	pub mixed_credentials: Vec<Vec<Ciphertext>>,
+	pub credential_shuffle: ShuffleProof,
+	/// Blinding key of the invalid credential elimination
+	pub validity_key: GroupElement,
+	/// Tags of the credentials of the mixed ballots
+	pub ballot_tags: Vec<CredentialTag>,
+	/// Tags of the mixed registered credentials
+	pub credential_tags: Vec<CredentialTag>,
+	/// Mixed ballots with a registered credential, in mix order
+	pub counted: Vec<CountedBallot>,
+}
+
+/// Step of the tally a transcript failed to prove.
+#[derive(Clone, Copy, Eq, PartialEq, RuntimeDebug)]
+pub enum TranscriptError {
+	/// A credential tag of the duplicate elimination is invalid
+	DuplicateTags,
+	/// The ballot mix is not a shuffle of the ballots left after the duplicate elimination
+	BallotShuffle,
+	/// The credential mix is not a shuffle of the registered credentials
+	CredentialShuffle,
+	/// A credential tag of the invalid credential elimination is invalid
+	ValidityTags,
+	/// The counted ballots are not the mixed ballots with a registered credential, or a commitment
+	/// is decrypted wrongly
+	CountedBallots,
+}
+
+/// Input of the ballot mix: the last ballot of every credential, by the tags of the duplicate
+/// elimination, with its commitment encoded as a trivial encryption of `g^commitment`
+fn unique_ballots(ballots: &[(u32, Ciphertext)], tags: &[GroupElement]) -> Vec<Vec<Ciphertext>> {
+	let mut seen = BTreeSet::new();
+	let mut unique: Vec<_> = ballots
+		.iter()
+		.zip(tags)
+		.rev()
+		.filter(|(_, tag)| seen.insert(*tag))
+		.map(|((commitment, credential), _)| {
+			let commitment = Ciphertext {
+				a: encode_element(&BigUint::from(1u32)),
+				b: encode_element(&commitment_element(*commitment)),
+			};
+			Vec::from([commitment, credential.clone()])
+		})
+		.collect();
+	unique.reverse();
+	unique
+}
+
+/// Count the credential ballots, given in casting order as `(commitment, credential)` pairs, and
+/// prove the tally. Only the last ballot cast with a credential counts, and only when the
+/// credential is one of the `registered` credentials. `None` when a ciphertext is invalid.
+pub fn prove_tally(
+	tally_secret: &[u8],
+	registered: &[Ciphertext],
+	ballots: &[(u32, Ciphertext)],
+	rng: &mut impl RngCore,
+) -> Option<TallyTranscript> {
+	let p = modulus();
+	let x = BigUint::from_bytes_be(tally_secret);
+	let tally_key = generator().modpow(&x, &p);
+
+	let (duplicate_key, duplicate_tags) =
+		tag_credentials(&x, ballots.iter().map(|(_, credential)| credential), rng)?;
+	let tags: Vec<_> = duplicate_tags.iter().map(|tag| tag.tag).collect();
+	let (mixed_ballots, ballot_shuffle) =
+		shuffle(&tally_key, &unique_ballots(ballots, &tags), rng)?;
+
+	let registered: Vec<_> = registered.iter().map(|c| Vec::from([c.clone()])).collect();
+	let (mixed_credentials, credential_shuffle) = shuffle(&tally_key, &registered, rng)?;
+
+	let blinding = random_scalar(rng);
+	let validity_key = encode_element(&generator().modpow(&blinding, &p));
+	let tag_all = |items: &[Vec<Ciphertext>], rng: &mut _| {
+		items
+			.iter()
+			.map(|item| tag_credential(&x, &blinding, item.last()?, rng))
+			.collect::<Option<Vec<_>>>()
+	};
+	let ballot_tags = tag_all(&mixed_ballots, rng)?;
+	let credential_tags = tag_all(&mixed_credentials, rng)?;
+
+	// The commitments are decrypted to `g^commitment`, matched against the cast commitments
+	let commitments: BTreeMap<_, _> = ballots
+		.iter()
+		.map(|(commitment, _)| (encode_element(&commitment_element(*commitment)), *commitment))
+		.collect();
+	let registered_tags: BTreeSet<_> = credential_tags.iter().map(|tag| tag.tag).collect();
+	let mut counted = Vec::new();
+	for (index, (ballot, tag)) in mixed_ballots.iter().zip(&ballot_tags).enumerate() {
+		if !registered_tags.contains(&tag.tag) {
+			continue
+		}
+		let a = decode_element(&ballot[0].a)?;
+		let b = decode_element(&ballot[0].b)?;
+		let plaintext = (&b * inverse(&a.modpow(&x, &p))) % &p;
+		let commitment = *commitments.get(&encode_element(&plaintext))?;
+		let decryption_proof =
+			prove_equal_logs(&decryption_statement(&tally_key, (&a, &b), &plaintext), &x, rng);
+		counted.push(CountedBallot { index: index as u32, commitment, decryption_proof });
+	}
+
+	Some(TallyTranscript {
+		duplicate_key,
+		duplicate_tags,
+		mixed_ballots,
+		ballot_shuffle,
+		mixed_credentials,
+		credential_shuffle,
+		validity_key,
+		ballot_tags,
+		credential_tags,
+		counted,
+	})
+}
+
+/// Verify the transcript of a credential tally against the tally key, the registered credentials
+/// and the credential ballots in casting order. Returns the votes per commitment it proves.
+pub fn verify_tally(
+	tally_key: &GroupElement,
+	registered: &[Ciphertext],
+	ballots: &[(u32, Ciphertext)],
+	transcript: &TallyTranscript,
+) -> Result<BTreeMap<u32, u32>, TranscriptError> {
+	use TranscriptError::*;
+	let tally_key = decode_element(tally_key).ok_or(DuplicateTags)?;
+
+	let tags = verify_tags(
+		&tally_key,
+		&transcript.duplicate_key,
+		ballots.iter().map(|(_, credential)| credential),
+		&transcript.duplicate_tags,
+	)
+	.ok_or(DuplicateTags)?;
+	let unique = unique_ballots(ballots, &tags);
+	if transcript.mixed_ballots.iter().any(|ballot| ballot.len() != 2) ||
+		!verify_shuffle(
+			&tally_key,
+			&unique,
+			&transcript.mixed_ballots,
+			&transcript.ballot_shuffle,
+		) {
+		return Err(BallotShuffle)
+	}
+
+	let registered: Vec<_> = registered.iter().map(|c| Vec::from([c.clone()])).collect();
+	if transcript.mixed_credentials.iter().any(|credential| credential.len() != 1) ||
+		!verify_shuffle(
+			&tally_key,
+			&registered,
+			&transcript.mixed_credentials,
+			&transcript.credential_shuffle,
+		) {
+		return Err(CredentialShuffle)
+	}
+
+	let ballot_tags = verify_tags(
+		&tally_key,
+		&transcript.validity_key,
+		transcript.mixed_ballots.iter().map(|ballot| &ballot[1]),
+		&transcript.ballot_tags,
+	)
+	.ok_or(ValidityTags)?;
+	let registered_tags: BTreeSet<_> = verify_tags(
+		&tally_key,
+		&transcript.validity_key,
+		transcript.mixed_credentials.iter().map(|credential| &credential[0]),
+		&transcript.credential_tags,
+	)
+	.ok_or(ValidityTags)?
+	.into_iter()
+	.collect();
+
+	// Exactly the mixed ballots with a registered credential are counted
+	let valid: Vec<u32> = ballot_tags
+		.iter()
+		.enumerate()
+		.filter(|(_, tag)| registered_tags.contains(*tag))
+		.map(|(index, _)| index as u32)
+		.collect();
+	let counted: Vec<u32> = transcript.counted.iter().map(|ballot| ballot.index).collect();
+	if valid != counted {
+		return Err(CountedBallots)
+	}
+
+	let mut tally = BTreeMap::new();
+	for ballot in &transcript.counted {
+		let commitment = &transcript.mixed_ballots[ballot.index as usize][0];
+		let a = decode_element(&commitment.a).ok_or(CountedBallots)?;
+		let b = decode_element(&commitment.b).ok_or(CountedBallots)?;
+		let statement =
+			decryption_statement(&tally_key, (&a, &b), &commitment_element(ballot.commitment));
+		if !verify_equal_logs(&statement, &ballot.decryption_proof) {
+			return Err(CountedBallots)
+		}
+		*tally.entry(ballot.commitment).or_insert(0) += 1;
+	}
+	Ok(tally)
+}
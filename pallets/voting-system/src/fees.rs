@@ -0,0 +1,82 @@
+//! Fee exemption of the election duties.
+//!
+//! Voters vote from fresh anonymous accounts that hold no funds, and candidates and the CA should
+//! not have to pay for taking part in the election. [`FeeExemptCalls`] wraps the
+//! `OnChargeTransaction` of the runtime and lets these calls through without a fee:
+//!
+//! - the first two ballots a voting account submits, `vote`, `vote_timelocked` and
+//!   `vote_with_credential` during voting, with a valid signature set,
+//! - `biased_signing` and `biased_signing_batch` by the candidate, during biased signing,
+//! - `add_voter` and `add_voters` by the CA, during registration,
+//! - `register_blinded_messages` by the CA, during biased signing.
+//!
+//! The calls are checked with the cheap checks of their dispatch, see `Pallet::is_fee_exempt`.
+//! The signatures of a ballot are verified by `CheckVotingCall`, which the runtime has to run
+//! before `ChargeTransactionPayment`, so a ballot with a forged signature set is rejected before a
+//! fee is decided on. Free ballots are counted when their fee is waived, not when they are cast, so
+//! a ballot failing on dispatch uses one up as well and a voting account can not keep the chain
+//! busy for free. Any other call, or an exempt call by anybody else, is charged by the wrapped
+//! `OnChargeTransaction` and rejected when the sender can not pay.
+
+use crate::{Call, Config, Pallet};
+use core::marker::PhantomData;
+use frame_support::traits::IsSubType;
+use pallet_transaction_payment::OnChargeTransaction;
+use sp_runtime::{
+	traits::{DispatchInfoOf, PostDispatchInfoOf},
+	transaction_validity::TransactionValidityError,
+};
+
+/// Ballots a voting account submits for free, its first ballot and one replacement
+pub const FREE_BALLOTS: u32 = 2;
+
+/// `OnChargeTransaction` charging every call with `OnCharge` except the election duties.
+pub struct FeeExemptCalls<T, OnCharge>(PhantomData<(T, OnCharge)>);
+
+impl<T, OnCharge> OnChargeTransaction<T> for FeeExemptCalls<T, OnCharge>
+where
+	T: Config + pallet_transaction_payment::Config,
+	<T as frame_system::Config>::RuntimeCall: IsSubType<Call<T>>,
+	OnCharge: OnChargeTransaction<T>,
+{
+	type Balance = OnCharge::Balance;
+	// Nothing was withdrawn for an exempt call
+	type LiquidityInfo = Option<OnCharge::LiquidityInfo>;
+
+	fn withdraw_fee(
+		who: &T::AccountId,
+		call: &<T as frame_system::Config>::RuntimeCall,
+		dispatch_info: &DispatchInfoOf<<T as frame_system::Config>::RuntimeCall>,
+		fee: Self::Balance,
+		tip: Self::Balance,
+	) -> Result<Self::LiquidityInfo, TransactionValidityError> {
+		if let Some(call) = call.is_sub_type() {
+			if Pallet::<T>::is_fee_exempt(who, call) {
+				Pallet::<T>::note_free_ballot(who, call);
+				return Ok(None)
+			}
+		}
+		OnCharge::withdraw_fee(who, call, dispatch_info, fee, tip).map(Some)
+	}
+
+	fn correct_and_deposit_fee(
+		who: &T::AccountId,
+		dispatch_info: &DispatchInfoOf<<T as frame_system::Config>::RuntimeCall>,
+		post_info: &PostDispatchInfoOf<<T as frame_system::Config>::RuntimeCall>,
+		corrected_fee: Self::Balance,
+		tip: Self::Balance,
+		already_withdrawn: Self::LiquidityInfo,
+	) -> Result<(), TransactionValidityError> {
+		match already_withdrawn {
+			Some(liquidity) => OnCharge::correct_and_deposit_fee(
+				who,
+				dispatch_info,
+				post_info,
+				corrected_fee,
+				tip,
+				liquidity,
+			),
+			None => Ok(()),
+		}
+	}
+}
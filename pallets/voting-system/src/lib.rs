@@ -10,15 +10,44 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+pub mod credentials;
+pub mod fees;
+pub mod migrations;
+mod offchain;
+pub mod runtime_api;
+pub mod timelock;
+pub mod validation;
+pub mod weights;
+
+pub use offchain::{crypto, rsa_key_storage_key, KEY_TYPE};
+
 #[frame_support::pallet]
 pub mod pallet {
 
-	use frame_support::{inherent::Vec, pallet_prelude::*};
-	use frame_system::pallet_prelude::*;
+	use crate::{
+		credentials::{self, Ciphertext, GroupElement},
+		fees::FREE_BALLOTS,
+		timelock::{self, TimelockBeacon, TimelockCiphertext},
+		validation,
+		weights::WeightInfo,
+	};
+	use frame_support::{inherent::Vec, pallet_prelude::*, storage::with_storage_layer};
+	use frame_system::{offchain::CreateSignedTransaction, pallet_prelude::*};
 	use scale_info::prelude::string::String;
+	use sp_runtime::{
+		traits::{Hash, Saturating},
+		SaturatedConversion,
+	};
+
+	/// The current storage version, see the `migrations` module for the migrations between versions
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+	/// Entries of the ballot log returned at once, see `ballot_log_page`
+	pub const BALLOT_LOG_PAGE: u64 = 1024;
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
@@ -30,6 +59,8 @@ pub mod pallet {
 		Voting,
 		Counting,
 		Completed,
+		/// The election was cancelled, see `cancel_election`
+		Cancelled,
 	}
 
 	impl ElectionPhase {
@@ -43,12 +74,39 @@ pub mod pallet {
 				Voting => Counting,
 				Counting => Completed,
 				Completed => Completed,
+				Cancelled => Cancelled,
 			}
 		}
 	}
 
+	/// How often a voter may cast a ballot. Only the last ballot of a voter is counted
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+	pub enum RevotePolicy {
+		/// A ballot can not be replaced
+		SingleVote,
+		/// A ballot can be replaced any number of times
+		LastVoteCounts,
+		/// A ballot can be replaced at most the given number of times
+		MaxRevotes(u32),
+	}
+
+	impl Default for RevotePolicy {
+		fn default() -> Self {
+			// Replacing a ballot lets a coerced voter vote again in private
+			RevotePolicy::LastVoteCounts
+		}
+	}
+
+	/// Voters are identified by the hash of their blinded key, see `voter_id`. Unlike a counter
+	/// the id tells nothing about the registration order and stays the same whoever else is
+	/// registered
+	pub type VoterId<T> = <T as frame_system::Config>::Hash;
+
 	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
 	pub struct Voter {
+		// Identifies the voter, see `voter_id`. The messages the candidates sign are registered
+		// per candidate with `register_blinded_messages`
 		pub blinded_pubkey: Vec<u8>,
 		pub is_eligible: bool,
 		// Signed by CA after verifying eligibility
@@ -80,7 +138,7 @@ pub mod pallet {
 	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
 	pub struct Ballot {
 		pub commitment: u32,
-		pub signature: Vec<u8>, // TODO: There needs to be one for each candidate
+		pub signature: Vec<u8>, // SCALE encoded signature set, one signature per candidate
 		pub nonce: u64,
 	}
 	/// Todo: determine maximum length of struct storage
@@ -90,6 +148,130 @@ pub mod pallet {
 		}
 	}
 
+	/// Ballot of a timelock election, its commitment is encrypted to the timelock round and only
+	/// decrypted by the tally, see the `timelock` module
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct TimelockedBallot {
+		pub ciphertext: TimelockCiphertext,
+		pub signature: Vec<u8>, // SCALE encoded signature set, one signature per candidate
+		pub nonce: u64,
+	}
+	/// Todo: determine maximum length of struct storage
+	impl MaxEncodedLen for TimelockedBallot {
+		fn max_encoded_len() -> usize {
+			usize::MAX - 1
+		}
+	}
+
+	/// Ballot of the coercion resistant mode, only counted when `credential` encrypts the
+	/// registered credential of a voter
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct CredentialBallot {
+		pub commitment: u32,
+		pub credential: Ciphertext,
+		pub signature: Vec<u8>, // SCALE encoded signature set, one signature per candidate
+	}
+	/// Todo: determine maximum length of struct storage
+	impl MaxEncodedLen for CredentialBallot {
+		fn max_encoded_len() -> usize {
+			usize::MAX - 1
+		}
+	}
+
+	/// Entry of the append-only ballot log. Every cast ballot is appended, including the ballots
+	/// that were replaced later
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct BallotLogEntry<AccountId, Hash, BlockNumber> {
+		pub voter: AccountId,
+		pub ballot: Vec<u8>, // SCALE encoded `Ballot` or `CredentialBallot`
+		pub receipt: Hash,
+		pub block: BlockNumber,
+	}
+	/// Todo: determine maximum length of struct storage
+	impl<AccountId, Hash, BlockNumber> MaxEncodedLen for BallotLogEntry<AccountId, Hash, BlockNumber> {
+		fn max_encoded_len() -> usize {
+			usize::MAX - 1
+		}
+	}
+
+	/// Proof that a ballot receipt is part of the ballot log, see `verify_inclusion_proof`. The
+	/// log is a Merkle mountain range over the receipts, the proof holds the path from the
+	/// receipt to the peak of its mountain and the other peaks of the range
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct InclusionProof<Hash> {
+		/// Position of the ballot in the log
+		pub index: u64,
+		/// Number of ballots in the log the proof leads to
+		pub length: u64,
+		/// Siblings on the path from the receipt to its peak, lowest first
+		pub path: Vec<Hash>,
+		/// Peaks of the other mountains of the range, highest mountain first
+		pub peaks: Vec<Hash>,
+	}
+
+	/// A ballot of the log with the proof of its inclusion, as served by the runtime API
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct BallotInclusion<AccountId, Hash, BlockNumber> {
+		pub entry: BallotLogEntry<AccountId, Hash, BlockNumber>,
+		pub proof: InclusionProof<Hash>,
+		/// Root of the log the proof leads to
+		pub root: Hash,
+		/// Whether `root` is the root published at the end of voting or the current one
+		pub published: bool,
+	}
+
+	/// Results of the election without its ballots, as served by the runtime API. The ballots are
+	/// read from the ballot log page by page, see `ballot_log_page`
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct ElectionSummary<AccountId, BlockNumber> {
+		pub phase: Option<ElectionPhase>,
+		/// Winning commitment, set once the ballots are counted
+		pub winner: Option<u32>,
+		/// Votes per commitment, ordered by commitment
+		pub tally: Vec<(u32, u32)>,
+		pub registered_voters: u64,
+		/// Whether the ballots are credential ballots, see `vote_with_credential`
+		pub credentials: bool,
+		/// Timelock round the ballots are encrypted to, see `vote_timelocked`
+		pub timelock_round: Option<u64>,
+		/// Beacon signature of the timelock round, the key of the time-locked ballots
+		pub beacon_signature: Option<Vec<u8>>,
+		/// Number of time-locked ballots that did not decrypt to a commitment
+		pub rejected_ballots: u32,
+		/// Number of ballots in the log, including replaced ballots
+		pub ballot_log_length: u64,
+		/// Block every phase started at, in the order of the phases
+		pub timeline: Vec<(ElectionPhase, BlockNumber)>,
+		pub excluded_candidates: Vec<(AccountId, Exclusion<BlockNumber>)>,
+	}
+
+	/// How the CA resolved a dispute
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum DisputeResolution {
+		/// The dispute has no merit, the result stands
+		Dismissed,
+		/// The dispute has merit, the election is cancelled
+		Upheld,
+	}
+
+	/// Dispute of the election result, filed by a candidate or a voter during the dispute period
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct Dispute<BlockNumber, Evidence> {
+		pub evidence: Evidence,
+		pub filed: BlockNumber,
+		// `None` until the CA resolves the dispute
+		pub resolution: Option<DisputeResolution>,
+	}
+
+	/// Exclusion of a candidate that did not sign every voter before the biased signing deadline
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct Exclusion<BlockNumber> {
+		pub excluded: BlockNumber,
+		// Voters the candidate signed and voters registered when they were excluded
+		pub signed: u64,
+		pub voters: u64,
+	}
+
 	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
 	pub struct BlindSignature {
 		// Candidate Lookup key
@@ -112,10 +294,34 @@ pub mod pallet {
 	}
 
 	#[pallet::config]
-	pub trait Config: frame_system::Config {
+	pub trait Config: CreateSignedTransaction<Call<Self>> + frame_system::Config {
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+		/// Account keys the offchain worker signs the biased signing transactions with
+		type AuthorityId: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>;
 		#[pallet::constant]
 		type SignatureLength: Get<u32>;
+		/// Minimum modulus size in bits of a candidates RSA public key
+		#[pallet::constant]
+		type MinKeyBits: Get<u32>;
+		/// Maximum number of items accepted by the batch calls, and of vote counts in a credential
+		/// tally
+		#[pallet::constant]
+		type MaxBatchSize: Get<u32>;
+		/// Maximum length of the names, keys and hashes passed to a call, checked by
+		/// `validation::CheckVotingCall` before the call enters the transaction pool
+		#[pallet::constant]
+		type MaxPayloadLength: Get<u32>;
+		/// Number of blocks the candidates have to sign every voter once biased signing started.
+		/// After that the CA can exclude the candidates that did not
+		#[pallet::constant]
+		type BiasedSignerPeriod: Get<Self::BlockNumber>;
+		/// Number of blocks after the tally during which the result can be disputed
+		#[pallet::constant]
+		type DisputePeriod: Get<Self::BlockNumber>;
+		/// Beacon the ballots of a timelock election are encrypted to, see the `timelock` module
+		type Beacon: TimelockBeacon;
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
 	}
 
 	#[pallet::storage]
@@ -133,25 +339,57 @@ pub mod pallet {
 	#[pallet::getter(fn phase)]
 	pub type Phase<T: Config> = StorageValue<_, ElectionPhase, OptionQuery>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn phase_change)] // phase -> block the phase started at
+	pub type PhaseChanges<T: Config> =
+		StorageMap<_, Twox64Concat, ElectionPhase, T::BlockNumber, OptionQuery>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn candidates)]
 	pub type Candidates<T: Config> =
 		StorageMap<_, Twox64Concat, T::AccountId, Candidate, OptionQuery>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn candidate_nominations)] // candidates waiting for CA approval
+	pub type CandidateNominations<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, Candidate, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn candidate_key_fingerprint)] // candidate_id -> hash of the RSA public key
+	pub type CandidateKeyFingerprints<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, T::Hash, OptionQuery>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn candidates_count)]
 	pub type CandidatesCount<T: Config> = StorageValue<_, u64, OptionQuery>;
 
 	#[pallet::storage]
-	#[pallet::getter(fn voters)]
-	pub type Voters<T: Config> = StorageMap<_, Twox64Concat, u64, Voter, OptionQuery>;
+	#[pallet::getter(fn voters)] // voter_id -> voter, counted for the completeness checks
+	pub type Voters<T: Config> =
+		CountedStorageMap<_, Blake2_128Concat, VoterId<T>, Voter, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn blinded_message)] // (voter_id, candidate_id) -> message the candidate signs
+	pub type BlindedMessages<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		VoterId<T>,
+		Twox64Concat,
+		T::AccountId,
+		BoundedVec<u8, T::SignatureLength>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn blinded_message_count)] // number of voters with registered messages
+	pub type BlindedMessageCount<T: Config> = StorageValue<_, u64, ValueQuery>;
 
 	#[pallet::storage]
 	#[pallet::getter(fn blinded_signatures)] // (voter_id, candidate_id) -> signature
 	pub type BlindedSignatures<T: Config> = StorageDoubleMap<
 		_,
-		Twox64Concat,
-		u64,
+		Blake2_128Concat,
+		VoterId<T>,
 		Twox64Concat,
 		T::AccountId,
 		BoundedVec<u8, T::SignatureLength>,
@@ -159,26 +397,208 @@ pub mod pallet {
 	>;
 
 	#[pallet::storage]
-	#[pallet::getter(fn voter_count)]
-	pub type VoterCount<T: Config> = StorageValue<_, u64, OptionQuery>;
+	#[pallet::getter(fn signed_voter_count)] // candidate_id -> number of voters they signed
+	pub type SignedVoterCount<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, u64, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn excluded_candidate)] // candidate_id -> exclusion from signing
+	pub type ExcludedCandidates<T: Config> =
+		CountedStorageMap<_, Twox64Concat, T::AccountId, Exclusion<T::BlockNumber>, OptionQuery>;
 
 	#[pallet::storage]
 	#[pallet::getter(fn ballots)]
 	pub type Ballots<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, Ballot, OptionQuery>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn revote_policy)]
+	pub type RevotingPolicy<T: Config> = StorageValue<_, RevotePolicy, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn credential_key)] // set when the election runs in coercion resistant mode
+	pub type CredentialKey<T: Config> = StorageValue<_, GroupElement, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn timelock_round)] // set when counting is time-locked to a beacon round
+	pub type TimelockRound<T: Config> = StorageValue<_, u64, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn beacon_signature)] // signature of the timelock round, starts the tally
+	pub type BeaconSignature<T: Config> =
+		StorageValue<_, BoundedVec<u8, T::MaxPayloadLength>, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn voter_credential)] // voter_id -> encrypted credential
+	pub type VoterCredentials<T: Config> =
+		CountedStorageMap<_, Blake2_128Concat, VoterId<T>, Ciphertext, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn credential_ballot)] // index in casting order -> ballot
+	pub type CredentialBallots<T: Config> =
+		StorageMap<_, Twox64Concat, u32, CredentialBallot, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn credential_ballot_count)]
+	pub type CredentialBallotCount<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn credential_tally_transcript)] // hash of the proof of the credential tally
+	pub type CredentialTallyTranscript<T: Config> = StorageValue<_, T::Hash, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn ballot_log_entry)] // index in casting order -> entry
+	pub type BallotLog<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		u64,
+		BallotLogEntry<T::AccountId, T::Hash, T::BlockNumber>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn ballot_log_length)]
+	pub type BallotLogLength<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn ballot_log_node)] // (height, index) -> node of the Merkle mountain range
+	pub type BallotLogNodes<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, u32, Twox64Concat, u64, T::Hash, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn ballot_log_index)] // ballot receipt -> index in the log
+	pub type BallotLogIndex<T: Config> = StorageMap<_, Identity, T::Hash, u64, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn ballot_log_voter)] // voting account -> cast a ballot in the log
+	pub type BallotLogVoters<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, (), OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn free_ballots)] // voting account -> number of ballots submitted without a fee
+	pub type FreeBallots<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn published_ballot_log)] // (length, root) of the log at the end of voting
+	pub type PublishedBallotLog<T: Config> = StorageValue<_, (u64, T::Hash), OptionQuery>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn candidate_vote_count)]
 	pub type CandidateVoteCount<T: Config> = StorageMap<_, Twox64Concat, u32, u32, OptionQuery>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn tally_leader)] // (commitment, votes) leading the tally, see `count_votes`
+	pub type TallyLeader<T: Config> = StorageValue<_, (u32, u32), OptionQuery>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn winner)]
 	pub type VoterWinner<T: Config> = StorageValue<_, u32, OptionQuery>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn timelocked_ballot)]
+	pub type TimelockedBallots<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, TimelockedBallot, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn rejected_timelocked_ballots)] // ballots that did not decrypt to a commitment
+	pub type RejectedTimelockedBallots<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn tally_cursor)] // voter whose ballot the tally counted last
+	pub type TallyCursor<T: Config> = StorageValue<_, T::AccountId, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn paused)] // set by `pause`, see `is_call_allowed`
+	pub type Paused<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn cancellation_reason)]
+	pub type CancellationReason<T: Config> =
+		StorageValue<_, BoundedVec<u8, T::MaxPayloadLength>, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn tally_counted_at)] // block the dispute period starts at
+	pub type TallyCountedAt<T: Config> = StorageValue<_, T::BlockNumber, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn dispute)] // filer -> dispute, every account files at most one
+	pub type Disputes<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Dispute<T::BlockNumber, BoundedVec<u8, T::MaxPayloadLength>>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn open_disputes)]
+	pub type OpenDisputes<T: Config> = StorageValue<_, u32, ValueQuery>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
 		/// Phase changed
 		PhaseChanged { when: T::BlockNumber, phase: ElectionPhase },
+		/// A batch of voters was registered. `voters` holds the ids assigned to the successful
+		/// entries, `failed` the batch position and error of every rejected entry
+		VotersAdded { voters: Vec<VoterId<T>>, failed: Vec<(u32, DispatchError)> },
+		/// A batch of blinded signatures was submitted by a candidate. `failed` holds the voter id
+		/// and error of every rejected signature
+		BatchSigned {
+			candidate: T::AccountId,
+			signed: u32,
+			failed: Vec<(VoterId<T>, DispatchError)>,
+		},
+		/// The messages the candidates blind sign for a voter were registered
+		BlindedMessagesRegistered { voter: VoterId<T> },
+		/// The CA dropped a voter whose blinded messages were not registered in time
+		VoterDropped { voter: VoterId<T> },
+		/// An account asked to become a candidate
+		CandidateNominated { candidate: T::AccountId },
+		/// The CA approved a nomination, the account is now a candidate
+		CandidateApproved { candidate: T::AccountId },
+		/// A candidate or nominee withdrew from the election
+		CandidateWithdrawn { candidate: T::AccountId },
+		/// The CA excluded a candidate that signed only `signed` of the `voters` voters before the
+		/// biased signing deadline, ballots no longer carry their signature
+		CandidateExcluded { candidate: T::AccountId, signed: u64, voters: u64 },
+		/// The RSA public key of a candidate was locked in for the rest of the election
+		CandidateKeyCommitted { candidate: T::AccountId, fingerprint: T::Hash },
+		/// The CA changed the revote policy of the election
+		RevotePolicySet { policy: RevotePolicy },
+		/// A voter cast their first ballot. `receipt` identifies the ballot, see `ballot_receipt`
+		BallotCast { voter: T::AccountId, receipt: T::Hash },
+		/// A voter replaced their ballot, only the ballot with the highest `nonce` is counted
+		BallotReplaced { voter: T::AccountId, receipt: T::Hash, nonce: u64 },
+		/// The CA switched the coercion resistant mode on or off
+		CredentialKeySet { enabled: bool },
+		/// The CA locked counting to a beacon round, or switched the timelock mode off
+		TimelockRoundSet { round: Option<u64> },
+		/// The beacon signature of the timelock round was submitted, the ballots can be counted
+		BeaconSignatureSubmitted { round: u64 },
+		/// The encrypted credential of a voter was registered
+		CredentialRegistered { voter: VoterId<T> },
+		/// A ballot with an encrypted credential was cast. `index` is its position in
+		/// `CredentialBallots`
+		CredentialBallotCast { index: u32, receipt: T::Hash },
+		/// Voting ended, `root` is the final root of the ballot log holding `length` ballots
+		BallotLogPublished { length: u64, root: T::Hash },
+		/// The filtered tally of the credential ballots was submitted, `discarded` counts the
+		/// replaced ballots and the ballots cast with a fake credential
+		CredentialTallySubmitted { counted: u32, discarded: u32 },
+		/// The tally counted `counted` ballots, `done` once every ballot is counted
+		TallyStepped { counted: u32, done: bool },
+		/// The time-locked ballot of `voter` did not decrypt to a commitment and is not counted
+		TimelockedBallotRejected { voter: T::AccountId },
+		/// The election was paused, no voting system call but `unpause` is accepted
+		ElectionPaused,
+		/// The election was unpaused
+		ElectionUnpaused,
+		/// The election was cancelled
+		ElectionCancelled { reason: BoundedVec<u8, T::MaxPayloadLength> },
+		/// A candidate or voter disputed the result
+		DisputeFiled { filer: T::AccountId },
+		/// The CA resolved the dispute of `filer`
+		DisputeResolved { filer: T::AccountId, resolution: DisputeResolution },
 	}
 
 	#[pallet::error]
@@ -213,46 +633,219 @@ pub mod pallet {
 		RSAInvalidSignature,
 		/// Invalid public key
 		InvalidPublicKey,
+		/// Candidate info can only be changed before the biased signer phase
+		CandidateInfoLocked,
+		/// Candidate public key does not match the key committed at the start of biased signing
+		CandidateKeyMismatch,
+		/// Account is not a candidate
+		CandidateDoesNotExist,
+		/// Account is already a candidate
+		CandidateAlreadyExists,
+		/// Account already has a pending nomination
+		NominationAlreadyExists,
+		/// Account has no pending nomination
+		NominationNotFound,
+		/// At least 2 candidates are required to close registration
+		NotEnoughCandidates,
+		/// RSA public key modulus is smaller than the configured minimum
+		PublicKeyTooShort,
+		/// RSA public key is already used by another candidate
+		DuplicatePublicKey,
+		/// The revote policy does not allow replacing a ballot
+		AlreadyVoted,
+		/// The ballot was already replaced as often as the revote policy allows
+		RevoteLimitReached,
+		/// Credential, credential ciphertext or tally key is not a valid group element
+		InvalidCredential,
+		/// The election is not in coercion resistant mode
+		CredentialsDisabled,
+		/// The election is in coercion resistant mode, ballots must carry a credential
+		CredentialRequired,
+		/// The tally was already counted
+		TallyAlreadyCounted,
+		/// The tally counts more ballots than were cast or credentials registered
+		InvalidTally,
+		/// The election is already paused
+		AlreadyPaused,
+		/// The election is not paused
+		NotPaused,
+		/// The election was cancelled
+		ElectionCancelled,
+		/// The result can only be disputed during the dispute period after the tally
+		DisputePeriodClosed,
+		/// The election can not complete before the dispute period is over
+		DisputePeriodOpen,
+		/// Only candidates and accounts that cast a ballot can dispute the result
+		NotEligibleToDispute,
+		/// The account already filed a dispute
+		DisputeAlreadyFiled,
+		/// The account did not file a dispute
+		DisputeNotFound,
+		/// The dispute was already resolved
+		DisputeAlreadyResolved,
+		/// The election can not complete while disputes are unresolved
+		UnresolvedDisputes,
+		/// The election can not complete before the ballots are counted
+		TallyNotCounted,
+		/// The revealed ballot key is not a valid DER encoded RSA private key
+		InvalidPrivateKey,
+		/// The revealed ballot key does not belong to the ballot public key
+		BallotKeyMismatch,
+		/// The ballot private key was already revealed
+		BallotKeyAlreadyRevealed,
+		/// Candidates can only be excluded and voters dropped once the biased signing deadline
+		/// passed
+		SigningDeadlineNotReached,
+		/// The candidate has no blinded message of the voter to sign
+		BlindedMessageNotFound,
+		/// The blinded messages of the voter were already registered
+		BlindedMessagesAlreadyRegistered,
+		/// There must be exactly one blinded message for every candidate
+		InvalidBlindedMessages,
+		/// Candidates can only be excluded once the blinded messages of every voter are registered
+		BlindedMessagesMissing,
+		/// The candidate was excluded from signing
+		CandidateExcluded,
+		/// The candidate signed every voter and can not be excluded
+		CandidateSignedAllVoters,
+		/// At least one candidate must keep signing the voters
+		LastSigningCandidate,
+		/// The ballots can only be counted after the ballot key or the beacon signature of the
+		/// timelock round is revealed, credential ballots are counted with
+		/// `submit_credential_tally`
+		TallyNotStarted,
+		/// The runtime has no beacon, ballots can not be time-locked
+		TimelockUnavailable,
+		/// The ballots are not time-locked to a beacon round
+		TimelockDisabled,
+		/// Counting is time-locked to a beacon round, there is no ballot key to reveal
+		BallotKeyTimeLocked,
+		/// The signature is not for the timelock round of the election
+		WrongBeaconRound,
+		/// The signature is not the beacon signature of the round
+		InvalidBeaconSignature,
+		/// The beacon signature of the timelock round was already submitted
+		BeaconSignatureAlreadySubmitted,
+		/// The ballots are encrypted to the timelock round, they are cast with `vote_timelocked`
+		/// and counted with `tally_timelocked_step`
+		TimelockRequired,
+		/// The time-locked ballot is not a ciphertext of the timelock scheme
+		InvalidTimelockedBallot,
+		/// Coercion resistant elections can not be time-locked
+		CredentialsTimeLocked,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn offchain_worker(block_number: T::BlockNumber) {
+			Self::offchain_biased_signing(block_number);
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn try_state(_block_number: T::BlockNumber) -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
 	}
 
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
 		pub central_authority: Option<T::AccountId>,
-		pub candidates: Vec<(T::AccountId, Vec<u8>)>,
+		// (account, name, DER encoded RSA public key)
+		pub candidates: Vec<(T::AccountId, String, Vec<u8>)>,
 		pub ballot_public_key: Vec<u8>,
+		pub revote_policy: RevotePolicy,
 	}
 
 	#[cfg(feature = "std")]
 	impl<T: Config> Default for GenesisConfig<T> {
 		fn default() -> Self {
-			Self { central_authority: None, candidates: Vec::new(), ballot_public_key: Vec::new() }
+			Self {
+				central_authority: None,
+				candidates: Vec::new(),
+				ballot_public_key: Vec::new(),
+				revote_policy: Default::default(),
+			}
+		}
+	}
+
+	/// Why `GenesisConfig::validate` rejected a genesis config, candidates are named
+	#[derive(Clone, Eq, PartialEq, RuntimeDebug)]
+	pub enum GenesisError {
+		InvalidBallotKey(&'static str),
+		TooFewCandidates,
+		DuplicateCandidate(String),
+		InvalidCandidateKey(String, &'static str),
+		DuplicateCandidateKey(String),
+	}
+
+	impl core::fmt::Display for GenesisError {
+		fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+			match self {
+				GenesisError::InvalidBallotKey(e) => write!(f, "Invalid ballot key: {}", e),
+				GenesisError::TooFewCandidates => write!(f, "At least 2 candidates are required"),
+				GenesisError::DuplicateCandidate(name) =>
+					write!(f, "Candidate {} is listed twice", name),
+				GenesisError::InvalidCandidateKey(name, e) =>
+					write!(f, "Invalid public key for candidate {}: {}", name, e),
+				GenesisError::DuplicateCandidateKey(name) =>
+					write!(f, "Candidate {} reuses the public key of another candidate", name),
+			}
+		}
+	}
+
+	impl<T: Config> GenesisConfig<T> {
+		/// Check the rules `GenesisBuild::build` enforces, so tooling can reject a genesis config
+		/// before building a chain from it
+		pub fn validate(&self) -> Result<(), GenesisError> {
+			Pallet::<T>::check_rsa_key(&self.ballot_public_key)
+				.map_err(|e| GenesisError::InvalidBallotKey(e.into()))?;
+
+			if self.candidates.len() < 2 {
+				return Err(GenesisError::TooFewCandidates)
+			}
+			for (index, (account, name, pubkey)) in self.candidates.iter().enumerate() {
+				let earlier = &self.candidates[..index];
+				if earlier.iter().any(|other| &other.0 == account) {
+					return Err(GenesisError::DuplicateCandidate(name.clone()))
+				}
+				Pallet::<T>::check_rsa_key(pubkey)
+					.map_err(|e| GenesisError::InvalidCandidateKey(name.clone(), e.into()))?;
+				if earlier.iter().any(|other| &other.2 == pubkey) {
+					return Err(GenesisError::DuplicateCandidateKey(name.clone()))
+				}
+			}
+
+			Ok(())
 		}
 	}
 
 	#[pallet::genesis_build]
 	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
 		fn build(&self) {
+			if let Err(e) = self.validate() {
+				panic!("{}", e);
+			}
+
 			Phase::<T>::put(ElectionPhase::Initialization);
+			PhaseChanges::<T>::insert(
+				ElectionPhase::Initialization,
+				frame_system::Pallet::<T>::block_number(),
+			);
 
 			if let Some(ref ca) = self.central_authority {
 				CentralAuthority::<T>::put(ca);
 			}
 
-			let pubkey = &self.ballot_public_key;
-			if pubkey.len() == 0 {
-				panic!("Ballot public key is empty");
-			}
-
-			BallotKeys::<T>::put(BallotKey { public: pubkey.clone(), private: Vec::new() });
+			BallotKeys::<T>::put(BallotKey {
+				public: self.ballot_public_key.clone(),
+				private: Vec::new(),
+			});
+			RevotingPolicy::<T>::put(self.revote_policy.clone());
 
-			if self.candidates.len() < 2 {
-				panic!("At least 2 candidates are required");
-			}
-			for candidate in &self.candidates {
-				// pubkey with place holder
+			for (account, name, pubkey) in &self.candidates {
 				Candidates::<T>::insert(
-					candidate.clone().0,
-					Candidate { name: "".to_string(), pubkey: candidate.clone().1 },
+					account,
+					Candidate { name: name.clone(), pubkey: pubkey.clone() },
 				);
 			}
 			CandidatesCount::<T>::put(self.candidates.len() as u64);
@@ -278,7 +871,7 @@ pub mod pallet {
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::change_phase(Pallet::<T>::weighed_candidates()))]
 		#[pallet::call_index(0)]
 		pub fn change_phase(origin: OriginFor<T>) -> DispatchResult {
 			// make sure that it is signed by the CA
@@ -295,49 +888,71 @@ pub mod pallet {
 			// TODO: Pull this out into it's own function for testability
 			// Additional phase-specific logic check if current phase can be ended
 
-			// TODO: Change this logic, in biased_sign function, per candidate
-			// keeps track of how many endorsement they have made. Only
-			//  proceed if for all candidates the count == voter count
 			let current_phase = Self::phase();
 			match current_phase {
+				Some(ElectionPhase::Registration) => {
+					// Candidates may have withdrawn during registration
+					ensure!(
+						Self::candidates_count().unwrap_or(0) >= 2,
+						<Error<T>>::NotEnoughCandidates
+					);
+				},
 				Some(ElectionPhase::BiasedSigner) => {
-					// Check if all the voters has received all blinded signatures from all
-					// candidates For each voter, check if blinded signature array == candidate
-					// count
-					let mut voter_index = 1;
-					while Some(voter_index) <= Self::voter_count() {
-						// Get BlindedSignature(voter_id, candidate)
-						let mut blinded_signature_count: u64 = 0;
-						BlindedSignatures::<T>::iter_prefix(voter_index).for_each(
-							|(_candidate, _)| {
-								blinded_signature_count += 1;
-							},
-						);
-						if Some(blinded_signature_count) != Self::candidates_count() {
+					// Every candidate that was not excluded must have signed every voter. A
+					// candidate signs every voter at most once, so that is the case when their
+					// count matches the voters
+					let voters = Voters::<T>::count() as u64;
+					for candidate in Candidates::<T>::iter_keys() {
+						if !ExcludedCandidates::<T>::contains_key(&candidate) &&
+							Self::signed_voter_count(candidate) != voters
+						{
 							return Err(Error::<T>::InvalidPhaseChange.into())
 						}
-						voter_index += 1;
 					}
 				},
+				Some(ElectionPhase::Counting) => {
+					// The result is final once the dispute period is over and every dispute
+					// resolved
+					let counted_at = Self::tally_counted_at().ok_or(<Error<T>>::TallyNotCounted)?;
+					ensure!(
+						frame_system::Pallet::<T>::block_number() >=
+							counted_at.saturating_add(T::DisputePeriod::get()),
+						<Error<T>>::DisputePeriodOpen
+					);
+					ensure!(Self::open_disputes() == 0, <Error<T>>::UnresolvedDisputes);
+				},
+				Some(ElectionPhase::Cancelled) => return Err(Error::<T>::ElectionCancelled.into()),
 				_ => {},
 			}
 
 			// Update the phase
 			// TODO: Refactor this section
-			let new_phase = Self::phase().expect("REASON").increment();
+			let new_phase = Self::phase().ok_or(Error::<T>::InternalError)?.increment();
+			let now = frame_system::Pallet::<T>::block_number();
 			Phase::<T>::put(new_phase.clone());
+			PhaseChanges::<T>::insert(new_phase.clone(), now);
+
+			// Candidate keys are final once biased signing starts, commit to them so any later
+			// change to a key can be detected
+			if new_phase == ElectionPhase::BiasedSigner {
+				Self::commit_candidate_keys();
+			}
+
+			// No ballot is cast after voting, publish the root the inclusion proofs lead to
+			if new_phase == ElectionPhase::Counting {
+				let length = Self::ballot_log_length();
+				let root = Self::ballot_log_root(length);
+				PublishedBallotLog::<T>::put((length, root));
+				Self::deposit_event(Event::BallotLogPublished { length, root });
+			}
 
 			// Emit event
-			Self::deposit_event(Event::PhaseChanged {
-				when: frame_system::Pallet::<T>::block_number(),
-				phase: new_phase,
-			});
+			Self::deposit_event(Event::PhaseChanged { when: now, phase: new_phase });
 
 			Ok(())
 		}
 
-		// TODO: After system is working, experiment with removing or changing value to 0
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::add_voter())]
 		#[pallet::call_index(1)]
 		pub fn add_voter(
 			origin: OriginFor<T>,
@@ -364,21 +979,17 @@ pub mod pallet {
 				<Error<T>>::InvalidPhase
 			);
 
-			// Get the voter count
-			let voter_count = Self::voter_count().unwrap_or(0);
-			let new_voter_index = voter_count + 1;
-
-			// Add the voter
-			<Voters<T>>::insert(
-				new_voter_index,
-				Voter { blinded_pubkey, is_eligible, signed_blinded_pubkey, personal_data_hash },
-			);
-			VoterCount::<T>::put(new_voter_index);
+			Self::do_add_voter(Voter {
+				blinded_pubkey,
+				is_eligible,
+				signed_blinded_pubkey,
+				personal_data_hash,
+			})?;
 
 			Ok(())
 		}
 
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::update_candidate_info(Pallet::<T>::weighed_candidates()))]
 		#[pallet::call_index(2)]
 		pub fn update_candidate_info(
 			origin: OriginFor<T>,
@@ -390,107 +1001,899 @@ pub mod pallet {
 			let sender = ensure_signed(origin)?;
 			ensure!(sender == candidate, <Error<T>>::BadSender);
 
+			// Candidate info is locked once the registration phase is over, changing the key
+			// after that would invalidate the blinded signatures already issued
+			ensure!(Self::candidates_open(), <Error<T>>::CandidateInfoLocked);
+
+			// Only existing candidates can be updated, new candidates go through nomination
+			ensure!(<Candidates<T>>::contains_key(&candidate), <Error<T>>::CandidateDoesNotExist);
+
+			Self::validate_candidate_key(&candidate, &pubkey)?;
+
 			// Update candidate info
 			<Candidates<T>>::insert(candidate, Candidate { name, pubkey });
 
 			Ok(())
 		}
 
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::biased_signing())]
 		#[pallet::call_index(3)]
 		pub fn biased_signing(
 			origin: OriginFor<T>,
 			candidate: T::AccountId,
-			voter: u64,
+			voter: VoterId<T>,
 			blinded_signature: BoundedVec<u8, T::SignatureLength>,
 		) -> DispatchResult {
 			// make sure that it is signed by the candidate
 			let sender = ensure_signed(origin)?;
 			ensure!(sender == candidate, <Error<T>>::BadSender);
+			ensure!(
+				!ExcludedCandidates::<T>::contains_key(&candidate),
+				<Error<T>>::CandidateExcluded
+			);
 
-			// Fetch the voters blinded key to verify the signature
-			let voter_data;
+			let rsa_public = Self::candidate_rsa_key(&candidate)?;
+			Self::do_biased_signing(&rsa_public, &candidate, voter, blinded_signature)
+		}
 
-			match Self::get_voter(voter) {
-				Some(data) => voter_data = data,
-				None => return Err(Error::<T>::VoterDoesNotExist.into()),
+		#[pallet::weight(T::WeightInfo::add_voters(voters.len() as u32))]
+		#[pallet::call_index(7)]
+		pub fn add_voters(
+			origin: OriginFor<T>,
+			voters: BoundedVec<Voter, T::MaxBatchSize>,
+		) -> DispatchResult {
+			// make sure that it is signed by the CA
+			let sender = ensure_signed(origin)?;
+			let ca = Self::ca();
+			if let Some(ca) = ca {
+				ensure!(sender == ca, <Error<T>>::SenderNotCA);
+			} else {
+				// if CA is not set, return error
+				return Err(Error::<T>::InternalError.into())
 			}
 
-			// Fetch the candidates public key
-			// let rsa_public: blind_rsa_signatures::reexports::rsa::RsaPublicKey;
-			let rsa_public: blind_rsa_signatures::PublicKey;
-			if let Some(candidate_struct) = Self::get_candidate(candidate.clone()) {
-				// let res = blind_rsa_signatures::reexports::rsa::RsaPublicKey::from_public_key_der(candidate_struct.pubkey.as_slice());
-				let res =
-					blind_rsa_signatures::PublicKey::from_der(candidate_struct.pubkey.as_slice());
-				if let Ok(key) = res {
-					rsa_public = key;
-				} else {
-					return Err(Error::<T>::InvalidPublicKey.into())
+			// Voters can only be added during the registration phase
+			ensure!(
+				Self::get_phase() == Some(ElectionPhase::Registration),
+				<Error<T>>::InvalidPhase
+			);
+
+			// Every entry runs in its own storage layer so a rejected entry does not revert the
+			// rest of the batch
+			let mut added = Vec::new();
+			let mut failed = Vec::new();
+			for (index, voter) in voters.into_iter().enumerate() {
+				match with_storage_layer(|| Self::do_add_voter(voter)) {
+					Ok(voter_id) => added.push(voter_id),
+					Err(e) => failed.push((index as u32, e)),
 				}
-			} else {
-				return Err(Error::<T>::RSAStorageNotFound.into())
 			}
 
-			// Format the signature correctly
-			let signature = blind_rsa_signatures::Signature::new(blinded_signature.to_vec());
+			Self::deposit_event(Event::VotersAdded { voters: added, failed });
 
-			// Set the verification options
-			let options = blind_rsa_signatures::Options::default();
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::biased_signing_batch(signatures.len() as u32))]
+		#[pallet::call_index(8)]
+		pub fn biased_signing_batch(
+			origin: OriginFor<T>,
+			candidate: T::AccountId,
+			signatures: BoundedVec<
+				(VoterId<T>, BoundedVec<u8, T::SignatureLength>),
+				T::MaxBatchSize,
+			>,
+		) -> DispatchResult {
+			// make sure that it is signed by the candidate
+			let sender = ensure_signed(origin)?;
+			ensure!(sender == candidate, <Error<T>>::BadSender);
+			ensure!(
+				!ExcludedCandidates::<T>::contains_key(&candidate),
+				<Error<T>>::CandidateExcluded
+			);
 
-			// Verify the signatures match the candidates public key
-			let verification =
-				rsa_public.verify(&signature, None, voter_data.blinded_pubkey, &options);
+			// The candidates key is shared by the whole batch, so a bad key fails the call
+			let rsa_public = Self::candidate_rsa_key(&candidate)?;
 
-			// If Verification fails we need to kill the transaction
-			if verification.is_err() {
-				return Err(Error::<T>::RSAInvalidSignature.into())
+			let mut signed: u32 = 0;
+			let mut failed = Vec::new();
+			for (voter, blinded_signature) in signatures {
+				let res = with_storage_layer(|| {
+					Self::do_biased_signing(&rsa_public, &candidate, voter, blinded_signature)
+				});
+				match res {
+					Ok(()) => signed += 1,
+					Err(e) => failed.push((voter, e)),
+				}
 			}
 
-			// Write to BlindedSignature
-			<BlindedSignatures<T>>::insert(voter, candidate, blinded_signature);
+			Self::deposit_event(Event::BatchSigned { candidate, signed, failed });
 
 			Ok(())
 		}
 
-		#[pallet::weight(0)]
-		#[pallet::call_index(4)]
-		pub fn vote(
+		#[pallet::weight(T::WeightInfo::nominate_candidate(Pallet::<T>::weighed_candidates()))]
+		#[pallet::call_index(9)]
+		pub fn nominate_candidate(
 			origin: OriginFor<T>,
-			commitment: u32,
-			mut signature_set: Vec<(T::AccountId, BlindSignature)>,
+			name: String,
+			pubkey: Vec<u8>,
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 
-			// Fetch the voters public key from their AccountID
-			let voter_public_key: Vec<u8> = sender.encode();
+			// Nominations are only accepted while the candidate list is still open
+			ensure!(Self::candidates_open(), <Error<T>>::CandidateInfoLocked);
+			ensure!(!<Candidates<T>>::contains_key(&sender), <Error<T>>::CandidateAlreadyExists);
+			ensure!(
+				!<CandidateNominations<T>>::contains_key(&sender),
+				<Error<T>>::NominationAlreadyExists
+			);
+			Self::validate_candidate_key(&sender, &pubkey)?;
 
-			// Votes can only be cast during the voting phase
-			ensure!(Self::get_phase() == Some(ElectionPhase::Voting), <Error<T>>::InvalidPhase);
+			<CandidateNominations<T>>::insert(sender.clone(), Candidate { name, pubkey });
 
-			// Get the total count of candidates
-			let candidate_count: u64;
-			if let Some(count) = CandidatesCount::<T>::get() {
-				candidate_count = count;
+			Self::deposit_event(Event::CandidateNominated { candidate: sender });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::approve_candidate(Pallet::<T>::weighed_candidates()))]
+		#[pallet::call_index(10)]
+		pub fn approve_candidate(origin: OriginFor<T>, candidate: T::AccountId) -> DispatchResult {
+			// make sure that it is signed by the CA
+			let sender = ensure_signed(origin)?;
+			let ca = Self::ca();
+			if let Some(ca) = ca {
+				ensure!(sender == ca, <Error<T>>::SenderNotCA);
 			} else {
-				return Err(Error::<T>::MissingCandidateCount.into())
+				// if CA is not set, return error
+				return Err(Error::<T>::InternalError.into())
 			}
 
-			// Check if the number of signatures does not match the number of expected candidates
-			// signatures
-			if candidate_count as usize != signature_set.len() {
-				return Err(Error::<T>::InvalidBlindSignatures.into())
-			}
+			ensure!(Self::candidates_open(), <Error<T>>::CandidateInfoLocked);
 
-			// Sort the list of signatures so we can later verify that no two signatures match to
-			// prevent a user submitting multiple of the same signature while only using O(N) time
-			signature_set.sort_by(|a, b| b.0.cmp(&a.0));
+			let nomination = <CandidateNominations<T>>::take(&candidate)
+				.ok_or(<Error<T>>::NominationNotFound)?;
+			// Another candidate may have taken the key since the nomination was made
+			Self::validate_candidate_key(&candidate, &nomination.pubkey)?;
+			<Candidates<T>>::insert(candidate.clone(), nomination);
+			CandidatesCount::<T>::mutate(|count| *count = Some(count.unwrap_or(0) + 1));
 
-			// Verify that the ballot is valid by checking for all candidates signatures
-			let mut last_id: Option<T::AccountId> = None;
-			for signature in signature_set {
-				let candidate_id = signature.0;
-				let blind_signature = signature.1;
+			Self::deposit_event(Event::CandidateApproved { candidate });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::withdraw_candidate())]
+		#[pallet::call_index(11)]
+		pub fn withdraw_candidate(origin: OriginFor<T>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// Withdrawing after biased signing started would leave voters without a full set of
+			// signatures
+			ensure!(Self::candidates_open(), <Error<T>>::CandidateInfoLocked);
+
+			if <Candidates<T>>::contains_key(&sender) {
+				<Candidates<T>>::remove(&sender);
+				CandidatesCount::<T>::mutate(|count| {
+					*count = Some(count.unwrap_or(0).saturating_sub(1))
+				});
+			} else if <CandidateNominations<T>>::contains_key(&sender) {
+				<CandidateNominations<T>>::remove(&sender);
+			} else {
+				return Err(Error::<T>::CandidateDoesNotExist.into())
+			}
+
+			Self::deposit_event(Event::CandidateWithdrawn { candidate: sender });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::set_revote_policy())]
+		#[pallet::call_index(12)]
+		pub fn set_revote_policy(origin: OriginFor<T>, policy: RevotePolicy) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(Some(sender) == Self::ca(), <Error<T>>::SenderNotCA);
+
+			// The policy is part of the election rules, it can not change once signing started
+			ensure!(Self::candidates_open(), <Error<T>>::InvalidPhase);
+
+			RevotingPolicy::<T>::put(policy.clone());
+			Self::deposit_event(Event::RevotePolicySet { policy });
+
+			Ok(())
+		}
+
+		/// Switch the coercion resistant mode on by setting the tally key the credentials are
+		/// encrypted under, or off by clearing it. See the `credentials` module
+		#[pallet::weight(T::WeightInfo::set_credential_key())]
+		#[pallet::call_index(13)]
+		pub fn set_credential_key(
+			origin: OriginFor<T>,
+			tally_key: Option<GroupElement>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(Some(sender) == Self::ca(), <Error<T>>::SenderNotCA);
+
+			// Credentials are registered with the voters, the key must be known before that
+			ensure!(
+				Self::get_phase() == Some(ElectionPhase::Initialization),
+				<Error<T>>::InvalidPhase
+			);
+			if let Some(key) = &tally_key {
+				ensure!(credentials::is_group_element(key), <Error<T>>::InvalidCredential);
+				ensure!(Self::timelock_round().is_none(), <Error<T>>::CredentialsTimeLocked);
+			}
+
+			let enabled = tally_key.is_some();
+			CredentialKey::<T>::set(tally_key);
+			Self::deposit_event(Event::CredentialKeySet { enabled });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::register_credential())]
+		#[pallet::call_index(14)]
+		pub fn register_credential(
+			origin: OriginFor<T>,
+			voter: VoterId<T>,
+			credential: Ciphertext,
+		) -> DispatchResult {
+			// make sure that it is signed by the CA
+			let sender = ensure_signed(origin)?;
+			ensure!(Some(sender) == Self::ca(), <Error<T>>::SenderNotCA);
+
+			// Credentials are registered together with the voters
+			ensure!(
+				Self::get_phase() == Some(ElectionPhase::Registration),
+				<Error<T>>::InvalidPhase
+			);
+			ensure!(Self::credential_key().is_some(), <Error<T>>::CredentialsDisabled);
+			ensure!(<Voters<T>>::contains_key(voter), <Error<T>>::VoterDoesNotExist);
+			ensure!(credentials::is_valid_ciphertext(&credential), <Error<T>>::InvalidCredential);
+
+			<VoterCredentials<T>>::insert(voter, credential);
+			Self::deposit_event(Event::CredentialRegistered { voter });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::vote(signature_set.len() as u32))]
+		#[pallet::call_index(4)]
+		pub fn vote(
+			origin: OriginFor<T>,
+			commitment: u32,
+			signature_set: Vec<(T::AccountId, BlindSignature)>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// Votes can only be cast during the voting phase
+			ensure!(Self::get_phase() == Some(ElectionPhase::Voting), <Error<T>>::InvalidPhase);
+			ensure!(Self::credential_key().is_none(), <Error<T>>::CredentialRequired);
+			ensure!(Self::timelock_round().is_none(), <Error<T>>::TimelockRequired);
+
+			// Check the revote policy before paying for the signature verification
+			let previous = <Ballots<T>>::get(sender.clone());
+			Self::check_revote_policy(previous.as_ref().map(|ballot| ballot.nonce))?;
+
+			// Keep the verified signatures with the ballot so the tally can be audited later
+			let signature_set = Self::verify_signature_set(&sender, signature_set)?.encode();
+
+			let nonce = previous.map_or(1, |ballot| ballot.nonce + 1);
+			let ballot = Ballot { commitment, signature: signature_set, nonce };
+			let receipt = Self::ballot_receipt(&sender, &ballot, Self::ballot_log_length());
+			Self::append_to_ballot_log(&sender, ballot.encode(), receipt);
+			<Ballots<T>>::insert(sender.clone(), ballot);
+
+			if nonce == 1 {
+				Self::deposit_event(Event::BallotCast { voter: sender, receipt });
+			} else {
+				Self::deposit_event(Event::BallotReplaced { voter: sender, receipt, nonce });
+			}
+
+			Ok(())
+		}
+
+		/// Cast a ballot whose commitment is encrypted to the timelock round, see the `timelock`
+		/// module. The revote policy applies as for `vote`
+		#[pallet::weight(T::WeightInfo::vote_timelocked(signature_set.len() as u32))]
+		#[pallet::call_index(28)]
+		pub fn vote_timelocked(
+			origin: OriginFor<T>,
+			ciphertext: TimelockCiphertext,
+			signature_set: Vec<(T::AccountId, BlindSignature)>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// Votes can only be cast during the voting phase
+			ensure!(Self::get_phase() == Some(ElectionPhase::Voting), <Error<T>>::InvalidPhase);
+			ensure!(Self::timelock_round().is_some(), <Error<T>>::TimelockDisabled);
+			ensure!(
+				timelock::is_valid_ciphertext(&ciphertext),
+				<Error<T>>::InvalidTimelockedBallot
+			);
+
+			let previous = <TimelockedBallots<T>>::get(sender.clone());
+			Self::check_revote_policy(previous.as_ref().map(|ballot| ballot.nonce))?;
+
+			let signature_set = Self::verify_signature_set(&sender, signature_set)?.encode();
+
+			let nonce = previous.map_or(1, |ballot| ballot.nonce + 1);
+			let ballot = TimelockedBallot { ciphertext, signature: signature_set, nonce };
+			let receipt = Self::ballot_receipt(&sender, &ballot, Self::ballot_log_length());
+			Self::append_to_ballot_log(&sender, ballot.encode(), receipt);
+			<TimelockedBallots<T>>::insert(sender.clone(), ballot);
+
+			if nonce == 1 {
+				Self::deposit_event(Event::BallotCast { voter: sender, receipt });
+			} else {
+				Self::deposit_event(Event::BallotReplaced { voter: sender, receipt, nonce });
+			}
+
+			Ok(())
+		}
+
+		/// Cast a ballot in coercion resistant mode. Ballots are never replaced, every ballot is
+		/// kept and the tally only counts the last ballot of every registered credential, so the
+		/// revote policy does not apply
+		#[pallet::weight(T::WeightInfo::vote_with_credential(signature_set.len() as u32))]
+		#[pallet::call_index(15)]
+		pub fn vote_with_credential(
+			origin: OriginFor<T>,
+			commitment: u32,
+			credential: Ciphertext,
+			signature_set: Vec<(T::AccountId, BlindSignature)>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// Votes can only be cast during the voting phase
+			ensure!(Self::get_phase() == Some(ElectionPhase::Voting), <Error<T>>::InvalidPhase);
+			ensure!(Self::credential_key().is_some(), <Error<T>>::CredentialsDisabled);
+			ensure!(credentials::is_valid_ciphertext(&credential), <Error<T>>::InvalidCredential);
+
+			// The signatures only prove the sender is an eligible voter, whether the ballot counts
+			// is decided by the credential
+			let signature_set = Self::verify_signature_set(&sender, signature_set)?.encode();
+
+			let index = Self::credential_ballot_count();
+			let ballot = CredentialBallot { commitment, credential, signature: signature_set };
+			let receipt = Self::ballot_receipt(&sender, &ballot, Self::ballot_log_length());
+			Self::append_to_ballot_log(&sender, ballot.encode(), receipt);
+			<CredentialBallots<T>>::insert(index, ballot);
+			CredentialBallotCount::<T>::put(index + 1);
+
+			Self::deposit_event(Event::CredentialBallotCast { index, receipt });
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::reveal_ballot_key())]
+		#[pallet::call_index(6)]
+		pub fn reveal_ballot_key(origin: OriginFor<T>, private_key: Vec<u8>) -> DispatchResult {
+			// make sure that it is signed by the CA
+			let sender = ensure_signed(origin)?;
+
+			let ca = Self::ca();
+			if let Some(ca) = ca {
+				ensure!(sender == ca, <Error<T>>::SenderNotCA);
+			} else {
+				// if CA is not set, return error
+				return Err(Error::<T>::InternalError.into())
+			}
+
+			// Ballot private key can only be revealed during the counting phase
+			ensure!(Self::get_phase() == Some(ElectionPhase::Counting), <Error<T>>::InvalidPhase);
+			// Time-locked counting is started by the beacon signature, not by the CA
+			ensure!(Self::timelock_round().is_none(), <Error<T>>::BallotKeyTimeLocked);
+
+			let k = BallotKeys::<T>::get();
+			if let Some(mut ballot_key) = k {
+				// The key is revealed once, revealing it again would count the ballots twice
+				ensure!(ballot_key.private.is_empty(), <Error<T>>::BallotKeyAlreadyRevealed);
+				// Anybody must be able to open the ballots with the revealed key
+				Self::check_ballot_private_key(&ballot_key.public, &private_key)?;
+
+				// Update the ballot key
+				ballot_key.private = private_key;
+				<BallotKeys<T>>::set(Some(ballot_key));
+			} else {
+				return Err(Error::<T>::InternalError.into())
+			}
+
+			// The ballots are counted with `tally_step`, credential ballots by the holder of the
+			// tally key with `submit_credential_tally`
+			Ok(())
+		}
+
+		/// Count up to `max_ballots` ballots, continuing after the ballot counted last. The winner
+		/// is selected once every ballot is counted. Anybody can advance the tally once the ballot
+		/// key or the beacon signature of the timelock round is revealed
+		#[pallet::weight(T::WeightInfo::tally_step(*max_ballots))]
+		#[pallet::call_index(22)]
+		pub fn tally_step(origin: OriginFor<T>, max_ballots: u32) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			ensure!(Self::get_phase() == Some(ElectionPhase::Counting), <Error<T>>::InvalidPhase);
+			ensure!(Self::timelock_round().is_none(), <Error<T>>::TimelockRequired);
+			ensure!(Self::tally_open(), <Error<T>>::TallyNotStarted);
+			ensure!(Self::winner().is_none(), <Error<T>>::TallyAlreadyCounted);
+
+			// No ballot is cast during counting, so the iteration order is stable between steps
+			let mut ballots = match Self::tally_cursor() {
+				Some(last) => Ballots::<T>::iter_from(Ballots::<T>::hashed_key_for(last)),
+				None => Ballots::<T>::iter(),
+			};
+			let mut counted: u32 = 0;
+			let mut last = None;
+			while counted < max_ballots {
+				match ballots.next() {
+					Some((voter, ballot)) => {
+						Self::count_votes(ballot.commitment, 1);
+						last = Some(voter);
+						counted += 1;
+					},
+					None => break,
+				}
+			}
+			let done = ballots.next().is_none();
+
+			if let Some(last) = last {
+				TallyCursor::<T>::put(last);
+			}
+			if done {
+				TallyCursor::<T>::kill();
+				Self::select_winner();
+			}
+			Self::deposit_event(Event::TallyStepped { counted, done });
+
+			Ok(())
+		}
+
+		/// Decrypt and count up to `max_ballots` time-locked ballots with the beacon signature of
+		/// the timelock round, continuing after the ballot counted last. Ballots that do not
+		/// decrypt to a commitment are rejected and not counted. The winner is selected once
+		/// every ballot is counted. Anybody can advance the tally once the beacon signature is
+		/// submitted
+		#[pallet::weight(T::WeightInfo::tally_timelocked_step(*max_ballots))]
+		#[pallet::call_index(29)]
+		pub fn tally_timelocked_step(origin: OriginFor<T>, max_ballots: u32) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			ensure!(Self::get_phase() == Some(ElectionPhase::Counting), <Error<T>>::InvalidPhase);
+			ensure!(Self::timelock_round().is_some(), <Error<T>>::TimelockDisabled);
+			let signature = Self::beacon_signature().ok_or(<Error<T>>::TallyNotStarted)?;
+			ensure!(Self::winner().is_none(), <Error<T>>::TallyAlreadyCounted);
+
+			// No ballot is cast during counting, so the iteration order is stable between steps
+			let mut ballots = match Self::tally_cursor() {
+				Some(last) =>
+					TimelockedBallots::<T>::iter_from(TimelockedBallots::<T>::hashed_key_for(last)),
+				None => TimelockedBallots::<T>::iter(),
+			};
+			let mut counted: u32 = 0;
+			let mut last = None;
+			while counted < max_ballots {
+				match ballots.next() {
+					Some((voter, ballot)) => {
+						match timelock::decrypt(&signature, &ballot.ciphertext)
+							.and_then(|message| timelock::message_commitment(&message))
+						{
+							Some(commitment) => Self::count_votes(commitment, 1),
+							None => {
+								RejectedTimelockedBallots::<T>::mutate(|rejected| *rejected += 1);
+								Self::deposit_event(Event::TimelockedBallotRejected {
+									voter: voter.clone(),
+								});
+							},
+						}
+						last = Some(voter);
+						counted += 1;
+					},
+					None => break,
+				}
+			}
+			let done = ballots.next().is_none();
+
+			if let Some(last) = last {
+				TallyCursor::<T>::put(last);
+			}
+			if done {
+				TallyCursor::<T>::kill();
+				Self::select_winner();
+			}
+			Self::deposit_event(Event::TallyStepped { counted, done });
+
+			Ok(())
+		}
+
+		/// Encrypt the ballots to a round of the beacon instead of the ballot key, or switch the
+		/// timelock mode off. See the `timelock` module
+		#[pallet::weight(T::WeightInfo::set_timelock_round())]
+		#[pallet::call_index(23)]
+		pub fn set_timelock_round(origin: OriginFor<T>, round: Option<u64>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(Some(sender) == Self::ca(), <Error<T>>::SenderNotCA);
+
+			// The round is fixed before registration, so the CA can not pick it once votes come in
+			ensure!(
+				Self::get_phase() == Some(ElectionPhase::Initialization),
+				<Error<T>>::InvalidPhase
+			);
+			if round.is_some() {
+				ensure!(T::Beacon::is_available(), <Error<T>>::TimelockUnavailable);
+				ensure!(Self::credential_key().is_none(), <Error<T>>::CredentialsTimeLocked);
+			}
+
+			TimelockRound::<T>::set(round);
+			Self::deposit_event(Event::TimelockRoundSet { round });
+
+			Ok(())
+		}
+
+		/// Submit the beacon signature of the timelock round, the key `tally_timelocked_step`
+		/// decrypts the ballots with. Anybody can submit it once the beacon published it
+		#[pallet::weight(T::WeightInfo::submit_beacon_signature())]
+		#[pallet::call_index(24)]
+		pub fn submit_beacon_signature(
+			origin: OriginFor<T>,
+			round: u64,
+			signature: Vec<u8>,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			ensure!(Self::get_phase() == Some(ElectionPhase::Counting), <Error<T>>::InvalidPhase);
+			let timelock_round = Self::timelock_round().ok_or(<Error<T>>::TimelockDisabled)?;
+			ensure!(round == timelock_round, <Error<T>>::WrongBeaconRound);
+			ensure!(
+				Self::beacon_signature().is_none(),
+				<Error<T>>::BeaconSignatureAlreadySubmitted
+			);
+			ensure!(T::Beacon::verify(round, &signature), <Error<T>>::InvalidBeaconSignature);
+			let signature: BoundedVec<u8, T::MaxPayloadLength> =
+				signature.try_into().map_err(|_| <Error<T>>::InvalidBeaconSignature)?;
+
+			BeaconSignature::<T>::put(signature);
+			Self::deposit_event(Event::BeaconSignatureSubmitted { round });
+
+			Ok(())
+		}
+
+		/// Submit the votes per commitment of the credential ballots left after filtering out the
+		/// replaced ballots and the ballots with a fake credential. `transcript` is the hash of
+		/// the `credentials::TallyTranscript` proving the tally, which is published for anybody to
+		/// check with `verify-tally`
+		#[pallet::weight(T::WeightInfo::submit_credential_tally(tally.len() as u32))]
+		#[pallet::call_index(16)]
+		pub fn submit_credential_tally(
+			origin: OriginFor<T>,
+			tally: BoundedVec<(u32, u32), T::MaxBatchSize>,
+			transcript: T::Hash,
+		) -> DispatchResult {
+			// make sure that it is signed by the CA
+			let sender = ensure_signed(origin)?;
+			ensure!(Some(sender) == Self::ca(), <Error<T>>::SenderNotCA);
+
+			ensure!(Self::get_phase() == Some(ElectionPhase::Counting), <Error<T>>::InvalidPhase);
+			ensure!(Self::credential_key().is_some(), <Error<T>>::CredentialsDisabled);
+			ensure!(Self::winner().is_none(), <Error<T>>::TallyAlreadyCounted);
+
+			// Every registered credential counts at most once and only with a ballot cast
+			let counted = tally
+				.iter()
+				.try_fold(0u32, |total, (_, votes)| total.checked_add(*votes))
+				.ok_or(<Error<T>>::InvalidTally)?;
+			let ballots = Self::credential_ballot_count();
+			ensure!(counted <= ballots, <Error<T>>::InvalidTally);
+			ensure!(counted <= VoterCredentials::<T>::count(), <Error<T>>::InvalidTally);
+
+			CredentialTallyTranscript::<T>::put(transcript);
+			for (commitment, votes) in tally {
+				Self::count_votes(commitment, votes);
+			}
+			Self::select_winner();
+
+			Self::deposit_event(Event::CredentialTallySubmitted {
+				counted,
+				discarded: ballots - counted,
+			});
+
+			Ok(())
+		}
+
+		/// Freeze every voting system call but `unpause` in an emergency, the election resumes in
+		/// the same phase once unpaused. Can be called by the CA or root
+		#[pallet::weight(T::WeightInfo::pause())]
+		#[pallet::call_index(17)]
+		pub fn pause(origin: OriginFor<T>) -> DispatchResult {
+			Self::ensure_ca_or_root(origin)?;
+			ensure!(!Self::paused(), <Error<T>>::AlreadyPaused);
+
+			Paused::<T>::put(true);
+			Self::deposit_event(Event::ElectionPaused);
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::unpause())]
+		#[pallet::call_index(18)]
+		pub fn unpause(origin: OriginFor<T>) -> DispatchResult {
+			Self::ensure_ca_or_root(origin)?;
+			ensure!(Self::paused(), <Error<T>>::NotPaused);
+
+			Paused::<T>::kill();
+			Self::deposit_event(Event::ElectionUnpaused);
+
+			Ok(())
+		}
+
+		/// Cancel the election in any phase, for example when fraud is detected. A cancelled
+		/// election accepts no further calls. Can be called by the CA or root
+		#[pallet::weight(T::WeightInfo::cancel_election())]
+		#[pallet::call_index(19)]
+		pub fn cancel_election(
+			origin: OriginFor<T>,
+			reason: BoundedVec<u8, T::MaxPayloadLength>,
+		) -> DispatchResult {
+			Self::ensure_ca_or_root(origin)?;
+			ensure!(
+				Self::get_phase() != Some(ElectionPhase::Cancelled),
+				<Error<T>>::ElectionCancelled
+			);
+
+			Self::do_cancel_election(reason);
+
+			Ok(())
+		}
+
+		/// Dispute the result during the dispute period, completing the election waits for the CA
+		/// to resolve the dispute. Open to candidates and to accounts that cast a ballot
+		#[pallet::weight(T::WeightInfo::file_dispute())]
+		#[pallet::call_index(20)]
+		pub fn file_dispute(
+			origin: OriginFor<T>,
+			evidence: BoundedVec<u8, T::MaxPayloadLength>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(Self::get_phase() == Some(ElectionPhase::Counting), <Error<T>>::InvalidPhase);
+			let now = frame_system::Pallet::<T>::block_number();
+			let counted_at = Self::tally_counted_at().ok_or(<Error<T>>::DisputePeriodClosed)?;
+			ensure!(
+				now < counted_at.saturating_add(T::DisputePeriod::get()),
+				<Error<T>>::DisputePeriodClosed
+			);
+			ensure!(Self::may_dispute(&sender), <Error<T>>::NotEligibleToDispute);
+			ensure!(!<Disputes<T>>::contains_key(&sender), <Error<T>>::DisputeAlreadyFiled);
+
+			<Disputes<T>>::insert(&sender, Dispute { evidence, filed: now, resolution: None });
+			OpenDisputes::<T>::mutate(|open| *open += 1);
+			Self::deposit_event(Event::DisputeFiled { filer: sender });
+
+			Ok(())
+		}
+
+		/// Resolve the dispute filed by `filer`. Upholding a dispute cancels the election with
+		/// the evidence of the dispute as reason
+		#[pallet::weight(T::WeightInfo::resolve_dispute())]
+		#[pallet::call_index(21)]
+		pub fn resolve_dispute(
+			origin: OriginFor<T>,
+			filer: T::AccountId,
+			resolution: DisputeResolution,
+		) -> DispatchResult {
+			// make sure that it is signed by the CA
+			let sender = ensure_signed(origin)?;
+			ensure!(Some(sender) == Self::ca(), <Error<T>>::SenderNotCA);
+
+			ensure!(Self::get_phase() == Some(ElectionPhase::Counting), <Error<T>>::InvalidPhase);
+			let mut dispute = Self::dispute(&filer).ok_or(<Error<T>>::DisputeNotFound)?;
+			ensure!(dispute.resolution.is_none(), <Error<T>>::DisputeAlreadyResolved);
+
+			dispute.resolution = Some(resolution.clone());
+			<Disputes<T>>::insert(&filer, dispute.clone());
+			OpenDisputes::<T>::mutate(|open| *open -= 1);
+			Self::deposit_event(Event::DisputeResolved { filer, resolution: resolution.clone() });
+
+			if resolution == DisputeResolution::Upheld {
+				Self::do_cancel_election(dispute.evidence);
+			}
+
+			Ok(())
+		}
+
+		/// Exclude a candidate that did not sign every voter within `BiasedSignerPeriod` blocks
+		/// of the start of biased signing. Ballots no longer need their signature, so the
+		/// election can move on to voting without them
+		#[pallet::weight(T::WeightInfo::exclude_candidate())]
+		#[pallet::call_index(25)]
+		pub fn exclude_candidate(origin: OriginFor<T>, candidate: T::AccountId) -> DispatchResult {
+			// make sure that it is signed by the CA
+			let sender = ensure_signed(origin)?;
+			ensure!(Some(sender) == Self::ca(), <Error<T>>::SenderNotCA);
+
+			ensure!(
+				Self::get_phase() == Some(ElectionPhase::BiasedSigner),
+				<Error<T>>::InvalidPhase
+			);
+			let started =
+				Self::phase_change(ElectionPhase::BiasedSigner).ok_or(<Error<T>>::InternalError)?;
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(
+				now >= started.saturating_add(T::BiasedSignerPeriod::get()),
+				<Error<T>>::SigningDeadlineNotReached
+			);
+			// A candidate can not be blamed for the voters they had nothing to sign for
+			ensure!(
+				Self::blinded_message_count() == Voters::<T>::count() as u64,
+				<Error<T>>::BlindedMessagesMissing
+			);
+
+			ensure!(<Candidates<T>>::contains_key(&candidate), <Error<T>>::CandidateDoesNotExist);
+			ensure!(
+				!ExcludedCandidates::<T>::contains_key(&candidate),
+				<Error<T>>::CandidateExcluded
+			);
+			let signed = Self::signed_voter_count(&candidate);
+			let voters = Voters::<T>::count() as u64;
+			ensure!(signed < voters, <Error<T>>::CandidateSignedAllVoters);
+			// Ballots without any candidate signature would not prove anything
+			ensure!(Self::signing_candidates_count() > 1, <Error<T>>::LastSigningCandidate);
+
+			ExcludedCandidates::<T>::insert(
+				&candidate,
+				Exclusion { excluded: now, signed, voters },
+			);
+			Self::deposit_event(Event::CandidateExcluded { candidate, signed, voters });
+
+			Ok(())
+		}
+
+		/// Register the messages the candidates blind sign for a voter, one per candidate. A
+		/// message blinded for one candidate key can only be unblinded from a signature with that
+		/// key, so the voter blinds their voting account for every candidate key once the keys
+		/// are committed at the start of biased signing
+		#[pallet::weight(T::WeightInfo::register_blinded_messages(messages.len() as u32))]
+		#[pallet::call_index(26)]
+		pub fn register_blinded_messages(
+			origin: OriginFor<T>,
+			voter: VoterId<T>,
+			messages: BoundedVec<
+				(T::AccountId, BoundedVec<u8, T::SignatureLength>),
+				T::MaxBatchSize,
+			>,
+		) -> DispatchResult {
+			// make sure that it is signed by the CA
+			let sender = ensure_signed(origin)?;
+			ensure!(Some(sender) == Self::ca(), <Error<T>>::SenderNotCA);
+
+			ensure!(
+				Self::get_phase() == Some(ElectionPhase::BiasedSigner),
+				<Error<T>>::InvalidPhase
+			);
+			ensure!(<Voters<T>>::contains_key(voter), <Error<T>>::VoterDoesNotExist);
+			// Replacing a message would invalidate the signatures already issued for it
+			ensure!(
+				<BlindedMessages<T>>::iter_prefix(voter).next().is_none(),
+				<Error<T>>::BlindedMessagesAlreadyRegistered
+			);
+
+			let mut candidates: Vec<_> = messages.iter().map(|(candidate, _)| candidate).collect();
+			candidates.sort();
+			candidates.dedup();
+			ensure!(
+				candidates.len() == messages.len() &&
+					candidates.len() as u64 == Self::candidates_count().unwrap_or(0) &&
+					candidates
+						.into_iter()
+						.all(|candidate| Candidates::<T>::contains_key(candidate)),
+				<Error<T>>::InvalidBlindedMessages
+			);
+
+			for (candidate, message) in messages {
+				<BlindedMessages<T>>::insert(voter, candidate, message);
+			}
+			BlindedMessageCount::<T>::mutate(|count| *count += 1);
+			Self::deposit_event(Event::BlindedMessagesRegistered { voter });
+
+			Ok(())
+		}
+
+		/// Drop a voter whose blinded messages were not registered within `BiasedSignerPeriod`
+		/// blocks of the start of biased signing. Nobody can sign for such a voter, keeping them
+		/// would hold the election in biased signing forever
+		#[pallet::weight(T::WeightInfo::drop_voter())]
+		#[pallet::call_index(27)]
+		pub fn drop_voter(origin: OriginFor<T>, voter: VoterId<T>) -> DispatchResult {
+			// make sure that it is signed by the CA
+			let sender = ensure_signed(origin)?;
+			ensure!(Some(sender) == Self::ca(), <Error<T>>::SenderNotCA);
+
+			ensure!(
+				Self::get_phase() == Some(ElectionPhase::BiasedSigner),
+				<Error<T>>::InvalidPhase
+			);
+			let started =
+				Self::phase_change(ElectionPhase::BiasedSigner).ok_or(<Error<T>>::InternalError)?;
+			ensure!(
+				frame_system::Pallet::<T>::block_number() >=
+					started.saturating_add(T::BiasedSignerPeriod::get()),
+				<Error<T>>::SigningDeadlineNotReached
+			);
+			ensure!(<Voters<T>>::contains_key(voter), <Error<T>>::VoterDoesNotExist);
+			// A voter with messages can be signed, a candidate not doing so is excluded instead.
+			// Without messages no candidate signed the voter, there are no signatures to remove
+			ensure!(
+				<BlindedMessages<T>>::iter_prefix(voter).next().is_none(),
+				<Error<T>>::BlindedMessagesAlreadyRegistered
+			);
+
+			<Voters<T>>::remove(voter);
+			<VoterCredentials<T>>::remove(voter);
+			Self::deposit_event(Event::VoterDropped { voter });
+
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Store a new voter under the id derived from their blinded key and return that id
+		fn do_add_voter(voter: Voter) -> Result<VoterId<T>, DispatchError> {
+			// The same blinded key can not be registered twice
+			let voter_id = Self::voter_id(&voter.blinded_pubkey);
+			ensure!(!<Voters<T>>::contains_key(voter_id), <Error<T>>::VoterAlreadyExists);
+
+			<Voters<T>>::insert(voter_id, voter);
+
+			Ok(voter_id)
+		}
+
+		/// Number of candidates whose signature a ballot carries, every candidate that was not
+		/// excluded
+		pub fn signing_candidates_count() -> u64 {
+			Self::candidates_count()
+				.unwrap_or(0)
+				.saturating_sub(ExcludedCandidates::<T>::count() as u64)
+		}
+
+		/// Number of candidates the calls walking every candidate are weighed with. Candidates are
+		/// only bounded by the approval of the CA, so the weight follows the stored count
+		pub(crate) fn weighed_candidates() -> u32 {
+			Self::candidates_count().unwrap_or(0).saturated_into()
+		}
+
+		/// Id of the voter registered with `blinded_pubkey`
+		pub fn voter_id(blinded_pubkey: &[u8]) -> VoterId<T> {
+			T::Hashing::hash(blinded_pubkey)
+		}
+
+		/// Verify that `signature_set` holds exactly one valid signature of every candidate over
+		/// the account id of `voter`, the set is returned sorted by candidate
+		pub(crate) fn verify_signature_set(
+			voter: &T::AccountId,
+			mut signature_set: Vec<(T::AccountId, BlindSignature)>,
+		) -> Result<Vec<(T::AccountId, BlindSignature)>, DispatchError> {
+			// Fetch the voters public key from their AccountID
+			let voter_public_key: Vec<u8> = voter.encode();
+
+			// Get the total count of candidates
+			ensure!(CandidatesCount::<T>::get().is_some(), <Error<T>>::MissingCandidateCount);
+			let candidate_count = Self::signing_candidates_count();
+
+			// Check if the number of signatures does not match the number of expected candidates
+			// signatures
+			if candidate_count as usize != signature_set.len() {
+				return Err(Error::<T>::InvalidBlindSignatures.into())
+			}
+
+			// Sort the list of signatures so we can later verify that no two signatures match to
+			// prevent a user submitting multiple of the same signature while only using O(N) time
+			signature_set.sort_by(|a, b| a.0.cmp(&b.0));
+
+			// Verify that the ballot is valid by checking for all candidates signatures
+			let mut last_id: Option<T::AccountId> = None;
+			for signature in signature_set.iter().cloned() {
+				let candidate_id = signature.0;
+				let blind_signature = signature.1;
 				// If the last candidate id is equal to or greater then the last there are duplicate
 				// entries
 				if let Some(id) = last_id {
@@ -501,21 +1904,16 @@ pub mod pallet {
 				// Update the last id for the next loops check
 				last_id = Some(candidate_id.clone());
 
+				// Excluded candidates stand in for none of the candidates that still sign
+				ensure!(
+					!ExcludedCandidates::<T>::contains_key(&candidate_id),
+					<Error<T>>::InvalidBlindSignatures
+				);
+
 				// Verify the actual signatures to make sure they came from a candidate
 				// Start by trying to fetch the candidates public key
-				let rsa_public: blind_rsa_signatures::PublicKey;
-				if let Some(candidate_struct) = Self::get_candidate(candidate_id.clone()) {
-					let res = blind_rsa_signatures::PublicKey::from_der(
-						candidate_struct.pubkey.as_slice(),
-					);
-					if let Ok(key) = res {
-						rsa_public = key;
-					} else {
-						return Err(Error::<T>::InvalidBlindSignatures.into())
-					}
-				} else {
-					return Err(Error::<T>::InvalidBlindSignatures.into())
-				}
+				let rsa_public = Self::candidate_rsa_key(&candidate_id)
+					.map_err(|_| <Error<T>>::InvalidBlindSignatures)?;
 
 				// Format the signature correctly
 				let signature =
@@ -543,74 +1941,744 @@ pub mod pallet {
 				}
 			}
 
-			// If the ballot already exists, update the vote
-			if let Some(ballot) = <Ballots<T>>::get(sender.clone()) {
-				// Update the ballot
-				<Ballots<T>>::insert(
-					sender,
-					Ballot { commitment, signature: Vec::new(), nonce: ballot.nonce + 1 },
-				);
-			} else {
-				// Add the ballot
-				<Ballots<T>>::insert(
-					sender,
-					Ballot { commitment, signature: Vec::new(), nonce: 1 },
-				);
-			}
+			Ok(signature_set)
+		}
 
+		/// Check the revote policy allows replacing the previous ballot of a voter, which has
+		/// `previous_nonce`
+		pub(crate) fn check_revote_policy(previous_nonce: Option<u64>) -> DispatchResult {
+			if let Some(nonce) = previous_nonce {
+				match Self::revote_policy() {
+					RevotePolicy::SingleVote => return Err(Error::<T>::AlreadyVoted.into()),
+					RevotePolicy::LastVoteCounts => {},
+					// The first ballot has nonce 1, every replacement bumps it
+					RevotePolicy::MaxRevotes(max) =>
+						ensure!(nonce <= max as u64, <Error<T>>::RevoteLimitReached),
+				}
+			}
 			Ok(())
 		}
 
-		#[pallet::weight(0)]
-		#[pallet::call_index(6)]
-		pub fn reveal_ballot_key(origin: OriginFor<T>, private_key: Vec<u8>) -> DispatchResult {
-			// make sure that it is signed by the CA
+		/// Whether `who` dispatches `call` free of fees, see the `fees` module. The call must be
+		/// one of the election duties of `who` and pass the cheap checks of its dispatch. The
+		/// signatures of a ballot are verified by `CheckVotingCall` before the fee is decided on
+		pub fn is_fee_exempt(who: &T::AccountId, call: &Call<T>) -> bool {
+			let phase = Self::get_phase();
+			match call {
+				Call::vote { signature_set, .. } =>
+					phase == Some(ElectionPhase::Voting) &&
+						Self::credential_key().is_none() &&
+						Self::timelock_round().is_none() &&
+						Self::free_ballots(who) < FREE_BALLOTS &&
+						Self::check_revote_policy(
+							Self::get_ballot(who.clone()).map(|ballot| ballot.nonce),
+						)
+						.is_ok() && validation::check_signature_set::<T>(signature_set).is_ok(),
+				Call::vote_timelocked { ciphertext, signature_set } =>
+					phase == Some(ElectionPhase::Voting) &&
+						Self::timelock_round().is_some() &&
+						Self::free_ballots(who) < FREE_BALLOTS &&
+						timelock::is_valid_ciphertext(ciphertext) &&
+						Self::check_revote_policy(
+							Self::timelocked_ballot(who.clone()).map(|ballot| ballot.nonce),
+						)
+						.is_ok() && validation::check_signature_set::<T>(signature_set).is_ok(),
+				Call::vote_with_credential { credential, signature_set, .. } =>
+					phase == Some(ElectionPhase::Voting) &&
+						Self::credential_key().is_some() &&
+						Self::free_ballots(who) < FREE_BALLOTS &&
+						credentials::is_valid_ciphertext(credential) &&
+						validation::check_signature_set::<T>(signature_set).is_ok(),
+				Call::biased_signing { candidate, .. } |
+				Call::biased_signing_batch { candidate, .. } =>
+					phase == Some(ElectionPhase::BiasedSigner) &&
+						who == candidate && Candidates::<T>::contains_key(candidate) &&
+						!ExcludedCandidates::<T>::contains_key(candidate),
+				Call::add_voter { .. } | Call::add_voters { .. } =>
+					phase == Some(ElectionPhase::Registration) && Some(who.clone()) == Self::ca(),
+				Call::register_blinded_messages { .. } =>
+					phase == Some(ElectionPhase::BiasedSigner) && Some(who.clone()) == Self::ca(),
+				_ => false,
+			}
+		}
+
+		/// Count a ballot of `who` submitted without a fee. Counted when the fee is waived, before
+		/// the dispatch, so a ballot failing on dispatch still uses up one of the free ballots
+		pub fn note_free_ballot(who: &T::AccountId, call: &Call<T>) {
+			if matches!(
+				call,
+				Call::vote { .. } |
+					Call::vote_timelocked { .. } |
+					Call::vote_with_credential { .. }
+			) {
+				FreeBallots::<T>::mutate(who, |free| *free = free.saturating_add(1));
+			}
+		}
+
+		/// Whether the runtime call filter lets `call` through in the current phase. While the
+		/// election is paused only `pause`, `unpause` and `cancel_election` are. During voting the
+		/// only other administrative call is the phase change, once voting ended no ballot is
+		/// accepted and a cancelled election accepts nothing
+		pub fn is_call_allowed(call: &Call<T>) -> bool {
+			if matches!(
+				call,
+				Call::pause { .. } | Call::unpause { .. } | Call::cancel_election { .. }
+			) {
+				return true
+			}
+			if Self::paused() {
+				return false
+			}
+			match Self::get_phase() {
+				Some(ElectionPhase::Voting) => matches!(
+					call,
+					Call::vote { .. } |
+						Call::vote_timelocked { .. } |
+						Call::vote_with_credential { .. } |
+						Call::change_phase { .. }
+				),
+				Some(ElectionPhase::Counting) | Some(ElectionPhase::Completed) => !matches!(
+					call,
+					Call::vote { .. } |
+						Call::vote_timelocked { .. } |
+						Call::vote_with_credential { .. }
+				),
+				Some(ElectionPhase::Cancelled) => false,
+				_ => true,
+			}
+		}
+
+		/// Cancel the election, recording `reason`
+		fn do_cancel_election(reason: BoundedVec<u8, T::MaxPayloadLength>) {
+			let now = frame_system::Pallet::<T>::block_number();
+			Phase::<T>::put(ElectionPhase::Cancelled);
+			PhaseChanges::<T>::insert(ElectionPhase::Cancelled, now);
+			CancellationReason::<T>::put(reason.clone());
+
+			Self::deposit_event(Event::ElectionCancelled { reason });
+			Self::deposit_event(Event::PhaseChanged { when: now, phase: ElectionPhase::Cancelled });
+		}
+
+		/// Candidates and the accounts that cast a ballot may dispute the result
+		fn may_dispute(who: &T::AccountId) -> bool {
+			Candidates::<T>::contains_key(who) ||
+				Ballots::<T>::contains_key(who) ||
+				TimelockedBallots::<T>::contains_key(who) ||
+				// Credential ballots are only linked to their voting account by the ballot log
+				(Self::credential_key().is_some() && BallotLogVoters::<T>::contains_key(who))
+		}
+
+		/// Ensure `origin` is root or signed by the CA
+		fn ensure_ca_or_root(origin: OriginFor<T>) -> DispatchResult {
+			if ensure_root(origin.clone()).is_ok() {
+				return Ok(())
+			}
 			let sender = ensure_signed(origin)?;
+			ensure!(Some(sender) == Self::ca(), <Error<T>>::SenderNotCA);
+			Ok(())
+		}
 
-			let ca = Self::ca();
-			if let Some(ca) = ca {
-				ensure!(sender == ca, <Error<T>>::SenderNotCA);
-			} else {
-				// if CA is not set, return error
-				return Err(Error::<T>::InternalError.into())
+		/// Receipt of a ballot or credential ballot, voters keep it to check their last ballot is
+		/// the one that was counted. The receipt is salted with the position of the ballot in the
+		/// log, so identical ballots cast twice from one account get different receipts
+		pub fn ballot_receipt<B: Encode>(voter: &T::AccountId, ballot: &B, index: u64) -> T::Hash {
+			T::Hashing::hash_of(&(voter, ballot, index))
+		}
+
+		/// Inputs of the credential tally: the registered credentials ordered by voter id and the
+		/// `(commitment, credential)` pairs of the credential ballots in casting order
+		pub fn credential_tally_inputs() -> (Vec<Ciphertext>, Vec<(u32, Ciphertext)>) {
+			let mut registered: Vec<_> = VoterCredentials::<T>::iter().collect();
+			registered.sort_by(|(left, _), (right, _)| left.cmp(right));
+			let ballots = (0..Self::credential_ballot_count())
+				.filter_map(CredentialBallots::<T>::get)
+				.map(|ballot| (ballot.commitment, ballot.credential))
+				.collect();
+			(registered.into_iter().map(|(_, credential)| credential).collect(), ballots)
+		}
+
+		/// Hash of the transcript proving a credential tally, as submitted with the tally
+		pub fn credential_transcript_hash(transcript: &credentials::TallyTranscript) -> T::Hash {
+			T::Hashing::hash_of(transcript)
+		}
+
+		/// Parent of two nodes of the ballot log
+		pub fn hash_ballot_log_nodes(left: &T::Hash, right: &T::Hash) -> T::Hash {
+			T::Hashing::hash_of(&(left, right))
+		}
+
+		/// Root of the ballot log holding `length` ballots: the hash of the length and the peaks
+		/// of its mountains, highest mountain first
+		pub fn bag_ballot_log_peaks(length: u64, peaks: &[T::Hash]) -> T::Hash {
+			T::Hashing::hash_of(&(length, peaks))
+		}
+
+		/// Mountains of a log holding `length` ballots, highest first, as `(height, first index)`.
+		/// There is one mountain per bit set in `length`
+		pub fn ballot_log_mountains(length: u64) -> Vec<(u32, u64)> {
+			let mut mountains = Vec::new();
+			let mut first = 0;
+			for height in (0..u64::BITS).rev() {
+				if length & (1 << height) != 0 {
+					mountains.push((height, first));
+					first += 1 << height;
+				}
 			}
+			mountains
+		}
 
-			// Ballot private key can only be revealed during the counting phase
-			ensure!(Self::get_phase() == Some(ElectionPhase::Counting), <Error<T>>::InvalidPhase);
+		/// Root of the first `length` ballots of the log. Nodes are never changed once written,
+		/// so the root of an earlier length can be computed later on
+		pub fn ballot_log_root(length: u64) -> T::Hash {
+			let peaks: Vec<_> = Self::ballot_log_mountains(length)
+				.into_iter()
+				.map(|(height, first)| {
+					Self::ballot_log_node(height, first >> height).unwrap_or_default()
+				})
+				.collect();
+			Self::bag_ballot_log_peaks(length, &peaks)
+		}
 
-			let k = BallotKeys::<T>::get();
-			if let Some(mut ballot_key) = k {
-				// Update the ballot key
-				ballot_key.private = private_key;
-				<BallotKeys<T>>::set(Some(ballot_key));
-			} else {
-				return Err(Error::<T>::InternalError.into())
+		/// Append a cast ballot to the ballot log, together with the mountain peaks it completes
+		fn append_to_ballot_log(voter: &T::AccountId, ballot: Vec<u8>, receipt: T::Hash) {
+			let index = Self::ballot_log_length();
+			let block = frame_system::Pallet::<T>::block_number();
+
+			BallotLog::<T>::insert(
+				index,
+				BallotLogEntry { voter: voter.clone(), ballot, receipt, block },
+			);
+			BallotLogLength::<T>::put(index + 1);
+			BallotLogIndex::<T>::insert(receipt, index);
+			BallotLogVoters::<T>::insert(voter, ());
+
+			// A right child completes its parent, at most one node per level is written
+			let mut node = receipt;
+			let mut height: u32 = 0;
+			let mut position = index;
+			BallotLogNodes::<T>::insert(height, position, node);
+			while position % 2 == 1 {
+				let left = Self::ballot_log_node(height, position - 1).unwrap_or_default();
+				node = Self::hash_ballot_log_nodes(&left, &node);
+				height += 1;
+				position /= 2;
+				BallotLogNodes::<T>::insert(height, position, node);
 			}
+		}
 
-			// TODO: Open the ballot
-			Ballots::<T>::iter_values()
-				.map(|ballot| ballot.commitment)
-				.for_each(|commitment| {
-					let current_count = Self::candidate_vote_count(commitment).unwrap_or(0);
-					CandidateVoteCount::<T>::insert(commitment, current_count + 1);
-				});
+		/// Build the proof that the ballot with `receipt` is part of the ballot log. Once voting
+		/// ended the proof leads to the published root, until then to the current root. The
+		/// proof reads one node per level of the log
+		pub fn ballot_inclusion_proof(receipt: &T::Hash) -> Option<InclusionProof<T::Hash>> {
+			let length = Self::published_ballot_log()
+				.map_or_else(Self::ballot_log_length, |(length, _)| length);
+			let index = Self::ballot_log_index(receipt).filter(|index| *index < length)?;
+
+			let mut path = Vec::new();
+			let mut peaks = Vec::new();
+			for (height, first) in Self::ballot_log_mountains(length) {
+				if (first..first + (1 << height)).contains(&index) {
+					for level in 0..height {
+						path.push(Self::ballot_log_node(level, (index >> level) ^ 1)?);
+					}
+				} else {
+					peaks.push(Self::ballot_log_node(height, first >> height)?);
+				}
+			}
+
+			Some(InclusionProof { index, length, path, peaks })
+		}
+
+		/// Check that `proof` leads from the ballot with `receipt` to the ballot log `root`
+		pub fn verify_inclusion_proof(
+			receipt: &T::Hash,
+			proof: &InclusionProof<T::Hash>,
+			root: &T::Hash,
+		) -> bool {
+			let mountains = Self::ballot_log_mountains(proof.length);
+			let position = match mountains
+				.iter()
+				.position(|&(height, first)| (first..first + (1 << height)).contains(&proof.index))
+			{
+				Some(position) => position,
+				None => return false,
+			};
+			if proof.path.len() != mountains[position].0 as usize ||
+				proof.peaks.len() + 1 != mountains.len()
+			{
+				return false
+			}
 
-			let mut max_count = 0;
-			let mut winner: u32 = 0;
-			CandidateVoteCount::<T>::iter().for_each(|(commitment, count)| {
-				if count > max_count {
-					max_count = count;
-					winner = commitment;
+			let peak = proof.path.iter().enumerate().fold(*receipt, |node, (level, sibling)| {
+				if (proof.index >> level) & 1 == 0 {
+					Self::hash_ballot_log_nodes(&node, sibling)
+				} else {
+					Self::hash_ballot_log_nodes(sibling, &node)
 				}
 			});
+			let mut peaks = proof.peaks.clone();
+			peaks.insert(position, peak);
+			&Self::bag_ballot_log_peaks(proof.length, &peaks) == root
+		}
+
+		/// The ballot with `receipt`, the proof of its inclusion in the ballot log and the root
+		/// the proof leads to
+		pub fn ballot_inclusion(
+			receipt: &T::Hash,
+		) -> Option<BallotInclusion<T::AccountId, T::Hash, T::BlockNumber>> {
+			let proof = Self::ballot_inclusion_proof(receipt)?;
+			let entry = Self::ballot_log_entry(proof.index)?;
+			let (root, published) = match Self::published_ballot_log() {
+				Some((_, root)) => (root, true),
+				None => (Self::ballot_log_root(proof.length), false),
+			};
+			Some(BallotInclusion { entry, proof, root, published })
+		}
+
+		/// Results of the election, without the ballots. Reads one entry per commitment, phase and
+		/// excluded candidate
+		pub fn election_summary() -> ElectionSummary<T::AccountId, T::BlockNumber> {
+			let mut tally: Vec<_> = CandidateVoteCount::<T>::iter().collect();
+			tally.sort();
+
+			use ElectionPhase::*;
+			let timeline = [
+				Initialization,
+				Registration,
+				BiasedSigner,
+				Voting,
+				Counting,
+				Completed,
+				Cancelled,
+			]
+			.into_iter()
+			.filter_map(|phase| PhaseChanges::<T>::get(&phase).map(|block| (phase, block)))
+			.collect();
+
+			let mut excluded_candidates: Vec<_> = ExcludedCandidates::<T>::iter().collect();
+			excluded_candidates.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+			ElectionSummary {
+				phase: Self::get_phase(),
+				winner: Self::get_winner(),
+				tally,
+				registered_voters: Voters::<T>::count() as u64,
+				credentials: Self::credential_key().is_some(),
+				timelock_round: Self::timelock_round(),
+				beacon_signature: Self::beacon_signature().map(|signature| signature.into_inner()),
+				rejected_ballots: Self::rejected_timelocked_ballots(),
+				ballot_log_length: Self::ballot_log_length(),
+				timeline,
+				excluded_candidates,
+			}
+		}
+
+		/// Up to `BALLOT_LOG_PAGE` entries of the ballot log, starting at index `start`
+		pub fn ballot_log_page(
+			start: u64,
+		) -> Vec<BallotLogEntry<T::AccountId, T::Hash, T::BlockNumber>> {
+			let end = start.saturating_add(BALLOT_LOG_PAGE).min(Self::ballot_log_length());
+			(start..end).filter_map(Self::ballot_log_entry).collect()
+		}
+
+		/// Whether `tally_step` can count the ballots: the ballot key is revealed and the ballots
+		/// are neither credential ballots nor time-locked
+		pub(crate) fn tally_open() -> bool {
+			if Self::credential_key().is_some() || Self::timelock_round().is_some() {
+				return false
+			}
+			Self::get_ballot_key().map_or(false, |key| !key.private.is_empty())
+		}
+
+		/// Add `votes` to the count of `commitment`, keeping track of the leader so selecting the
+		/// winner does not walk the counts. The leader has the most votes and a tie goes to the
+		/// lowest commitment, the winner does not depend on the order the ballots are counted in
+		fn count_votes(commitment: u32, votes: u32) {
+			let count = CandidateVoteCount::<T>::mutate(commitment, |count| {
+				let new = count.unwrap_or(0).saturating_add(votes);
+				*count = Some(new);
+				new
+			});
+			let leads = match Self::tally_leader() {
+				Some((leader, most)) => count > most || (count == most && commitment < leader),
+				None => count > 0,
+			};
+			if leads {
+				TallyLeader::<T>::put((commitment, count));
+			}
+		}
+
+		/// Select the leader of the tally as the winner, commitment 0 when no vote was counted
+		fn select_winner() {
+			let winner = Self::tally_leader().map_or(0, |(commitment, _)| commitment);
 
 			VoterWinner::<T>::set(Some(winner));
+			// The result can be disputed from now on
+			TallyCountedAt::<T>::put(frame_system::Pallet::<T>::block_number());
+		}
+
+		/// Re-verify the signatures stored with a ballot, as `vote` did when the ballot was cast
+		pub fn verify_ballot(voter: &T::AccountId, ballot: &Ballot) -> DispatchResult {
+			let signature_set =
+				<Vec<(T::AccountId, BlindSignature)>>::decode(&mut ballot.signature.as_slice())
+					.map_err(|_| <Error<T>>::InvalidBlindSignatures)?;
+			Self::verify_signature_set(voter, signature_set).map(|_| ())
+		}
+
+		/// Fetch and decode the RSA public key of a candidate. Once the keys are committed the
+		/// stored key must still match its fingerprint
+		fn candidate_rsa_key(
+			candidate: &T::AccountId,
+		) -> Result<blind_rsa_signatures::PublicKey, DispatchError> {
+			let candidate_struct =
+				Self::get_candidate(candidate.clone()).ok_or(<Error<T>>::RSAStorageNotFound)?;
+			if let Some(fingerprint) = Self::candidate_key_fingerprint(candidate) {
+				ensure!(
+					T::Hashing::hash(&candidate_struct.pubkey) == fingerprint,
+					<Error<T>>::CandidateKeyMismatch
+				);
+			}
+			blind_rsa_signatures::PublicKey::from_der(candidate_struct.pubkey.as_slice())
+				.map_err(|_| <Error<T>>::InvalidPublicKey.into())
+		}
+
+		/// Check a blind signature issued by a candidate. The candidate signs the blinded message
+		/// directly, so `signature ^ e mod n` must give back the blinded message
+		pub fn verify_blind_signature(
+			rsa_public: &blind_rsa_signatures::PublicKey,
+			blinded_message: &[u8],
+			signature: &[u8],
+		) -> bool {
+			use blind_rsa_signatures::reexports::rsa::{BigUint, PublicKeyParts};
+
+			let n = rsa_public.0.n();
+			let message = BigUint::from_bytes_be(blinded_message);
+			let signature = BigUint::from_bytes_be(signature);
+			if &message >= n || &signature >= n {
+				return false
+			}
+			signature.modpow(rsa_public.0.e(), n) == message
+		}
+
+		/// Parse a DER encoded RSA public key and make sure its modulus is at least `MinKeyBits`
+		pub fn check_rsa_key(pubkey: &[u8]) -> Result<blind_rsa_signatures::PublicKey, Error<T>> {
+			use blind_rsa_signatures::reexports::rsa::PublicKeyParts;
+
+			let key = blind_rsa_signatures::PublicKey::from_der(pubkey)
+				.map_err(|_| <Error<T>>::InvalidPublicKey)?;
+			ensure!(
+				key.0.n().bits() >= T::MinKeyBits::get() as usize,
+				<Error<T>>::PublicKeyTooShort
+			);
+			Ok(key)
+		}
+
+		/// Check that the DER encoded RSA `private_key` is a consistent private key belonging to
+		/// `public_key`
+		pub fn check_ballot_private_key(
+			public_key: &[u8],
+			private_key: &[u8],
+		) -> Result<(), Error<T>> {
+			use blind_rsa_signatures::reexports::rsa::PublicKeyParts;
+
+			let public = blind_rsa_signatures::PublicKey::from_der(public_key)
+				.map_err(|_| <Error<T>>::InvalidPublicKey)?;
+			let private = blind_rsa_signatures::SecretKey::from_der(private_key)
+				.map_err(|_| <Error<T>>::InvalidPrivateKey)?;
+			ensure!(
+				private.0.n() == public.0.n() && private.0.e() == public.0.e(),
+				<Error<T>>::BallotKeyMismatch
+			);
+			// The primes and the private exponent must fit the modulus, or the key would not
+			// decrypt what was encrypted under the public key
+			private.0.validate().map_err(|_| <Error<T>>::InvalidPrivateKey)?;
+			Ok(())
+		}
+
+		/// Check a candidates new key is valid and not used by any other candidate
+		fn validate_candidate_key(candidate: &T::AccountId, pubkey: &[u8]) -> DispatchResult {
+			Self::check_rsa_key(pubkey)?;
+			let duplicate = Candidates::<T>::iter().any(|(other, other_candidate)| {
+				&other != candidate && other_candidate.pubkey == pubkey
+			});
+			ensure!(!duplicate, <Error<T>>::DuplicatePublicKey);
+			Ok(())
+		}
+
+		/// Candidates can only join, leave or change their info before biased signing starts
+		pub(crate) fn candidates_open() -> bool {
+			matches!(
+				Self::get_phase(),
+				Some(ElectionPhase::Initialization) | Some(ElectionPhase::Registration)
+			)
+		}
+
+		/// Record the fingerprint of every candidates RSA public key
+		pub(crate) fn commit_candidate_keys() {
+			Candidates::<T>::iter().for_each(|(candidate, candidate_struct)| {
+				let fingerprint = T::Hashing::hash(&candidate_struct.pubkey);
+				CandidateKeyFingerprints::<T>::insert(candidate.clone(), fingerprint);
+				Self::deposit_event(Event::CandidateKeyCommitted { candidate, fingerprint });
+			});
+		}
+
+		/// Verify a candidates blinded signature for a voter and store it
+		fn do_biased_signing(
+			rsa_public: &blind_rsa_signatures::PublicKey,
+			candidate: &T::AccountId,
+			voter: VoterId<T>,
+			blinded_signature: BoundedVec<u8, T::SignatureLength>,
+		) -> DispatchResult {
+			ensure!(<Voters<T>>::contains_key(voter), <Error<T>>::VoterDoesNotExist);
+			// The candidate signs the message the voter blinded for their key
+			let message = Self::blinded_message(voter, candidate)
+				.ok_or(<Error<T>>::BlindedMessageNotFound)?;
+
+			// Verify the signatures match the candidates public key, if verification fails we
+			// need to kill the transaction
+			ensure!(
+				Self::verify_blind_signature(rsa_public, &message, &blinded_signature),
+				<Error<T>>::RSAInvalidSignature
+			);
+
+			// A voter signed again replaces their signature, they still count once
+			if !<BlindedSignatures<T>>::contains_key(voter, candidate) {
+				SignedVoterCount::<T>::mutate(candidate, |count| *count += 1);
+			}
+			<BlindedSignatures<T>>::insert(voter, candidate, blinded_signature);
+
+			Ok(())
+		}
+
+		/// Check the storage invariants of the pallet
+		#[cfg(any(feature = "try-runtime", test))]
+		pub fn do_try_state() -> Result<(), &'static str> {
+			// The candidate count follows every nomination approval and withdrawal
+			let candidates = Candidates::<T>::iter_keys().count() as u64;
+			ensure!(
+				Self::candidates_count().unwrap_or(0) == candidates,
+				"CandidatesCount does not match the number of candidates"
+			);
+
+			// Every voter is stored under the hash of their blinded key, and counted once
+			ensure!(
+				Voters::<T>::iter().all(|(id, voter)| Self::voter_id(&voter.blinded_pubkey) == id),
+				"A voter is not stored under the id of their blinded key"
+			);
+			ensure!(
+				Voters::<T>::count() as usize == Voters::<T>::iter_keys().count(),
+				"The voter counter does not match the number of voters"
+			);
+
+			// Blinded messages are registered for registered voters, all candidates at once
+			ensure!(
+				BlindedMessages::<T>::iter_keys().all(|(voter, candidate)| {
+					Voters::<T>::contains_key(voter) && Candidates::<T>::contains_key(candidate)
+				}),
+				"A blinded message belongs to an unknown voter or candidate"
+			);
+			let mut with_messages: Vec<_> =
+				BlindedMessages::<T>::iter_keys().map(|(voter, _)| voter).collect();
+			with_messages.sort();
+			with_messages.dedup();
+			ensure!(
+				Self::blinded_message_count() == with_messages.len() as u64,
+				"BlindedMessageCount does not match the voters with blinded messages"
+			);
+
+			// Blinded signatures are only issued by candidates to registered voters
+			ensure!(
+				BlindedSignatures::<T>::iter_keys().all(|(voter, candidate)| {
+					Voters::<T>::contains_key(voter) && Candidates::<T>::contains_key(candidate)
+				}),
+				"A blinded signature belongs to an unknown voter or candidate"
+			);
+
+			// Only candidates are excluded, and at least one candidate keeps signing
+			ensure!(
+				ExcludedCandidates::<T>::iter_keys()
+					.all(|candidate| { Candidates::<T>::contains_key(candidate) }),
+				"An excluded candidate is not a candidate"
+			);
+			ensure!(
+				ExcludedCandidates::<T>::count() == 0 || Self::signing_candidates_count() > 0,
+				"Every candidate was excluded"
+			);
+
+			// Every candidate counts the voters they signed
+			ensure!(
+				Candidates::<T>::iter_keys().all(|candidate| {
+					let signed = BlindedSignatures::<T>::iter_keys()
+						.filter(|(_, signer)| signer == &candidate)
+						.count() as u64;
+					Self::signed_voter_count(candidate) == signed
+				}),
+				"SignedVoterCount does not match the blinded signatures of a candidate"
+			);
+
+			// Credentials are only registered for registered voters
+			ensure!(
+				VoterCredentials::<T>::iter_keys().all(|voter| Voters::<T>::contains_key(voter)),
+				"A credential belongs to an unknown voter"
+			);
+
+			// Credential ballots are appended in order
+			let credential_ballots = CredentialBallots::<T>::iter_keys().count() as u64;
+			ensure!(
+				Self::credential_ballot_count() as u64 == credential_ballots,
+				"CredentialBallotCount does not match the number of credential ballots"
+			);
+
+			// The leaves of the ballot log are the receipts of its entries, in order, and every
+			// inner node is the parent of its children
+			let length = Self::ballot_log_length();
+			for index in 0..length {
+				let entry =
+					Self::ballot_log_entry(index).ok_or("The ballot log has a missing entry")?;
+				let mut encoded = entry.voter.encode();
+				encoded.extend_from_slice(&entry.ballot);
+				encoded.extend_from_slice(&index.encode());
+				ensure!(
+					T::Hashing::hash(&encoded) == entry.receipt,
+					"A ballot log receipt does not match its ballot"
+				);
+				ensure!(
+					Self::ballot_log_node(0u32, index) == Some(entry.receipt),
+					"A ballot log leaf is not the receipt of its entry"
+				);
+			}
+			for (height, position, node) in BallotLogNodes::<T>::iter() {
+				ensure!(
+					position
+						.checked_add(1)
+						.and_then(|end| end.checked_shl(height))
+						.map_or(false, |end| end <= length),
+					"A ballot log node lies beyond the end of the log"
+				);
+				if height > 0 {
+					let left = Self::ballot_log_node(height - 1, 2 * position)
+						.ok_or("A ballot log node has a missing child")?;
+					let right = Self::ballot_log_node(height - 1, 2 * position + 1)
+						.ok_or("A ballot log node has a missing child")?;
+					ensure!(
+						Self::hash_ballot_log_nodes(&left, &right) == node,
+						"A ballot log node is not the parent of its children"
+					);
+				}
+			}
+			ensure!(
+				BallotLog::<T>::iter_values()
+					.all(|entry| BallotLogVoters::<T>::contains_key(&entry.voter)),
+				"A voting account in the ballot log is not indexed"
+			);
+			ensure!(
+				BallotLogIndex::<T>::iter().all(|(receipt, index)| {
+					Self::ballot_log_entry(index).map(|entry| entry.receipt) == Some(receipt)
+				}),
+				"A ballot receipt is indexed to the wrong log entry"
+			);
+			ensure!(
+				BallotLog::<T>::iter_keys().count() as u64 == Self::ballot_log_length(),
+				"BallotLogLength does not match the number of log entries"
+			);
+			if let Some((length, published)) = Self::published_ballot_log() {
+				ensure!(
+					length <= Self::ballot_log_length() &&
+						Self::ballot_log_root(length) == published,
+					"The published ballot log root is not the root of the log"
+				);
+			}
+
+			// A revealed ballot key opens the ballots encrypted under the public key
+			if let Some(key) = Self::get_ballot_key().filter(|key| !key.private.is_empty()) {
+				ensure!(
+					Self::check_ballot_private_key(&key.public, &key.private).is_ok(),
+					"The revealed ballot key does not belong to the ballot public key"
+				);
+			}
+
+			// A submitted beacon signature is the signature of the timelock round
+			if let Some(signature) = Self::beacon_signature() {
+				let round = Self::timelock_round().ok_or("A beacon signature without timelock")?;
+				ensure!(
+					T::Beacon::verify(round, &signature),
+					"The beacon signature is not the signature of the timelock round"
+				);
+			}
+
+			// Ballots are time-locked exactly in timelock elections, which take no credentials
+			ensure!(
+				Self::timelock_round().is_some() ||
+					TimelockedBallots::<T>::iter_keys().next().is_none(),
+				"A time-locked ballot without timelock"
+			);
+			ensure!(
+				Self::timelock_round().is_none() || Ballots::<T>::iter_keys().next().is_none(),
+				"A ballot that is not time-locked in a timelock election"
+			);
+			ensure!(
+				Self::timelock_round().is_none() || Self::credential_key().is_none(),
+				"A timelock election with credentials"
+			);
+
+			// The tally cursor points at a ballot and is cleared once the tally is done
+			if let Some(last) = Self::tally_cursor() {
+				ensure!(
+					Ballots::<T>::contains_key(last.clone()) ||
+						TimelockedBallots::<T>::contains_key(last),
+					"The tally cursor is not a ballot"
+				);
+				ensure!(Self::winner().is_none(), "The tally cursor was kept after the tally");
+			}
+
+			// Disputes are counted as open until the CA resolves them, and the election only
+			// completes without open disputes
+			let open = Disputes::<T>::iter_values()
+				.filter(|dispute| dispute.resolution.is_none())
+				.count() as u32;
+			ensure!(Self::open_disputes() == open, "OpenDisputes does not match the open disputes");
+			ensure!(
+				open == 0 || Self::get_phase() != Some(ElectionPhase::Completed),
+				"The election completed with open disputes"
+			);
+
+			// The leader has the most votes, the lowest commitment on a tie
+			let leader = CandidateVoteCount::<T>::iter().filter(|(_, votes)| *votes > 0).fold(
+				None,
+				|leader: Option<(u32, u32)>, (commitment, votes)| match leader {
+					Some((c, v)) if v > votes || (v == votes && c < commitment) => leader,
+					_ => Some((commitment, votes)),
+				},
+			);
+			ensure!(Self::tally_leader() == leader, "TallyLeader does not lead the vote counts");
+
+			// Once the ballots are counted every ballot counts exactly once, of the credential
+			// ballots only the ones left after filtering count
+			if Self::winner().is_some() {
+				let counted: u64 = CandidateVoteCount::<T>::iter_values().map(u64::from).sum();
+				if Self::credential_key().is_some() {
+					ensure!(
+						counted <= credential_ballots,
+						"CandidateVoteCount exceeds the number of credential ballots"
+					);
+				} else if Self::timelock_round().is_some() {
+					// Time-locked ballots that did not decrypt are rejected instead of counted
+					ensure!(
+						counted + Self::rejected_timelocked_ballots() as u64 ==
+							TimelockedBallots::<T>::iter_keys().count() as u64,
+						"CandidateVoteCount and the rejected ballots do not add up to the number of time-locked ballots"
+					);
+				} else {
+					ensure!(
+						counted == Ballots::<T>::iter_keys().count() as u64,
+						"CandidateVoteCount does not add up to the number of ballots"
+					);
+				}
+			}
 
 			Ok(())
 		}
-	}
 
-	impl<T: Config> Pallet<T> {
 		pub fn get_ca() -> Option<T::AccountId> {
 			<CentralAuthority<T>>::get()
 		}
@@ -619,7 +2687,7 @@ pub mod pallet {
 			<Phase<T>>::get()
 		}
 
-		pub fn get_voter(voter: u64) -> Option<Voter> {
+		pub fn get_voter(voter: VoterId<T>) -> Option<Voter> {
 			<Voters<T>>::get(voter)
 		}
 
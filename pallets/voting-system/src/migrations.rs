@@ -0,0 +1,214 @@
+//! Storage migrations of the voting system pallet.
+//!
+//! Every storage version gets its own module holding the migration from the previous version.
+//! A migration only runs when the on-chain storage version is the one it migrates from and bumps
+//! the version when done, so it is safe to leave it in the runtime `Migrations` after the upgrade.
+//! Changing the layout of a stored type (`Voter`, `Ballot`, `Candidate`, ...) needs a new version
+//! whose migration translates the old values, keeping a copy of the old type in its module.
+//! Storage added since the last release starts out empty on released chains and needs no
+//! migration, only a change to storage that was released does.
+
+use crate::{
+	BlindedSignatures, CandidateKeyFingerprints, Candidates, Config, ElectionPhase, Pallet, Phase,
+	PhaseChanges, SignedVoterCount, Voter, Voters,
+};
+use core::marker::PhantomData;
+use frame_support::{
+	traits::{Get, GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+#[cfg(feature = "try-runtime")]
+use {
+	codec::{Decode, Encode},
+	frame_support::ensure,
+};
+
+/// Version 1 is the first versioned layout, migrated to from the released storage.
+///
+/// - Every phase start is recorded in `PhaseChanges` and the candidate keys are committed in
+///   `CandidateKeyFingerprints` when biased signing starts.
+/// - Voters are identified by the hash of their blinded key instead of their registration index,
+///   see `VoterId`. The voters and their blinded signatures move to the new ids, `VoterCount` is
+///   replaced by the counter of `Voters` and the voters every candidate signed are counted in
+///   `SignedVoterCount`.
+pub mod v1 {
+	use super::*;
+	use frame_support::{
+		inherent::Vec,
+		sp_std::collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+		storage_alias, BoundedVec, Twox64Concat,
+	};
+
+	/// The released storage keyed by the registration index
+	pub(crate) mod old {
+		use super::*;
+
+		#[storage_alias]
+		pub type Voters<T: Config> = StorageMap<Pallet<T>, Twox64Concat, u64, Voter>;
+
+		#[storage_alias]
+		pub type BlindedSignatures<T: Config> = StorageDoubleMap<
+			Pallet<T>,
+			Twox64Concat,
+			u64,
+			Twox64Concat,
+			<T as frame_system::Config>::AccountId,
+			BoundedVec<u8, <T as Config>::SignatureLength>,
+		>;
+
+		#[storage_alias]
+		pub type VoterCount<T: Config> = StorageValue<Pallet<T>, u64>;
+	}
+
+	/// Migrate the released storage to version 1.
+	///
+	/// - The current phase is recorded as started at the upgrade block, the actual start is lost.
+	/// - Once biased signing started the current candidate keys are committed.
+	/// - Entries of a voter index without a voter are dropped, nothing could ever use them. Voters
+	///   registered twice with the same blinded key share their id and end up as a single entry,
+	///   counted once per candidate that signed them.
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let on_chain_version = Pallet::<T>::on_chain_storage_version();
+			if on_chain_version != 0 {
+				log::info!(
+					target: "runtime::voting-system",
+					"Skipping the migration to v1, the storage version is {:?}",
+					on_chain_version
+				);
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut reads = 2;
+			let mut writes = 1;
+			if let Some(phase) = Phase::<T>::get() {
+				reads += 1;
+				if !PhaseChanges::<T>::contains_key(&phase) {
+					PhaseChanges::<T>::insert(&phase, frame_system::Pallet::<T>::block_number());
+					writes += 1;
+				}
+
+				if keys_committed(&phase) && CandidateKeyFingerprints::<T>::iter().next().is_none()
+				{
+					let candidates = Candidates::<T>::iter().count() as u64;
+					Pallet::<T>::commit_candidate_keys();
+					reads += 1 + candidates;
+					writes += candidates;
+				}
+			}
+
+			// The old and the new maps share their prefix, drain the old entries before writing
+			let voters: Vec<_> = old::Voters::<T>::drain().collect();
+			let signatures: Vec<_> = old::BlindedSignatures::<T>::drain().collect();
+			old::VoterCount::<T>::kill();
+			reads += (voters.len() + signatures.len()) as u64;
+			writes += 1 + (voters.len() + signatures.len()) as u64;
+
+			let mut ids = BTreeMap::new();
+			for (index, voter) in voters {
+				let id = Pallet::<T>::voter_id(&voter.blinded_pubkey);
+				ids.insert(index, id);
+				Voters::<T>::insert(id, voter);
+			}
+			// Every voter also bumps the counter of `Voters`
+			writes += 2 * ids.len() as u64;
+
+			let mut signed = BTreeSet::new();
+			for (index, candidate, signature) in signatures {
+				if let Some(id) = ids.get(&index) {
+					BlindedSignatures::<T>::insert(id, &candidate, signature);
+					signed.insert((*id, candidate));
+					writes += 1;
+				}
+			}
+			let mut counts = BTreeMap::new();
+			for (_, candidate) in signed {
+				*counts.entry(candidate).or_insert(0u64) += 1;
+			}
+			writes += counts.len() as u64;
+			for (candidate, count) in counts {
+				SignedVoterCount::<T>::insert(candidate, count);
+			}
+
+			StorageVersion::new(1).put::<Pallet<T>>();
+			log::info!(target: "runtime::voting-system", "Migrated the storage to v1");
+
+			T::DbWeight::get().reads_writes(reads, writes)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			// Duplicate blinded keys collapse into one voter, only distinct ids and distinct
+			// (id, candidate) pairs survive the move
+			let ids: BTreeMap<_, _> = old::Voters::<T>::iter()
+				.map(|(index, voter)| (index, Pallet::<T>::voter_id(&voter.blinded_pubkey)))
+				.collect();
+			let signatures: BTreeSet<_> = old::BlindedSignatures::<T>::iter_keys()
+				.filter_map(|(index, candidate)| ids.get(&index).map(|id| (*id, candidate)))
+				.collect();
+			let ids: BTreeSet<_> = ids.into_values().collect();
+			Ok((
+				Phase::<T>::get(),
+				Candidates::<T>::iter().count() as u32,
+				ids.into_iter().collect::<Vec<_>>(),
+				signatures.len() as u64,
+			)
+				.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let (phase, candidates, ids, signatures) =
+				<(Option<ElectionPhase>, u32, Vec<T::Hash>, u64)>::decode(&mut &state[..])
+					.map_err(|_| "The pre upgrade state can not be decoded")?;
+
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 1, "The storage version is not 1");
+			ensure!(Phase::<T>::get() == phase, "The phase changed");
+			ensure!(
+				Candidates::<T>::iter().count() as u32 == candidates,
+				"The number of candidates changed"
+			);
+			if let Some(phase) = phase {
+				ensure!(PhaseChanges::<T>::contains_key(&phase), "The current phase has no start");
+				if keys_committed(&phase) {
+					ensure!(
+						Candidates::<T>::iter_keys().all(|candidate| {
+							CandidateKeyFingerprints::<T>::contains_key(candidate)
+						}),
+						"A candidate key is not committed"
+					);
+				}
+			}
+
+			ensure!(Voters::<T>::count() as usize == ids.len(), "The number of voters changed");
+			ensure!(
+				ids.iter().all(|id| Voters::<T>::contains_key(id)),
+				"A voter was not moved to their id"
+			);
+			ensure!(
+				BlindedSignatures::<T>::iter_keys().count() as u64 == signatures,
+				"The number of blinded signatures changed"
+			);
+			ensure!(
+				SignedVoterCount::<T>::iter_values().sum::<u64>() == signatures,
+				"SignedVoterCount does not add up to the blinded signatures"
+			);
+			ensure!(old::VoterCount::<T>::get().is_none(), "VoterCount was kept");
+
+			Ok(())
+		}
+	}
+
+	/// Candidate keys are committed from the biased signer phase on
+	fn keys_committed(phase: &ElectionPhase) -> bool {
+		matches!(
+			phase,
+			ElectionPhase::BiasedSigner |
+				ElectionPhase::Voting |
+				ElectionPhase::Counting |
+				ElectionPhase::Completed
+		)
+	}
+}
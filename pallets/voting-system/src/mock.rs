@@ -1,14 +1,20 @@
 use crate as pallet_voting_system;
-use frame_support::traits::{ConstU16, ConstU64};
+use crate::timelock::{self, BeaconPublicKey, BlsBeacon};
+use codec::Encode;
+use frame_support::{
+	traits::{ConstU16, ConstU64, Contains},
+	BoundedVec,
+};
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
-	testing::Header,
+	testing::{Header, TestSignature, TestXt, UintAuthorityId},
 	traits::{BlakeTwo256, IdentityLookup},
 };
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
+pub type Extrinsic = TestXt<RuntimeCall, ()>;
 
 // Configure a mock runtime to test the pallet.
 frame_support::construct_runtime!(
@@ -22,8 +28,20 @@ frame_support::construct_runtime!(
 	}
 );
 
+/// Call filter of the runtime, see `Pallet::is_call_allowed`
+pub struct ElectionCallFilter;
+
+impl Contains<RuntimeCall> for ElectionCallFilter {
+	fn contains(call: &RuntimeCall) -> bool {
+		match call {
+			RuntimeCall::VotingSystem(call) => VotingSystem::is_call_allowed(call),
+			_ => true,
+		}
+	}
+}
+
 impl system::Config for Test {
-	type BaseCallFilter = frame_support::traits::Everything;
+	type BaseCallFilter = ElectionCallFilter;
 	type BlockWeights = ();
 	type BlockLength = ();
 	type DbWeight = ();
@@ -50,38 +68,211 @@ impl system::Config for Test {
 }
 
 frame_support::parameter_types! {
-	pub const SignatureLength: u32 = 32;
+	pub const SignatureLength: u32 = 256;
+	pub const MinKeyBits: u32 = 2048;
+	pub const MaxBatchSize: u32 = 16;
+	pub const MaxPayloadLength: u32 = 1024;
+	pub MockBeaconPublicKey: BeaconPublicKey = timelock::public_key(&MOCK_BEACON_SECRET).unwrap();
+}
+
+impl frame_system::offchain::SigningTypes for Test {
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	RuntimeCall: From<LocalCall>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+	RuntimeCall: From<LocalCall>,
+{
+	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: RuntimeCall,
+		_public: UintAuthorityId,
+		_account: u64,
+		nonce: u64,
+	) -> Option<(RuntimeCall, <Extrinsic as sp_runtime::traits::Extrinsic>::SignaturePayload)> {
+		Some((call, (nonce, ())))
+	}
+}
+
+/// Offchain worker keys backed by the `UintAuthorityId` test keystore
+pub struct TestAuthId;
+
+impl frame_system::offchain::AppCrypto<UintAuthorityId, TestSignature> for TestAuthId {
+	type RuntimeAppPublic = UintAuthorityId;
+	type GenericPublic = UintAuthorityId;
+	type GenericSignature = TestSignature;
+}
+
+/// Secret key of the local BLS beacon of the tests
+pub const MOCK_BEACON_SECRET: [u8; 32] = [7; 32];
+
+/// Signature of `round` by the local beacon
+pub fn mock_beacon_signature(round: u64) -> Vec<u8> {
+	timelock::sign(&MOCK_BEACON_SECRET, round).unwrap().to_vec()
 }
 
 impl pallet_voting_system::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
+	type AuthorityId = TestAuthId;
 	type SignatureLength = SignatureLength;
+	type MinKeyBits = MinKeyBits;
+	type MaxBatchSize = MaxBatchSize;
+	type MaxPayloadLength = MaxPayloadLength;
+	type BiasedSignerPeriod = ConstU64<10>;
+	type DisputePeriod = ConstU64<10>;
+	type Beacon = BlsBeacon<MockBeaconPublicKey>;
+	type WeightInfo = ();
+}
+
+// Test externalities checking the pallet invariants once the test code ran
+pub struct TestExternalities(sp_io::TestExternalities);
+
+impl TestExternalities {
+	pub fn execute_with<R>(&mut self, execute: impl FnOnce() -> R) -> R {
+		self.0.execute_with(|| {
+			let result = execute();
+			VotingSystem::do_try_state().expect("Storage invariants hold after the test");
+			result
+		})
+	}
+}
+
+impl std::ops::Deref for TestExternalities {
+	type Target = sp_io::TestExternalities;
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl std::ops::DerefMut for TestExternalities {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
 }
 
 // Build genesis storage according to the mock runtime.
-pub fn new_test_ext(root_key: u64) -> sp_io::TestExternalities {
+pub fn new_test_ext(root_key: u64) -> TestExternalities {
 	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
 	pallet_voting_system::GenesisConfig::<Test> {
 		central_authority: Some(root_key),
-		candidates: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10],
-		ballot_public_key: vec![1, 2, 3],
+		candidates: (1..=10).map(test_candidate).collect(),
+		ballot_public_key: test_public_key(BALLOT_KEY),
+		revote_policy: Default::default(),
 	}
 	.assimilate_storage(&mut t)
 	.unwrap();
-	t.into()
+	TestExternalities(t.into())
+}
+
+// Build genesis storage for the benchmarks. They set up the storage of every call directly, which
+// does not uphold the invariants checked by `TestExternalities`
+#[cfg(feature = "runtime-benchmarks")]
+pub fn new_benchmark_ext() -> sp_io::TestExternalities {
+	new_test_ext(1).0
 }
 
 pub fn new_test_ext_w_candidate(
 	root_key: u64,
 	candidate: Vec<<Test as system::Config>::AccountId>,
-) -> sp_io::TestExternalities {
+) -> TestExternalities {
 	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
 	pallet_voting_system::GenesisConfig::<Test> {
 		central_authority: Some(root_key),
-		candidates: candidate,
-		ballot_public_key: vec![1, 2, 3],
+		candidates: candidate.into_iter().map(test_candidate).collect(),
+		ballot_public_key: test_public_key(BALLOT_KEY),
+		revote_policy: Default::default(),
 	}
 	.assimilate_storage(&mut t)
 	.unwrap();
-	t.into()
+	TestExternalities(t.into())
+}
+
+/// Genesis entry of the candidate with account id `candidate`, named after their account
+pub fn test_candidate(candidate: u64) -> (u64, String, Vec<u8>) {
+	(candidate, format!("Candidate {}", candidate), test_public_key(candidate))
+}
+
+/// Test key pair of the ballot key
+pub const BALLOT_KEY: u64 = 12;
+
+/// DER encoded 2048 bit RSA key pairs used by the tests, the candidate with account id `n` uses
+/// key pair `n`
+const TEST_KEYS: [(&[u8], &[u8]); 12] = [
+	(include_bytes!("../test-keys/key1.pub.der"), include_bytes!("../test-keys/key1.der")),
+	(include_bytes!("../test-keys/key2.pub.der"), include_bytes!("../test-keys/key2.der")),
+	(include_bytes!("../test-keys/key3.pub.der"), include_bytes!("../test-keys/key3.der")),
+	(include_bytes!("../test-keys/key4.pub.der"), include_bytes!("../test-keys/key4.der")),
+	(include_bytes!("../test-keys/key5.pub.der"), include_bytes!("../test-keys/key5.der")),
+	(include_bytes!("../test-keys/key6.pub.der"), include_bytes!("../test-keys/key6.der")),
+	(include_bytes!("../test-keys/key7.pub.der"), include_bytes!("../test-keys/key7.der")),
+	(include_bytes!("../test-keys/key8.pub.der"), include_bytes!("../test-keys/key8.der")),
+	(include_bytes!("../test-keys/key9.pub.der"), include_bytes!("../test-keys/key9.der")),
+	(include_bytes!("../test-keys/key10.pub.der"), include_bytes!("../test-keys/key10.der")),
+	(include_bytes!("../test-keys/key11.pub.der"), include_bytes!("../test-keys/key11.der")),
+	(include_bytes!("../test-keys/key12.pub.der"), include_bytes!("../test-keys/key12.der")),
+];
+
+pub fn test_public_key(index: u64) -> Vec<u8> {
+	TEST_KEYS[index as usize - 1].0.to_vec()
+}
+
+pub fn test_secret_key(index: u64) -> Vec<u8> {
+	TEST_KEYS[index as usize - 1].1.to_vec()
+}
+
+/// Blind the voting account `voter` for the test key of `candidate`, as the voter client does
+pub fn blind(candidate: u64, voter: u64) -> blind_rsa_signatures::BlindingResult {
+	let options = blind_rsa_signatures::Options::default();
+	let public_key =
+		blind_rsa_signatures::PublicKey::from_der(&test_public_key(candidate)).unwrap();
+	public_key
+		.blind(&mut rand::thread_rng(), &voter.encode(), true, &options)
+		.unwrap()
+}
+
+/// Blind sign `blinded_message` with the test key of `candidate`, as the candidate does
+pub fn blind_sign(candidate: u64, blinded_message: &[u8]) -> BoundedVec<u8, SignatureLength> {
+	let options = blind_rsa_signatures::Options::default();
+	let secret_key =
+		blind_rsa_signatures::SecretKey::from_der(&test_secret_key(candidate)).unwrap();
+	let signature = secret_key
+		.blind_sign(&mut rand::thread_rng(), blinded_message, &options)
+		.unwrap();
+	BoundedVec::try_from(signature.to_vec()).unwrap()
+}
+
+// Run the whole blind signature flow for a voter with the test key of a candidate, the result is
+// the signature the voter submits with their vote
+pub fn candidate_signature(candidate: u64, voter: u64) -> crate::BlindSignature {
+	let mut rng = rand::thread_rng();
+	let options = blind_rsa_signatures::Options::default();
+	let public_key =
+		blind_rsa_signatures::PublicKey::from_der(&test_public_key(candidate)).unwrap();
+	let secret_key =
+		blind_rsa_signatures::SecretKey::from_der(&test_secret_key(candidate)).unwrap();
+
+	let message = voter.encode();
+	let blinding = public_key.blind(&mut rng, &message, true, &options).unwrap();
+	let msg_randomizer = blinding.msg_randomizer.as_ref().unwrap().0;
+	let blind_signature = secret_key.blind_sign(&mut rng, &blinding.blind_msg, &options).unwrap();
+	let signature = public_key
+		.finalize(
+			&blind_signature,
+			&blinding.secret,
+			Some(blind_rsa_signatures::MessageRandomizer::from(msg_randomizer)),
+			&message,
+			&options,
+		)
+		.unwrap();
+
+	crate::BlindSignature { signature: signature.to_vec(), msg_randomizer }
 }
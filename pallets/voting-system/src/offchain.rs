@@ -0,0 +1,159 @@
+//! Offchain worker performing the biased signing for candidates.
+//!
+//! A node signs on behalf of a candidate when its keystore holds the candidates account key under
+//! [`KEY_TYPE`] and its persistent offchain storage holds the candidates DER encoded RSA private
+//! key, as a SCALE encoded `Vec<u8>`, under [`rsa_key_storage_key`]. During the biased signer phase
+//! the worker signs the message every voter blinded for the candidates key, see
+//! `register_blinded_messages`, unless the voter already has the candidates signature, and submits
+//! the signatures as a signed `biased_signing_batch` transaction.
+
+use crate::{
+	BlindedMessages, BlindedSignatures, Call, Config, ElectionPhase, ExcludedCandidates, Pallet,
+	VoterId, Voters,
+};
+use codec::Encode;
+use frame_support::{inherent::Vec, sp_std::vec, traits::Get, BoundedVec};
+use frame_system::offchain::{AppCrypto, SendSignedTransaction, Signer};
+use rand_chacha::{
+	rand_core::{CryptoRng, RngCore, SeedableRng},
+	ChaCha20Rng,
+};
+use sp_runtime::{
+	offchain::storage::StorageValueRef, traits::IdentifyAccount, KeyTypeId, RuntimeAppPublic,
+	SaturatedConversion,
+};
+
+/// Key type of the candidate account keys used to sign the offchain worker transactions.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"vote");
+
+/// Prefix of the offchain storage keys holding the candidates RSA private keys.
+pub const RSA_KEY_STORAGE_PREFIX: &[u8] = b"voting-system::rsa-key::";
+
+/// Offchain storage key remembering the block of the last submission.
+const LAST_SUBMISSION_KEY: &[u8] = b"voting-system::last-submission";
+
+/// Number of blocks to wait before signing again, gives the last batch time to be included.
+const RESUBMIT_INTERVAL: u32 = 5;
+
+/// Account keys used by the offchain worker to sign transactions.
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_runtime::{
+		app_crypto::{app_crypto, sr25519},
+		MultiSignature, MultiSigner,
+	};
+	app_crypto!(sr25519, KEY_TYPE);
+
+	pub struct CandidateAuthId;
+
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for CandidateAuthId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+}
+
+/// Offchain storage key holding the DER encoded RSA private key of `candidate`.
+pub fn rsa_key_storage_key<AccountId: Encode>(candidate: &AccountId) -> Vec<u8> {
+	let mut key = RSA_KEY_STORAGE_PREFIX.to_vec();
+	key.extend(candidate.encode());
+	key
+}
+
+impl<T: Config> Pallet<T> {
+	/// Sign and submit the missing blinded signatures of every candidate this node holds keys for
+	pub(crate) fn offchain_biased_signing(block_number: T::BlockNumber) {
+		// Signatures submitted while the election is paused would be filtered out
+		if Self::get_phase() != Some(ElectionPhase::BiasedSigner) || Self::paused() {
+			return
+		}
+
+		// Give the previous submission some blocks to land before signing the same voters again
+		let block_number: u32 = block_number.saturated_into();
+		let last_submission = StorageValueRef::persistent(LAST_SUBMISSION_KEY);
+		if let Ok(Some(last)) = last_submission.get::<u32>() {
+			if block_number < last.saturating_add(RESUBMIT_INTERVAL) {
+				return
+			}
+		}
+
+		let mut submitted = false;
+		for key in <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::RuntimeAppPublic::all() {
+			let generic_public =
+				<T::AuthorityId as AppCrypto<T::Public, T::Signature>>::GenericPublic::from(key);
+			let public: T::Public = generic_public.into();
+			let candidate = public.clone().into_account();
+			// Excluded candidates no longer sign
+			if Self::get_candidate(candidate.clone()).is_none() ||
+				ExcludedCandidates::<T>::contains_key(&candidate)
+			{
+				continue
+			}
+
+			// Without the RSA key this node can not sign for the candidate
+			let secret_key = match StorageValueRef::persistent(&rsa_key_storage_key(&candidate))
+				.get::<Vec<u8>>()
+			{
+				Ok(Some(der)) => der,
+				_ => continue,
+			};
+
+			let mut rng = ChaCha20Rng::from_seed(sp_io::offchain::random_seed());
+			let signatures = Self::sign_pending_voters(&candidate, &secret_key, &mut rng);
+			if signatures.is_empty() {
+				continue
+			}
+
+			let call = Call::biased_signing_batch { candidate: candidate.clone(), signatures };
+			let result = Signer::<T, T::AuthorityId>::all_accounts()
+				.with_filter(vec![public])
+				.send_signed_transaction(|_account| call.clone());
+			submitted |= result.iter().any(|(_, res)| res.is_ok());
+		}
+
+		if submitted {
+			last_submission.set(&block_number);
+		}
+	}
+
+	/// Blind sign the message of every voter still missing the signature of `candidate`, at most
+	/// `MaxBatchSize` voters are signed per call. `secret_key` is the candidates DER encoded RSA
+	/// private key
+	pub fn sign_pending_voters<R: RngCore + CryptoRng>(
+		candidate: &T::AccountId,
+		secret_key: &[u8],
+		rng: &mut R,
+	) -> BoundedVec<(VoterId<T>, BoundedVec<u8, T::SignatureLength>), T::MaxBatchSize> {
+		let mut signatures = BoundedVec::default();
+		let secret_key = match blind_rsa_signatures::SecretKey::from_der(secret_key) {
+			Ok(key) => key,
+			Err(_) => return signatures,
+		};
+		let options = blind_rsa_signatures::Options::default();
+
+		for voter_id in Voters::<T>::iter_keys() {
+			if signatures.len() >= T::MaxBatchSize::get() as usize {
+				break
+			}
+			if BlindedSignatures::<T>::contains_key(voter_id, candidate) {
+				continue
+			}
+			// Voters are signed once the CA registered their messages
+			let message = match BlindedMessages::<T>::get(voter_id, candidate) {
+				Some(message) => message,
+				None => continue,
+			};
+
+			// Messages that do not fit the candidates key are skipped, the CA registered them wrong
+			let signature = match secret_key.blind_sign(rng, &message, &options) {
+				Ok(signature) => signature,
+				Err(_) => continue,
+			};
+			if let Ok(signature) = BoundedVec::try_from(signature.to_vec()) {
+				let _ = signatures.try_push((voter_id, signature));
+			}
+		}
+
+		signatures
+	}
+}
@@ -0,0 +1,28 @@
+//! Runtime API of the voting system.
+//!
+//! Nodes serve the voting system RPC methods through this API, so every call only reads the
+//! storage it needs instead of the whole voting system storage of a block.
+
+use crate::{BallotInclusion, BallotLogEntry, ElectionSummary};
+use codec::Codec;
+use frame_support::inherent::Vec;
+
+sp_api::decl_runtime_apis! {
+	/// Read-only queries of the voting system
+	pub trait VotingSystemApi<AccountId, Hash, BlockNumber>
+	where
+		AccountId: Codec,
+		Hash: Codec,
+		BlockNumber: Codec,
+	{
+		/// The ballot with `receipt` and the proof of its inclusion in the ballot log, see
+		/// `Pallet::ballot_inclusion`. The proof reads one node per level of the log
+		fn ballot_inclusion(receipt: Hash) -> Option<BallotInclusion<AccountId, Hash, BlockNumber>>;
+
+		/// Results of the election without its ballots, see `Pallet::election_summary`
+		fn election_summary() -> ElectionSummary<AccountId, BlockNumber>;
+
+		/// Up to `BALLOT_LOG_PAGE` entries of the ballot log starting at index `start`
+		fn ballot_log_page(start: u64) -> Vec<BallotLogEntry<AccountId, Hash, BlockNumber>>;
+	}
+}
@@ -1,5 +1,31 @@
-use crate::{mock::*, Ballot, BallotKey, Candidate, ElectionPhase::*, Error, Event, Voter};
-use frame_support::{assert_noop, assert_ok, BoundedVec};
+use crate::{
+	credentials::{self, TranscriptError},
+	migrations::v1::{self, MigrateToV1},
+	mock::*,
+	rsa_key_storage_key, timelock,
+	validation::{CheckVotingCall, VotingCallError, FIRST_BALLOT_PRIORITY},
+	Ballot, BallotKey, BlindSignature, Call, Candidate, Candidates, CandidatesCount,
+	DisputeResolution,
+	ElectionPhase::*,
+	Error, Event, Exclusion, GenesisError, Phase, PhaseChanges, RevotePolicy, RevotingPolicy,
+	Voter, Voters,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{GetStorageVersion, Hooks, OnRuntimeUpgrade, StorageVersion},
+	BoundedVec,
+};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use sp_core::{
+	offchain::{testing, OffchainDbExt, OffchainWorkerExt, StorageKind, TransactionPoolExt},
+	H256,
+};
+use sp_runtime::{
+	testing::UintAuthorityId,
+	traits::{Dispatchable, Hash, SignedExtension},
+	transaction_validity::{InvalidTransaction, TransactionPriority, TransactionValidityError},
+};
 
 #[test]
 fn e2e() {
@@ -16,8 +42,11 @@ fn e2e() {
 		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
 
 		// Registration phase
-		let voter = 1;
+		// The voter is registered under the id of their blinded key and votes from an anonymous
+		// voting account
 		let blinded_pubkey = vec![1, 2, 3];
+		let voter = VotingSystem::voter_id(&blinded_pubkey);
+		let voting_account = 5;
 		let signed_blinded_pubkey = vec![4, 5, 6];
 		let is_eligible = true;
 		let personal_data_hash = vec![7, 8, 9];
@@ -29,60 +58,64 @@ fn e2e() {
 			is_eligible
 		));
 		assert_eq!(
-			VotingSystem::voters(1),
+			VotingSystem::voters(voter),
 			Some(Voter { blinded_pubkey, signed_blinded_pubkey, is_eligible, personal_data_hash })
 		);
 		// Registration -> BiasedSigner
 		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
-
-		for candidate in candidates.iter() {
-			let blinded_signature: BoundedVec<u8, SignatureLength> =
-				BoundedVec::try_from(vec![1, 2, 3]).unwrap();
-
-			assert_ok!(VotingSystem::biased_signing(
-				RuntimeOrigin::signed(*candidate),
-				*candidate,
-				voter,
-				blinded_signature
-			));
-		}
+		sign_voter(ca, voter, voting_account);
 
 		// Biased Signing -> Voting
 		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
 
 		// Voting phase
-		let commitment = vec![1, 2, 3];
-		let signature = vec![4, 5, 6];
+		let commitment = 1;
+		let signature_set: Vec<_> = candidates
+			.iter()
+			.map(|candidate| (*candidate, candidate_signature(*candidate, voting_account)))
+			.collect();
 
 		assert_ok!(VotingSystem::vote(
-			RuntimeOrigin::signed(voter),
-			commitment.clone(),
-			signature.clone()
+			RuntimeOrigin::signed(voting_account),
+			commitment,
+			signature_set.clone()
 		));
 		assert_eq!(
-			VotingSystem::get_ballot(voter),
-			Some(Ballot { commitment, signature, nonce: 1 })
+			VotingSystem::get_ballot(voting_account),
+			Some(Ballot { commitment, signature: signature_set.encode(), nonce: 1 })
 		);
 
-		let new_commitment = vec![1, 2, 3, 4];
-		let new_signature = vec![4, 5, 6, 7];
+		let new_commitment = 2;
 		assert_ok!(VotingSystem::vote(
-			RuntimeOrigin::signed(voter),
-			new_commitment.clone(),
-			new_signature.clone()
+			RuntimeOrigin::signed(voting_account),
+			new_commitment,
+			signature_set.clone()
 		));
 		assert_eq!(
-			VotingSystem::get_ballot(voter),
-			Some(Ballot { commitment: new_commitment, signature: new_signature, nonce: 2 })
+			VotingSystem::get_ballot(voting_account),
+			Some(Ballot {
+				commitment: new_commitment,
+				signature: signature_set.encode(),
+				nonce: 2
+			})
 		);
 		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
 
-		// TODO: Counting phase
-		assert_ok!(VotingSystem::reveal_ballot_key(RuntimeOrigin::signed(ca), vec![1, 2, 3]));
+		// Counting phase
+		assert_ok!(VotingSystem::reveal_ballot_key(
+			RuntimeOrigin::signed(ca),
+			test_secret_key(BALLOT_KEY)
+		));
 		assert_eq!(
 			VotingSystem::get_ballot_key(),
-			Some(BallotKey { public: vec![1, 2, 3], private: vec![1, 2, 3] })
-		)
+			Some(BallotKey {
+				public: test_public_key(BALLOT_KEY),
+				private: test_secret_key(BALLOT_KEY)
+			})
+		);
+		assert_ok!(VotingSystem::tally_step(RuntimeOrigin::signed(ca), 10));
+		assert_eq!(VotingSystem::get_victor_count(new_commitment), Some(1));
+		assert_eq!(VotingSystem::get_winner(), Some(new_commitment));
 	})
 }
 
@@ -99,6 +132,8 @@ fn change_phase_works() {
 
 		// then
 		assert_eq!(VotingSystem::phase(), Some(Registration));
+		assert_eq!(VotingSystem::phase_change(Initialization), Some(0));
+		assert_eq!(VotingSystem::phase_change(Registration), Some(1));
 		System::assert_last_event(Event::PhaseChanged { phase: Registration, when: 1 }.into());
 	});
 }
@@ -127,8 +162,8 @@ fn can_add_voter() {
 	new_test_ext(root_key).execute_with(|| {
 		// with
 		let ca = root_key;
-		let voter = 1;
 		let blinded_pubkey = vec![1, 2, 3];
+		let voter = VotingSystem::voter_id(&blinded_pubkey);
 		let signed_blinded_pubkey = vec![4, 5, 6];
 		let is_eligible = true;
 		let personal_data_hash = vec![7, 8, 9];
@@ -152,6 +187,81 @@ fn can_add_voter() {
 	})
 }
 
+#[test]
+fn can_add_voters_in_batch() {
+	let root_key = 1;
+	new_test_ext(root_key).execute_with(|| {
+		// with
+		let ca = root_key;
+		let voter = |blinded_pubkey: Vec<u8>| Voter {
+			blinded_pubkey,
+			signed_blinded_pubkey: vec![4, 5, 6],
+			is_eligible: true,
+			personal_data_hash: vec![7, 8, 9],
+		};
+		let (first, second) = (voter(vec![1, 2, 3]), voter(vec![3, 2, 1]));
+		let voters: BoundedVec<Voter, MaxBatchSize> =
+			BoundedVec::try_from(vec![first.clone(), second.clone(), first.clone()]).unwrap();
+
+		// when
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_ok!(VotingSystem::add_voters(RuntimeOrigin::signed(ca), voters));
+
+		// then
+		// Voters are stored under the id of their blinded key, which can only be registered once
+		let first_id = VotingSystem::voter_id(&first.blinded_pubkey);
+		let second_id = VotingSystem::voter_id(&second.blinded_pubkey);
+		assert_eq!(Voters::<Test>::count(), 2);
+		assert_eq!(VotingSystem::voters(first_id), Some(first));
+		assert_eq!(VotingSystem::voters(second_id), Some(second));
+		System::assert_last_event(
+			Event::VotersAdded {
+				voters: vec![first_id, second_id],
+				failed: vec![(2, Error::<Test>::VoterAlreadyExists.into())],
+			}
+			.into(),
+		);
+	})
+}
+
+#[test]
+fn add_voters_errors_when_not_ca() {
+	let root_key = 1;
+	new_test_ext(root_key).execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(root_key)));
+
+		assert_noop!(
+			VotingSystem::add_voters(RuntimeOrigin::signed(2), BoundedVec::default()),
+			Error::<Test>::SenderNotCA
+		);
+	})
+}
+
+#[test]
+fn biased_signing_batch_errors_with_invalid_candidate_key() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates).execute_with(|| {
+		let candidate = 2;
+		let blinded_signature: BoundedVec<u8, SignatureLength> =
+			BoundedVec::try_from(vec![1, 2, 3]).unwrap();
+		let voter = VotingSystem::voter_id(&get_default_blinded_pubkey());
+		let signatures = BoundedVec::try_from(vec![(voter, blinded_signature)]).unwrap();
+
+		System::set_block_number(1);
+		assert_noop!(
+			VotingSystem::biased_signing_batch(
+				RuntimeOrigin::signed(candidate),
+				candidate,
+				signatures
+			),
+			Error::<Test>::InvalidPublicKey
+		);
+	})
+}
+
 #[test]
 fn can_update_candidate() {
 	let root_key = 1;
@@ -159,7 +269,7 @@ fn can_update_candidate() {
 		// with
 		let candidate = 2;
 		let name = "candidate 1";
-		let pubkey: Vec<u8> = vec![1, 2, 3];
+		let pubkey: Vec<u8> = test_public_key(11);
 
 		// when
 		System::set_block_number(1);
@@ -172,160 +282,1857 @@ fn can_update_candidate() {
 
 		// then
 		assert_eq!(
-			VotingSystem::get_candidate(candidate),
-			Some(Candidate { name: name.to_string(), pubkey })
+			VotingSystem::get_candidate(candidate),
+			Some(Candidate { name: name.to_string(), pubkey })
+		);
+	})
+}
+
+#[test]
+fn update_candidate_errors_with_invalid_key() {
+	let root_key = 1;
+	new_test_ext(root_key).execute_with(|| {
+		let candidate = 2;
+
+		System::set_block_number(1);
+		assert_noop!(
+			VotingSystem::update_candidate_info(
+				RuntimeOrigin::signed(candidate),
+				candidate,
+				"candidate 2".to_string(),
+				vec![1, 2, 3]
+			),
+			Error::<Test>::InvalidPublicKey
+		);
+	})
+}
+
+#[test]
+fn update_candidate_errors_with_duplicate_key() {
+	let root_key = 1;
+	new_test_ext(root_key).execute_with(|| {
+		let candidate = 2;
+
+		System::set_block_number(1);
+		// Candidate 3 already uses key 3
+		assert_noop!(
+			VotingSystem::update_candidate_info(
+				RuntimeOrigin::signed(candidate),
+				candidate,
+				"candidate 2".to_string(),
+				test_public_key(3)
+			),
+			Error::<Test>::DuplicatePublicKey
+		);
+		// Keeping its own key is fine
+		assert_ok!(VotingSystem::update_candidate_info(
+			RuntimeOrigin::signed(candidate),
+			candidate,
+			"candidate 2".to_string(),
+			test_public_key(2)
+		));
+	})
+}
+
+#[test]
+fn nominate_candidate_errors_with_duplicate_key() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates).execute_with(|| {
+		System::set_block_number(1);
+		assert_noop!(
+			VotingSystem::nominate_candidate(
+				RuntimeOrigin::signed(4),
+				"candidate 4".to_string(),
+				test_public_key(1)
+			),
+			Error::<Test>::DuplicatePublicKey
+		);
+	})
+}
+
+#[test]
+fn genesis_stores_the_candidate_names() {
+	let root_key = 1;
+	new_test_ext_w_candidate(root_key, vec![1, 2]).execute_with(|| {
+		assert_eq!(VotingSystem::candidates(1).unwrap().name, "Candidate 1");
+		assert_eq!(VotingSystem::candidates(2).unwrap().name, "Candidate 2");
+	})
+}
+
+#[test]
+fn genesis_validation_names_the_rejected_candidate() {
+	let config = crate::GenesisConfig::<Test> {
+		central_authority: Some(1),
+		candidates: vec![test_candidate(1), test_candidate(2), test_candidate(1)],
+		ballot_public_key: test_public_key(BALLOT_KEY),
+		revote_policy: Default::default(),
+	};
+
+	assert_eq!(config.validate(), Err(GenesisError::DuplicateCandidate("Candidate 1".into())));
+	assert_eq!(
+		crate::GenesisConfig::<Test> { candidates: vec![test_candidate(1)], ..config }.validate(),
+		Err(GenesisError::TooFewCandidates)
+	);
+}
+
+#[test]
+#[should_panic(expected = "reuses the public key")]
+fn genesis_panics_with_duplicate_candidate_keys() {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	crate::GenesisConfig::<Test> {
+		central_authority: Some(1),
+		candidates: vec![test_candidate(1), (2, "Candidate 2".into(), test_public_key(1))],
+		ballot_public_key: test_public_key(BALLOT_KEY),
+		revote_policy: Default::default(),
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+}
+
+#[test]
+#[should_panic(expected = "is listed twice")]
+fn genesis_panics_with_duplicate_candidate_accounts() {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	crate::GenesisConfig::<Test> {
+		central_authority: Some(1),
+		candidates: vec![
+			test_candidate(1),
+			test_candidate(2),
+			(1, "Candidate 3".into(), test_public_key(3)),
+		],
+		ballot_public_key: test_public_key(BALLOT_KEY),
+		revote_policy: Default::default(),
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Invalid public key")]
+fn genesis_panics_with_invalid_candidate_key() {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	crate::GenesisConfig::<Test> {
+		central_authority: Some(1),
+		candidates: vec![test_candidate(1), (2, "Candidate 2".into(), vec![1, 2, 3])],
+		ballot_public_key: test_public_key(BALLOT_KEY),
+		revote_policy: Default::default(),
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+}
+
+#[test]
+fn update_candidate_errors_when_not_candidate() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates).execute_with(|| {
+		let account = 7;
+
+		System::set_block_number(1);
+		assert_noop!(
+			VotingSystem::update_candidate_info(
+				RuntimeOrigin::signed(account),
+				account,
+				"not a candidate".to_string(),
+				vec![1, 2, 3]
+			),
+			Error::<Test>::CandidateDoesNotExist
+		);
+		assert_eq!(VotingSystem::candidates_count(), Some(3));
+	})
+}
+
+#[test]
+fn can_nominate_and_approve_candidate() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates).execute_with(|| {
+		// with
+		let ca = root_key;
+		let nominee = 4;
+		let name = "candidate 4";
+		let pubkey: Vec<u8> = test_public_key(4);
+
+		// when
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::nominate_candidate(
+			RuntimeOrigin::signed(nominee),
+			name.to_string(),
+			pubkey.clone()
+		));
+		assert_eq!(VotingSystem::get_candidate(nominee), None);
+		assert_ok!(VotingSystem::approve_candidate(RuntimeOrigin::signed(ca), nominee));
+
+		// then
+		assert_eq!(
+			VotingSystem::get_candidate(nominee),
+			Some(Candidate { name: name.to_string(), pubkey })
+		);
+		assert_eq!(VotingSystem::candidate_nominations(nominee), None);
+		assert_eq!(VotingSystem::candidates_count(), Some(4));
+		System::assert_last_event(Event::CandidateApproved { candidate: nominee }.into());
+	})
+}
+
+#[test]
+fn approve_candidate_errors_when_not_ca() {
+	let root_key = 1;
+	new_test_ext(root_key).execute_with(|| {
+		let nominee = 11;
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::nominate_candidate(
+			RuntimeOrigin::signed(nominee),
+			"candidate 11".to_string(),
+			test_public_key(11)
+		));
+
+		assert_noop!(
+			VotingSystem::approve_candidate(RuntimeOrigin::signed(nominee), nominee),
+			Error::<Test>::SenderNotCA
+		);
+	})
+}
+
+#[test]
+fn can_withdraw_candidate() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates).execute_with(|| {
+		let candidate = 3;
+
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::withdraw_candidate(RuntimeOrigin::signed(candidate)));
+
+		assert_eq!(VotingSystem::get_candidate(candidate), None);
+		assert_eq!(VotingSystem::candidates_count(), Some(2));
+		System::assert_last_event(Event::CandidateWithdrawn { candidate }.into());
+	})
+}
+
+#[test]
+fn withdraw_candidate_errors_after_registration() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates).execute_with(|| {
+		let ca = root_key;
+		System::set_block_number(1);
+		// Initialization => Registration => BiasedSigning
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+
+		assert_noop!(
+			VotingSystem::withdraw_candidate(RuntimeOrigin::signed(3)),
+			Error::<Test>::CandidateInfoLocked
+		);
+	})
+}
+
+#[test]
+fn change_phase_errors_when_not_enough_candidates() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2];
+	new_test_ext_w_candidate(root_key, candidates).execute_with(|| {
+		let ca = root_key;
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_ok!(VotingSystem::withdraw_candidate(RuntimeOrigin::signed(2)));
+
+		// Registration => BiasedSigning
+		assert_noop!(
+			VotingSystem::change_phase(RuntimeOrigin::signed(ca)),
+			Error::<Test>::NotEnoughCandidates
+		);
+	})
+}
+
+#[test]
+fn update_candidate_errors_after_registration() {
+	let root_key = 1;
+	new_test_ext(root_key).execute_with(|| {
+		// with
+		let ca = root_key;
+		let candidate = 2;
+
+		// when
+		System::set_block_number(1);
+		// Initialization => Registration => BiasedSigning
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+
+		// then
+		assert_noop!(
+			VotingSystem::update_candidate_info(
+				RuntimeOrigin::signed(candidate),
+				candidate,
+				"candidate 2".to_string(),
+				vec![4, 5, 6]
+			),
+			Error::<Test>::CandidateInfoLocked
+		);
+	})
+}
+
+#[test]
+fn candidate_keys_are_committed_when_biased_signing_starts() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		let ca = root_key;
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_eq!(VotingSystem::candidate_key_fingerprint(1), None);
+
+		// Registration => BiasedSigning
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+
+		for candidate in candidates {
+			let pubkey = VotingSystem::get_candidate(candidate).unwrap().pubkey;
+			assert_eq!(
+				VotingSystem::candidate_key_fingerprint(candidate),
+				Some(<Test as frame_system::Config>::Hashing::hash(&pubkey))
+			);
+		}
+	})
+}
+
+#[test]
+fn can_vote() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let voter = 5;
+		let ca = 1;
+		let blinded_pubkey = vec![1, 2, 3];
+		let signed_blinded_pubkey = vec![4, 5, 6];
+		let is_eligible = true;
+		let personal_data_hash = vec![7, 8, 9];
+		let commitment = 1;
+		let signature_set: Vec<_> = candidates
+			.iter()
+			.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+			.collect();
+
+		// when
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_ok!(VotingSystem::add_voter(
+			RuntimeOrigin::signed(ca),
+			blinded_pubkey,
+			signed_blinded_pubkey,
+			personal_data_hash,
+			is_eligible,
+		));
+
+		// Registration => BiasedSigning
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		sign_voter(ca, VotingSystem::voter_id(&[1, 2, 3]), voter);
+
+		// BiasedSigning => Voting
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+
+		// then vote
+		assert_ok!(VotingSystem::vote(
+			RuntimeOrigin::signed(voter),
+			commitment,
+			signature_set.clone()
+		));
+		assert_eq!(
+			VotingSystem::get_ballot(voter),
+			Some(Ballot { commitment, signature: signature_set.encode(), nonce: 1 })
+		);
+	})
+}
+
+#[test]
+pub fn can_change_vote() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let voter = 5;
+		let ca = 1;
+		let blinded_pubkey = vec![1, 2, 3];
+		let signed_blinded_pubkey = vec![4, 5, 6];
+		let is_eligible = true;
+		let commitment = 1;
+		let signature_set: Vec<_> = candidates
+			.iter()
+			.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+			.collect();
+		let personal_data_hash = vec![7, 8, 9];
+
+		// when
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_ok!(VotingSystem::add_voter(
+			RuntimeOrigin::signed(ca),
+			blinded_pubkey,
+			signed_blinded_pubkey,
+			personal_data_hash,
+			is_eligible
+		));
+		// Registration => BiasedSigning
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		sign_voter(ca, VotingSystem::voter_id(&[1, 2, 3]), voter);
+
+		// BiasedSigning => Voting
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_ok!(VotingSystem::vote(
+			RuntimeOrigin::signed(voter),
+			commitment,
+			signature_set.clone()
+		));
+		assert_eq!(
+			VotingSystem::get_ballot(voter),
+			Some(Ballot { commitment, signature: signature_set.encode(), nonce: 1 })
+		);
+
+		// then change vote
+		let new_commitment = 2;
+		assert_ok!(VotingSystem::vote(
+			RuntimeOrigin::signed(voter),
+			new_commitment,
+			signature_set.clone()
+		));
+		assert_eq!(
+			VotingSystem::get_ballot(voter),
+			Some(Ballot {
+				commitment: new_commitment,
+				signature: signature_set.encode(),
+				nonce: 2
+			})
+		);
+	})
+}
+
+#[test]
+fn can_biased_signing() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates).execute_with(|| {
+		// with
+		let ca = root_key;
+		let candidate = 2;
+		let voter = VotingSystem::voter_id(&get_default_blinded_pubkey());
+
+		// when
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_ok!(VotingSystem::add_voter(
+			RuntimeOrigin::signed(ca),
+			get_default_blinded_pubkey(),
+			vec![4, 5, 6],
+			vec![7, 8, 9],
+			true
+		));
+		// Registration => BiasedSigning
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+
+		// then
+		// Nothing can be signed before the blinded messages are registered
+		let message = blind(candidate, 5).blind_msg.to_vec();
+		assert_noop!(
+			VotingSystem::biased_signing(
+				RuntimeOrigin::signed(candidate),
+				candidate,
+				voter,
+				blind_sign(candidate, &message)
+			),
+			Error::<Test>::BlindedMessageNotFound
+		);
+		let blindings = register_blinded_messages(ca, voter, 5);
+		let message = blinded_message_of(&blindings, candidate);
+
+		let bad_signature: BoundedVec<u8, SignatureLength> =
+			BoundedVec::try_from(vec![1, 2, 3]).unwrap();
+		assert_noop!(
+			VotingSystem::biased_signing(
+				RuntimeOrigin::signed(candidate),
+				candidate,
+				voter,
+				bad_signature
+			),
+			Error::<Test>::RSAInvalidSignature
+		);
+
+		// The signature of another candidate does not verify with the candidates key
+		assert_noop!(
+			VotingSystem::biased_signing(
+				RuntimeOrigin::signed(candidate),
+				candidate,
+				voter,
+				blind_sign(1, &blinded_message_of(&blindings, 1))
+			),
+			Error::<Test>::RSAInvalidSignature
+		);
+
+		let blinded_signature = blind_sign(candidate, &message);
+		assert_ok!(VotingSystem::biased_signing(
+			RuntimeOrigin::signed(candidate),
+			candidate,
+			voter,
+			blinded_signature.clone()
+		));
+		assert_eq!(VotingSystem::blinded_signatures(voter, candidate), Some(blinded_signature));
+		assert_eq!(VotingSystem::signed_voter_count(candidate), 1);
+
+		// Signing a voter again replaces the signature, the voter still counts once
+		assert_ok!(VotingSystem::biased_signing(
+			RuntimeOrigin::signed(candidate),
+			candidate,
+			voter,
+			blind_sign(candidate, &message)
+		));
+		assert_eq!(VotingSystem::signed_voter_count(candidate), 1);
+
+		// The other candidates did not sign the voter yet
+		assert_noop!(
+			VotingSystem::change_phase(RuntimeOrigin::signed(ca)),
+			Error::<Test>::InvalidPhaseChange
+		);
+	})
+}
+
+#[test]
+fn voters_vote_with_the_unblinded_stored_signatures() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let ca = root_key;
+		let voter = 5;
+		let voter_id = VotingSystem::voter_id(&get_default_blinded_pubkey());
+		let options = blind_rsa_signatures::Options::default();
+
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_ok!(VotingSystem::add_voter(
+			RuntimeOrigin::signed(ca),
+			get_default_blinded_pubkey(),
+			vec![],
+			vec![],
+			true
+		));
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+
+		// when
+		// The messages are registered once, with exactly one message for every candidate
+		let blindings: Vec<_> = candidates
+			.iter()
+			.map(|candidate| (*candidate, blind(*candidate, voter)))
+			.collect();
+		let messages = |candidates: &[u64]| -> BoundedVec<_, MaxBatchSize> {
+			let messages: Vec<_> = candidates
+				.iter()
+				.map(|candidate| {
+					let message = blinded_message_of(&blindings, *candidate);
+					(*candidate, BoundedVec::try_from(message).unwrap())
+				})
+				.collect();
+			BoundedVec::try_from(messages).unwrap()
+		};
+		assert_noop!(
+			VotingSystem::register_blinded_messages(
+				RuntimeOrigin::signed(2),
+				voter_id,
+				messages(&candidates)
+			),
+			Error::<Test>::SenderNotCA
+		);
+		assert_noop!(
+			VotingSystem::register_blinded_messages(
+				RuntimeOrigin::signed(ca),
+				voter_id,
+				messages(&[1, 2])
+			),
+			Error::<Test>::InvalidBlindedMessages
+		);
+		assert_noop!(
+			VotingSystem::register_blinded_messages(
+				RuntimeOrigin::signed(ca),
+				voter_id,
+				messages(&[1, 2, 2])
+			),
+			Error::<Test>::InvalidBlindedMessages
+		);
+		assert_ok!(VotingSystem::register_blinded_messages(
+			RuntimeOrigin::signed(ca),
+			voter_id,
+			messages(&candidates)
+		));
+		assert_noop!(
+			VotingSystem::register_blinded_messages(
+				RuntimeOrigin::signed(ca),
+				voter_id,
+				messages(&candidates)
+			),
+			Error::<Test>::BlindedMessagesAlreadyRegistered
+		);
+		assert_eq!(VotingSystem::blinded_message_count(), 1);
+
+		// Every candidate signs the message blinded for their key
+		for candidate in candidates.iter() {
+			let message = VotingSystem::blinded_message(voter_id, candidate).unwrap();
+			assert_ok!(VotingSystem::biased_signing(
+				RuntimeOrigin::signed(*candidate),
+				*candidate,
+				voter_id,
+				blind_sign(*candidate, &message)
+			));
+		}
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+
+		// The voter unblinds the signatures stored on chain
+		let signature_set: Vec<_> = blindings
+			.iter()
+			.map(|(candidate, blinding)| {
+				let public_key =
+					blind_rsa_signatures::PublicKey::from_der(&test_public_key(*candidate))
+						.unwrap();
+				let stored = VotingSystem::blinded_signatures(voter_id, candidate).unwrap();
+				let msg_randomizer = blinding.msg_randomizer.as_ref().unwrap().0;
+				let signature = public_key
+					.finalize(
+						&blind_rsa_signatures::BlindSignature::new(stored.to_vec()),
+						&blinding.secret,
+						Some(blind_rsa_signatures::MessageRandomizer::from(msg_randomizer)),
+						&voter.encode(),
+						&options,
+					)
+					.unwrap();
+				(*candidate, BlindSignature { signature: signature.to_vec(), msg_randomizer })
+			})
+			.collect();
+
+		// then
+		assert_ok!(VotingSystem::vote(RuntimeOrigin::signed(voter), 2, signature_set.clone()));
+		assert_eq!(
+			VotingSystem::get_ballot(voter),
+			Some(Ballot { commitment: 2, signature: signature_set.encode(), nonce: 1 })
+		);
+	})
+}
+
+#[test]
+fn offchain_worker_signs_pending_voters() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	let (offchain, _offchain_state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut ext = new_test_ext_w_candidate(root_key, candidates);
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+
+	ext.execute_with(|| {
+		// with
+		let ca = root_key;
+		let candidate = 2;
+		let voter = VotingSystem::voter_id(&get_default_blinded_pubkey());
+
+		// The node holds the account and RSA key of candidate 2
+		UintAuthorityId::set_all_keys(vec![candidate]);
+		sp_io::offchain::local_storage_set(
+			StorageKind::PERSISTENT,
+			&rsa_key_storage_key(&candidate),
+			&test_secret_key(2).encode(),
+		);
+
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_ok!(VotingSystem::add_voter(
+			RuntimeOrigin::signed(ca),
+			get_default_blinded_pubkey(),
+			vec![4, 5, 6],
+			vec![7, 8, 9],
+			true
+		));
+
+		// Nothing is signed outside of the biased signer phase
+		VotingSystem::offchain_worker(1);
+		assert!(pool_state.read().transactions.is_empty());
+
+		// Registration => BiasedSigning, the voter has nothing to sign until their messages are
+		// registered
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		VotingSystem::offchain_worker(1);
+		assert!(pool_state.read().transactions.is_empty());
+		let blindings = register_blinded_messages(ca, voter, 5);
+
+		// when
+		VotingSystem::offchain_worker(1);
+
+		// then
+		let tx = pool_state.write().transactions.pop().unwrap();
+		assert!(pool_state.read().transactions.is_empty());
+		let tx = Extrinsic::decode(&mut &*tx).unwrap();
+		assert_eq!(tx.signature, Some((0, ())));
+		let signatures = match tx.call {
+			RuntimeCall::VotingSystem(crate::Call::biased_signing_batch {
+				candidate: 2,
+				signatures,
+			}) => signatures,
+			call => panic!("Unexpected call {:?}", call),
+		};
+		assert_eq!(signatures.len(), 1);
+		// The worker signed the message blinded for the candidates key
+		let public_key =
+			blind_rsa_signatures::PublicKey::from_der(&test_public_key(candidate)).unwrap();
+		assert!(VotingSystem::verify_blind_signature(
+			&public_key,
+			&blinded_message_of(&blindings, candidate),
+			&signatures[0].1
+		));
+		assert_ok!(VotingSystem::biased_signing_batch(
+			RuntimeOrigin::signed(candidate),
+			candidate,
+			signatures
+		));
+		assert!(VotingSystem::blinded_signatures(voter, candidate).is_some());
+	})
+}
+
+#[test]
+fn vote_keeps_signatures_for_audit() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let voter = 5;
+		let signature_set: Vec<_> = candidates
+			.iter()
+			.rev()
+			.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+			.collect();
+
+		// when
+		System::set_block_number(1);
+		Phase::<Test>::put(Voting);
+		assert_noop!(
+			VotingSystem::vote(
+				RuntimeOrigin::signed(voter),
+				2,
+				vec![signature_set[0].clone(), signature_set[0].clone(), signature_set[1].clone()]
+			),
+			Error::<Test>::InvalidBlindSignatures
+		);
+		assert_ok!(VotingSystem::vote(RuntimeOrigin::signed(voter), 2, signature_set.clone()));
+
+		// then
+		let mut sorted_set = signature_set;
+		sorted_set.sort_by_key(|(candidate, _)| *candidate);
+		let ballot = VotingSystem::get_ballot(voter).unwrap();
+		assert_eq!(ballot, Ballot { commitment: 2, signature: sorted_set.encode(), nonce: 1 });
+		assert_ok!(VotingSystem::verify_ballot(&voter, &ballot));
+
+		// a ballot moved to another voter no longer verifies
+		assert_noop!(
+			VotingSystem::verify_ballot(&6, &ballot),
+			Error::<Test>::InvalidBlindSignatures
+		);
+	})
+}
+
+#[test]
+fn revote_policy_limits_ballot_replacements() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let ca = root_key;
+		let voter = 5;
+		let signature_set = || -> Vec<_> {
+			candidates
+				.iter()
+				.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+				.collect()
+		};
+
+		// when
+		System::set_block_number(1);
+		assert_noop!(
+			VotingSystem::set_revote_policy(RuntimeOrigin::signed(2), RevotePolicy::SingleVote),
+			Error::<Test>::SenderNotCA
+		);
+		assert_ok!(VotingSystem::set_revote_policy(
+			RuntimeOrigin::signed(ca),
+			RevotePolicy::MaxRevotes(1)
+		));
+		System::assert_last_event(
+			Event::RevotePolicySet { policy: RevotePolicy::MaxRevotes(1) }.into(),
+		);
+		Phase::<Test>::put(Voting);
+		assert_noop!(
+			VotingSystem::set_revote_policy(RuntimeOrigin::signed(ca), RevotePolicy::SingleVote),
+			Error::<Test>::InvalidPhase
+		);
+
+		// then
+		assert_ok!(VotingSystem::vote(RuntimeOrigin::signed(voter), 1, signature_set()));
+		let receipt =
+			VotingSystem::ballot_receipt(&voter, &VotingSystem::ballots(voter).unwrap(), 0);
+		System::assert_last_event(Event::BallotCast { voter, receipt }.into());
+
+		assert_ok!(VotingSystem::vote(RuntimeOrigin::signed(voter), 2, signature_set()));
+		let ballot = VotingSystem::ballots(voter).unwrap();
+		assert_eq!((ballot.commitment, ballot.nonce), (2, 2));
+		let receipt = VotingSystem::ballot_receipt(&voter, &ballot, 1);
+		System::assert_last_event(Event::BallotReplaced { voter, receipt, nonce: 2 }.into());
+
+		assert_noop!(
+			VotingSystem::vote(RuntimeOrigin::signed(voter), 3, signature_set()),
+			Error::<Test>::RevoteLimitReached
+		);
+	})
+}
+
+#[test]
+fn single_vote_policy_rejects_second_ballot() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let voter = 5;
+		let signature_set: Vec<_> = candidates
+			.iter()
+			.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+			.collect();
+
+		// when
+		assert_ok!(VotingSystem::set_revote_policy(
+			RuntimeOrigin::signed(root_key),
+			RevotePolicy::SingleVote
+		));
+		Phase::<Test>::put(Voting);
+		assert_ok!(VotingSystem::vote(RuntimeOrigin::signed(voter), 1, signature_set.clone()));
+
+		// then
+		assert_noop!(
+			VotingSystem::vote(RuntimeOrigin::signed(voter), 2, signature_set),
+			Error::<Test>::AlreadyVoted
+		);
+	})
+}
+
+#[test]
+fn election_duties_are_fee_exempt() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let ca = root_key;
+		let voter = 5;
+		let add_voter = Call::<Test>::add_voter {
+			blinded_pubkey: get_default_blinded_pubkey(),
+			signed_blinded_pubkey: vec![],
+			personal_data_hash: vec![],
+			is_eligible: true,
+		};
+		let register_blinded_messages = Call::<Test>::register_blinded_messages {
+			voter: VotingSystem::voter_id(&get_default_blinded_pubkey()),
+			messages: BoundedVec::default(),
+		};
+		let biased_signing = Call::<Test>::biased_signing {
+			candidate: 2,
+			voter: VotingSystem::voter_id(&get_default_blinded_pubkey()),
+			blinded_signature: BoundedVec::default(),
+		};
+		let signature_set: Vec<_> = candidates
+			.iter()
+			.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+			.collect();
+		let vote = Call::<Test>::vote { commitment: 1, signature_set: signature_set.clone() };
+		let mut forged_set = signature_set.clone();
+		forged_set[0].1 = candidate_signature(1, voter + 1);
+		let forged_vote = Call::<Test>::vote { commitment: 1, signature_set: forged_set };
+		let incomplete_vote =
+			Call::<Test>::vote { commitment: 1, signature_set: signature_set[1..].to_vec() };
+
+		// then
+		// Only the CA registers voters for free, and only during registration
+		assert!(!VotingSystem::is_fee_exempt(&ca, &add_voter));
+		Phase::<Test>::put(Registration);
+		assert!(VotingSystem::is_fee_exempt(&ca, &add_voter));
+		assert!(!VotingSystem::is_fee_exempt(&2, &add_voter));
+		assert!(!VotingSystem::is_fee_exempt(&ca, &Call::<Test>::change_phase {}));
+
+		// Candidates only sign for free as themselves, the CA registers their messages for free
+		Phase::<Test>::put(BiasedSigner);
+		assert!(VotingSystem::is_fee_exempt(&ca, &register_blinded_messages));
+		assert!(!VotingSystem::is_fee_exempt(&2, &register_blinded_messages));
+		assert!(VotingSystem::is_fee_exempt(&2, &biased_signing));
+		assert!(!VotingSystem::is_fee_exempt(&3, &biased_signing));
+
+		// Voters only vote for free with a well formed signature set the revote policy accepts,
+		// a forged set never gets to the fee as `CheckVotingCall` verifies the signatures first
+		Phase::<Test>::put(Voting);
+		assert!(VotingSystem::is_fee_exempt(&voter, &vote));
+		assert!(!VotingSystem::is_fee_exempt(&voter, &incomplete_vote));
+		assert!(CheckVotingCall::<Test>::new()
+			.pre_dispatch(&voter, &forged_vote.into(), &Default::default(), 0)
+			.is_err());
+		RevotingPolicy::<Test>::put(RevotePolicy::SingleVote);
+		VotingSystem::note_free_ballot(&voter, &vote);
+		assert_ok!(VotingSystem::vote(RuntimeOrigin::signed(voter), 1, signature_set.clone()));
+		assert!(!VotingSystem::is_fee_exempt(&voter, &vote));
+
+		// The first replacement is free, any further one is charged
+		RevotingPolicy::<Test>::put(RevotePolicy::LastVoteCounts);
+		assert!(VotingSystem::is_fee_exempt(&voter, &vote));
+		VotingSystem::note_free_ballot(&voter, &vote);
+		assert_ok!(VotingSystem::vote(RuntimeOrigin::signed(voter), 2, signature_set.clone()));
+		assert_eq!(VotingSystem::free_ballots(voter), 2);
+		assert!(!VotingSystem::is_fee_exempt(&voter, &vote));
+		assert_ok!(VotingSystem::vote(RuntimeOrigin::signed(voter), 1, signature_set));
+
+		// Free ballots are counted when the fee is waived, a ballot failing on dispatch counts too
+		let other_voter = 6;
+		assert!(VotingSystem::is_fee_exempt(&other_voter, &vote));
+		VotingSystem::note_free_ballot(&other_voter, &vote);
+		VotingSystem::note_free_ballot(&other_voter, &vote);
+		assert!(!VotingSystem::is_fee_exempt(&other_voter, &vote));
+		VotingSystem::note_free_ballot(&ca, &add_voter);
+		assert_eq!(VotingSystem::free_ballots(ca), 0);
+	})
+}
+
+#[test]
+fn check_voting_call_rejects_calls_before_the_pool() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let ca = root_key;
+		let voter = 5;
+		let check = |who: u64, call: Call<Test>| {
+			CheckVotingCall::<Test>::new()
+				.validate(&who, &call.into(), &Default::default(), 0)
+				.map(|valid| valid.priority)
+		};
+		let rejected = |error: VotingCallError| -> Result<TransactionPriority, _> {
+			Err(TransactionValidityError::from(InvalidTransaction::from(error)))
+		};
+		let signature_set: Vec<_> = candidates
+			.iter()
+			.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+			.collect();
+		let vote = |signature_set| Call::<Test>::vote { commitment: 1, signature_set };
+		let update_info = |pubkey| Call::<Test>::update_candidate_info {
+			candidate: 2,
+			name: "Bob".into(),
+			pubkey,
+		};
+
+		// then
+		// Only the CA advances the phases
+		assert_eq!(
+			check(2, Call::<Test>::change_phase {}),
+			rejected(VotingCallError::NotAuthorized)
+		);
+		assert_ok!(check(ca, Call::<Test>::change_phase {}));
+
+		// Candidates update their own info with keys of a sensible size
+		assert_ok!(check(2, update_info(test_public_key(2))));
+		assert_eq!(
+			check(3, update_info(test_public_key(2))),
+			rejected(VotingCallError::NotAuthorized)
+		);
+		assert_eq!(
+			check(2, update_info(vec![0; 2048])),
+			rejected(VotingCallError::PayloadTooLarge)
+		);
+
+		// Ballots are only accepted during voting
+		assert_eq!(
+			check(voter, vote(signature_set.clone())),
+			rejected(VotingCallError::WrongPhase)
+		);
+		Phase::<Test>::put(Voting);
+		assert_eq!(
+			check(2, update_info(test_public_key(2))),
+			rejected(VotingCallError::WrongPhase)
+		);
+
+		// A signature set needs one valid signature of every candidate
+		assert_eq!(check(voter, vote(signature_set.clone())), Ok(FIRST_BALLOT_PRIORITY));
+		let mut forged_set = signature_set.clone();
+		forged_set[0].1 = candidate_signature(1, voter + 1);
+		assert_eq!(check(voter, vote(forged_set)), rejected(VotingCallError::InvalidSignatureSet));
+		assert_eq!(
+			check(voter, vote(signature_set[..2].to_vec())),
+			rejected(VotingCallError::InvalidSignatureSet)
+		);
+		let mut duplicate_set = signature_set.clone();
+		duplicate_set[1] = duplicate_set[0].clone();
+		assert_eq!(
+			check(voter, vote(duplicate_set)),
+			rejected(VotingCallError::InvalidSignatureSet)
+		);
+		let mut oversized_set = signature_set.clone();
+		oversized_set[0].1.signature = vec![0; 512];
+		assert_eq!(
+			check(voter, vote(oversized_set)),
+			rejected(VotingCallError::InvalidSignatureSet)
+		);
+
+		// Replacing a ballot has a lower priority, unless the revote policy forbids it
+		assert_ok!(VotingSystem::vote(RuntimeOrigin::signed(voter), 1, signature_set.clone()));
+		assert_eq!(check(voter, vote(signature_set.clone())), Ok(0));
+		RevotingPolicy::<Test>::put(RevotePolicy::SingleVote);
+		assert_eq!(check(voter, vote(signature_set)), rejected(VotingCallError::AlreadyVoted));
+	})
+}
+
+#[test]
+fn call_filter_follows_the_phase_and_pauses() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let ca = root_key;
+		let voter = 5;
+		let signature_set: Vec<_> = candidates
+			.iter()
+			.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+			.collect();
+		let vote = RuntimeCall::VotingSystem(Call::vote { commitment: 1, signature_set });
+		let add_voter = RuntimeCall::VotingSystem(Call::add_voter {
+			blinded_pubkey: get_default_blinded_pubkey(),
+			signed_blinded_pubkey: vec![],
+			personal_data_hash: vec![],
+			is_eligible: true,
+		});
+		let change_phase = RuntimeCall::VotingSystem(Call::change_phase {});
+		System::set_block_number(1);
+
+		// then
+		// During voting the phase change is the only administrative call
+		Phase::<Test>::put(Voting);
+		assert_noop!(
+			add_voter.dispatch(RuntimeOrigin::signed(ca)),
+			frame_system::Error::<Test>::CallFiltered
+		);
+
+		// Only the CA or root pause the election, which freezes every call but unpause
+		assert_noop!(VotingSystem::pause(RuntimeOrigin::signed(voter)), Error::<Test>::SenderNotCA);
+		assert_ok!(VotingSystem::pause(RuntimeOrigin::signed(ca)));
+		assert_noop!(VotingSystem::pause(RuntimeOrigin::root()), Error::<Test>::AlreadyPaused);
+		System::assert_last_event(Event::ElectionPaused.into());
+		assert_noop!(
+			vote.clone().dispatch(RuntimeOrigin::signed(voter)),
+			frame_system::Error::<Test>::CallFiltered
+		);
+		assert_noop!(
+			change_phase.clone().dispatch(RuntimeOrigin::signed(ca)),
+			frame_system::Error::<Test>::CallFiltered
+		);
+
+		// The election resumes in the same phase
+		assert_ok!(VotingSystem::unpause(RuntimeOrigin::root()));
+		assert_noop!(VotingSystem::unpause(RuntimeOrigin::signed(ca)), Error::<Test>::NotPaused);
+		assert_ok!(vote.clone().dispatch(RuntimeOrigin::signed(voter)));
+		assert_ok!(change_phase.dispatch(RuntimeOrigin::signed(ca)));
+
+		// No ballot gets through once voting ended
+		assert_eq!(VotingSystem::get_phase(), Some(Counting));
+		assert_noop!(
+			vote.dispatch(RuntimeOrigin::signed(voter)),
+			frame_system::Error::<Test>::CallFiltered
+		);
+	})
+}
+
+#[test]
+fn cancelled_election_accepts_no_further_calls() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let ca = root_key;
+		let reason: BoundedVec<u8, _> = b"ballot stuffing".to_vec().try_into().unwrap();
+		System::set_block_number(3);
+		Phase::<Test>::put(Voting);
+
+		// when
+		assert_noop!(
+			VotingSystem::cancel_election(RuntimeOrigin::signed(2), reason.clone()),
+			Error::<Test>::SenderNotCA
+		);
+		assert_ok!(VotingSystem::cancel_election(RuntimeOrigin::signed(ca), reason.clone()));
+
+		// then
+		assert_eq!(VotingSystem::get_phase(), Some(Cancelled));
+		assert_eq!(VotingSystem::phase_change(Cancelled), Some(3));
+		assert_eq!(VotingSystem::cancellation_reason(), Some(reason.clone()));
+		System::assert_has_event(Event::ElectionCancelled { reason: reason.clone() }.into());
+		assert_noop!(
+			VotingSystem::change_phase(RuntimeOrigin::signed(ca)),
+			Error::<Test>::ElectionCancelled
+		);
+		assert_noop!(
+			VotingSystem::cancel_election(RuntimeOrigin::root(), reason),
+			Error::<Test>::ElectionCancelled
+		);
+		assert_noop!(
+			RuntimeCall::VotingSystem(Call::change_phase {}).dispatch(RuntimeOrigin::signed(ca)),
+			frame_system::Error::<Test>::CallFiltered
+		);
+	})
+}
+
+#[test]
+fn disputes_block_completion_until_resolved() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let ca = root_key;
+		let voters = [5, 6];
+		let evidence: BoundedVec<u8, _> =
+			b"receipt missing from the log".to_vec().try_into().unwrap();
+		System::set_block_number(1);
+		Phase::<Test>::put(Voting);
+		for voter in voters {
+			let signature_set = candidates
+				.iter()
+				.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+				.collect();
+			assert_ok!(VotingSystem::vote(RuntimeOrigin::signed(voter), 1, signature_set));
+		}
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+
+		// The dispute period starts with the tally
+		assert_noop!(
+			VotingSystem::file_dispute(RuntimeOrigin::signed(voters[0]), evidence.clone()),
+			Error::<Test>::DisputePeriodClosed
+		);
+		assert_noop!(
+			VotingSystem::change_phase(RuntimeOrigin::signed(ca)),
+			Error::<Test>::TallyNotCounted
+		);
+		assert_ok!(VotingSystem::reveal_ballot_key(
+			RuntimeOrigin::signed(ca),
+			test_secret_key(BALLOT_KEY)
+		));
+		assert_ok!(VotingSystem::tally_step(RuntimeOrigin::signed(ca), 10));
+
+		// when
+		// Only candidates and accounts that voted can dispute, once
+		assert_eq!(VotingSystem::ballot_log_voter(voters[0]), Some(()));
+		assert_noop!(
+			VotingSystem::file_dispute(RuntimeOrigin::signed(7), evidence.clone()),
+			Error::<Test>::NotEligibleToDispute
+		);
+		assert_ok!(VotingSystem::file_dispute(RuntimeOrigin::signed(voters[0]), evidence.clone()));
+		assert_ok!(VotingSystem::file_dispute(RuntimeOrigin::signed(2), evidence.clone()));
+		assert_noop!(
+			VotingSystem::file_dispute(RuntimeOrigin::signed(2), evidence.clone()),
+			Error::<Test>::DisputeAlreadyFiled
+		);
+		assert_eq!(VotingSystem::open_disputes(), 2);
+
+		// then
+		// The election completes after the dispute period, once every dispute is resolved
+		System::set_block_number(11);
+		assert_noop!(
+			VotingSystem::file_dispute(RuntimeOrigin::signed(voters[1]), evidence.clone()),
+			Error::<Test>::DisputePeriodClosed
+		);
+		assert_noop!(
+			VotingSystem::change_phase(RuntimeOrigin::signed(ca)),
+			Error::<Test>::UnresolvedDisputes
+		);
+		assert_noop!(
+			VotingSystem::resolve_dispute(
+				RuntimeOrigin::signed(2),
+				voters[0],
+				DisputeResolution::Dismissed
+			),
+			Error::<Test>::SenderNotCA
+		);
+		for filer in [voters[0], 2] {
+			assert_ok!(VotingSystem::resolve_dispute(
+				RuntimeOrigin::signed(ca),
+				filer,
+				DisputeResolution::Dismissed
+			));
+		}
+		assert_noop!(
+			VotingSystem::resolve_dispute(RuntimeOrigin::signed(ca), 2, DisputeResolution::Upheld),
+			Error::<Test>::DisputeAlreadyResolved
+		);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_eq!(VotingSystem::get_phase(), Some(Completed));
+	})
+}
+
+#[test]
+fn upheld_dispute_cancels_the_election() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates).execute_with(|| {
+		// with
+		let ca = root_key;
+		let evidence: BoundedVec<u8, _> = b"forged signatures".to_vec().try_into().unwrap();
+		System::set_block_number(1);
+		Phase::<Test>::put(Counting);
+		assert_ok!(VotingSystem::reveal_ballot_key(
+			RuntimeOrigin::signed(ca),
+			test_secret_key(BALLOT_KEY)
+		));
+		assert_ok!(VotingSystem::tally_step(RuntimeOrigin::signed(ca), 10));
+		assert_ok!(VotingSystem::file_dispute(RuntimeOrigin::signed(3), evidence.clone()));
+
+		// when
+		assert_ok!(VotingSystem::resolve_dispute(
+			RuntimeOrigin::signed(ca),
+			3,
+			DisputeResolution::Upheld
+		));
+
+		// then
+		assert_eq!(VotingSystem::get_phase(), Some(Cancelled));
+		assert_eq!(VotingSystem::cancellation_reason(), Some(evidence));
+		assert_eq!(VotingSystem::open_disputes(), 0);
+	})
+}
+
+#[test]
+fn reveal_ballot_key_only_accepts_the_matching_key_once() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let ca = root_key;
+		let voter = 5;
+		let signature_set = candidates
+			.iter()
+			.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+			.collect();
+		System::set_block_number(1);
+		Phase::<Test>::put(Voting);
+		assert_ok!(VotingSystem::vote(RuntimeOrigin::signed(voter), 2, signature_set));
+
+		// then
+		// The key stays secret until voting ended
+		assert_noop!(
+			VotingSystem::reveal_ballot_key(RuntimeOrigin::signed(ca), test_secret_key(BALLOT_KEY)),
+			Error::<Test>::InvalidPhase
+		);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+
+		// A bogus key or the key of another pair is rejected
+		assert_noop!(
+			VotingSystem::reveal_ballot_key(RuntimeOrigin::signed(ca), vec![1, 2, 3]),
+			Error::<Test>::InvalidPrivateKey
+		);
+		assert_noop!(
+			VotingSystem::reveal_ballot_key(RuntimeOrigin::signed(ca), test_secret_key(2)),
+			Error::<Test>::BallotKeyMismatch
+		);
+
+		// The matching key is revealed once, so every ballot is counted once
+		assert_ok!(VotingSystem::reveal_ballot_key(
+			RuntimeOrigin::signed(ca),
+			test_secret_key(BALLOT_KEY)
+		));
+		assert_noop!(
+			VotingSystem::reveal_ballot_key(RuntimeOrigin::signed(ca), test_secret_key(BALLOT_KEY)),
+			Error::<Test>::BallotKeyAlreadyRevealed
+		);
+		assert_ok!(VotingSystem::tally_step(RuntimeOrigin::signed(ca), 10));
+		assert_eq!(VotingSystem::get_victor_count(2), Some(1));
+	})
+}
+
+#[test]
+fn tally_step_counts_ballots_over_several_calls() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let ca = root_key;
+		let counter = 7;
+		System::set_block_number(1);
+		Phase::<Test>::put(Voting);
+		for (voter, commitment) in [(10, 1), (11, 2), (12, 2), (13, 3), (14, 2)] {
+			let signature_set = candidates
+				.iter()
+				.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+				.collect();
+			assert_ok!(VotingSystem::vote(RuntimeOrigin::signed(voter), commitment, signature_set));
+		}
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+
+		// when
+		// Counting starts with the revealed ballot key
+		assert_noop!(
+			VotingSystem::tally_step(RuntimeOrigin::signed(counter), 2),
+			Error::<Test>::TallyNotStarted
+		);
+		assert_ok!(VotingSystem::reveal_ballot_key(
+			RuntimeOrigin::signed(ca),
+			test_secret_key(BALLOT_KEY)
+		));
+
+		// then
+		// Every step continues after the ballot counted last
+		for counted in [2, 4] {
+			assert_ok!(VotingSystem::tally_step(RuntimeOrigin::signed(counter), 2));
+			System::assert_last_event(Event::TallyStepped { counted: 2, done: false }.into());
+			let total: u32 = [1, 2, 3]
+				.into_iter()
+				.map(|commitment| VotingSystem::get_victor_count(commitment).unwrap_or(0))
+				.sum();
+			assert_eq!(total, counted);
+			assert_eq!(VotingSystem::get_winner(), None);
+		}
+
+		// The step counting the last ballot selects the winner
+		assert_ok!(VotingSystem::tally_step(RuntimeOrigin::signed(counter), 2));
+		System::assert_last_event(Event::TallyStepped { counted: 1, done: true }.into());
+		assert_eq!(VotingSystem::get_victor_count(1), Some(1));
+		assert_eq!(VotingSystem::get_victor_count(2), Some(3));
+		assert_eq!(VotingSystem::get_victor_count(3), Some(1));
+		assert_eq!(VotingSystem::get_winner(), Some(2));
+		assert_eq!(VotingSystem::tally_cursor(), None);
+		assert_noop!(
+			VotingSystem::tally_step(RuntimeOrigin::signed(counter), 2),
+			Error::<Test>::TallyAlreadyCounted
+		);
+	})
+}
+
+#[test]
+fn a_tie_goes_to_the_lowest_commitment() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let ca = root_key;
+		System::set_block_number(1);
+		Phase::<Test>::put(Voting);
+		// Commitments 1 and 3 tie, whatever order the ballots are counted in
+		for (voter, commitment) in [(10, 3), (11, 3), (12, 1), (13, 2), (14, 1)] {
+			let signature_set = candidates
+				.iter()
+				.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+				.collect();
+			assert_ok!(VotingSystem::vote(RuntimeOrigin::signed(voter), commitment, signature_set));
+		}
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_ok!(VotingSystem::reveal_ballot_key(
+			RuntimeOrigin::signed(ca),
+			test_secret_key(BALLOT_KEY)
+		));
+
+		// when
+		assert_ok!(VotingSystem::tally_step(RuntimeOrigin::signed(ca), 2));
+		assert_ok!(VotingSystem::tally_step(RuntimeOrigin::signed(ca), 3));
+
+		// then
+		assert_eq!(VotingSystem::get_victor_count(1), Some(2));
+		assert_eq!(VotingSystem::get_victor_count(3), Some(2));
+		assert_eq!(VotingSystem::tally_leader(), Some((1, 2)));
+		assert_eq!(VotingSystem::get_winner(), Some(1));
+	})
+}
+
+#[test]
+fn timelock_counting_starts_with_the_beacon_signature() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let ca = root_key;
+		let voter = 5;
+		let round = 1000;
+		let mut rng = ChaCha20Rng::seed_from_u64(0);
+		let public_key = timelock::public_key(&MOCK_BEACON_SECRET).unwrap();
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::set_timelock_round(RuntimeOrigin::signed(ca), Some(round)));
+		System::assert_last_event(Event::TimelockRoundSet { round: Some(round) }.into());
+		let signature_set = || -> Vec<_> {
+			candidates
+				.iter()
+				.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+				.collect()
+		};
+		Phase::<Test>::put(Voting);
+		assert_noop!(
+			VotingSystem::vote(RuntimeOrigin::signed(voter), 2, signature_set()),
+			Error::<Test>::TimelockRequired
+		);
+		let ciphertext =
+			timelock::encrypt(&public_key, round, &timelock::commitment_message(2), &mut rng)
+				.unwrap();
+		assert_ok!(VotingSystem::vote_timelocked(
+			RuntimeOrigin::signed(voter),
+			ciphertext.clone(),
+			signature_set()
+		));
+		assert_eq!(VotingSystem::timelocked_ballot(voter).unwrap().ciphertext, ciphertext);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+
+		// when
+		// The CA has no key to reveal and the tally waits until the round is signed
+		assert_noop!(
+			VotingSystem::reveal_ballot_key(RuntimeOrigin::signed(ca), test_secret_key(BALLOT_KEY)),
+			Error::<Test>::BallotKeyTimeLocked
+		);
+		assert_noop!(
+			VotingSystem::tally_timelocked_step(RuntimeOrigin::signed(voter), 10),
+			Error::<Test>::TallyNotStarted
+		);
+		assert_noop!(
+			VotingSystem::submit_beacon_signature(
+				RuntimeOrigin::signed(voter),
+				round - 1,
+				mock_beacon_signature(round - 1)
+			),
+			Error::<Test>::WrongBeaconRound
+		);
+		assert_noop!(
+			VotingSystem::submit_beacon_signature(
+				RuntimeOrigin::signed(voter),
+				round,
+				mock_beacon_signature(round - 1)
+			),
+			Error::<Test>::InvalidBeaconSignature
+		);
+
+		// Anybody submits the signature of the round
+		assert_ok!(VotingSystem::submit_beacon_signature(
+			RuntimeOrigin::signed(voter),
+			round,
+			mock_beacon_signature(round)
+		));
+		System::assert_last_event(Event::BeaconSignatureSubmitted { round }.into());
+
+		// then
+		assert_noop!(
+			VotingSystem::submit_beacon_signature(
+				RuntimeOrigin::signed(voter),
+				round,
+				mock_beacon_signature(round)
+			),
+			Error::<Test>::BeaconSignatureAlreadySubmitted
+		);
+		assert_noop!(
+			VotingSystem::tally_step(RuntimeOrigin::signed(voter), 10),
+			Error::<Test>::TimelockRequired
+		);
+		assert_ok!(VotingSystem::tally_timelocked_step(RuntimeOrigin::signed(voter), 10));
+		assert_eq!(VotingSystem::get_victor_count(2), Some(1));
+		assert_eq!(VotingSystem::get_winner(), Some(2));
+		assert_ok!(VotingSystem::do_try_state());
+	})
+}
+
+#[test]
+fn timelocked_ballots_only_decrypt_with_the_round_signature() {
+	// with
+	let mut rng = ChaCha20Rng::seed_from_u64(0);
+	let public_key = timelock::public_key(&MOCK_BEACON_SECRET).unwrap();
+	let message = timelock::commitment_message(7);
+
+	// when
+	let ciphertext = timelock::encrypt(&public_key, 42, &message, &mut rng).unwrap();
+
+	// then
+	let signature = timelock::sign(&MOCK_BEACON_SECRET, 42).unwrap();
+	assert!(timelock::verify(&public_key, 42, &signature));
+	assert!(!timelock::verify(&public_key, 43, &signature));
+	assert_eq!(timelock::decrypt(&signature, &ciphertext), Some(message));
+	assert_eq!(timelock::message_commitment(&message), Some(7));
+
+	// The signature of another round, or of another beacon, opens nothing
+	let other_round = timelock::sign(&MOCK_BEACON_SECRET, 43).unwrap();
+	assert_eq!(timelock::decrypt(&other_round, &ciphertext), None);
+	let other_beacon = timelock::sign(&[8; 32], 42).unwrap();
+	assert_eq!(timelock::decrypt(&other_beacon, &ciphertext), None);
+	assert_eq!(timelock::decrypt(&signature[1..], &ciphertext), None);
+
+	// Tampering with the ciphertext fails the Fujisaki-Okamoto check
+	let mut tampered = ciphertext.clone();
+	tampered.w[0] ^= 1;
+	assert_eq!(timelock::decrypt(&signature, &tampered), None);
+	let mut tampered = ciphertext;
+	tampered.v[0] ^= 1;
+	assert_eq!(timelock::decrypt(&signature, &tampered), None);
+}
+
+#[test]
+fn timelocked_ballots_that_do_not_decrypt_are_rejected() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let ca = root_key;
+		let round = 1000;
+		let mut rng = ChaCha20Rng::seed_from_u64(0);
+		let public_key = timelock::public_key(&MOCK_BEACON_SECRET).unwrap();
+		let signature_set = |voter| -> Vec<_> {
+			candidates
+				.iter()
+				.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+				.collect()
+		};
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::set_timelock_round(RuntimeOrigin::signed(ca), Some(round)));
+		let tally_key = credentials::public_key(&credentials::random_exponent(&mut rng));
+		assert_noop!(
+			VotingSystem::set_credential_key(RuntimeOrigin::signed(ca), Some(tally_key)),
+			Error::<Test>::CredentialsTimeLocked
+		);
+		Phase::<Test>::put(Voting);
+
+		// Voter 5 encrypts to the round, voter 6 to the wrong round and voter 7 a message that is
+		// no commitment
+		let encrypt = |round, message, rng: &mut ChaCha20Rng| {
+			timelock::encrypt(&public_key, round, &message, rng).unwrap()
+		};
+		let ballots = [
+			(5, encrypt(round, timelock::commitment_message(1), &mut rng)),
+			(6, encrypt(round + 1, timelock::commitment_message(2), &mut rng)),
+			(7, encrypt(round, [1; 32], &mut rng)),
+		];
+		for (voter, ciphertext) in ballots {
+			assert_ok!(VotingSystem::vote_timelocked(
+				RuntimeOrigin::signed(voter),
+				ciphertext,
+				signature_set(voter)
+			));
+		}
+		let mut not_a_point = encrypt(round, timelock::commitment_message(1), &mut rng);
+		not_a_point.u = [0xff; 96];
+		assert_noop!(
+			VotingSystem::vote_timelocked(RuntimeOrigin::signed(8), not_a_point, signature_set(8)),
+			Error::<Test>::InvalidTimelockedBallot
+		);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_ok!(VotingSystem::submit_beacon_signature(
+			RuntimeOrigin::signed(5),
+			round,
+			mock_beacon_signature(round)
+		));
+
+		// when
+		assert_ok!(VotingSystem::tally_timelocked_step(RuntimeOrigin::signed(5), 2));
+		assert_ok!(VotingSystem::tally_timelocked_step(RuntimeOrigin::signed(5), 2));
+
+		// then
+		System::assert_has_event(Event::TimelockedBallotRejected { voter: 6 }.into());
+		System::assert_has_event(Event::TimelockedBallotRejected { voter: 7 }.into());
+		assert_eq!(VotingSystem::rejected_timelocked_ballots(), 2);
+		assert_eq!(VotingSystem::get_victor_count(1), Some(1));
+		assert_eq!(VotingSystem::get_victor_count(2), None);
+		assert_eq!(VotingSystem::get_winner(), Some(1));
+		assert_eq!(VotingSystem::election_summary().rejected_ballots, 2);
+		assert_ok!(VotingSystem::do_try_state());
+	})
+}
+
+#[test]
+fn ballot_log_keeps_replaced_ballots_with_inclusion_proofs() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with
+		let ca = root_key;
+		let voters = [5, 6, 7];
+		let signature_set = |voter| -> Vec<_> {
+			candidates
+				.iter()
+				.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+				.collect()
+		};
+
+		// when
+		System::set_block_number(1);
+		Phase::<Test>::put(Voting);
+		let mut receipts = Vec::new();
+		let mut roots = Vec::new();
+		for (voter, commitment) in
+			[(voters[0], 1), (voters[1], 2), (voters[0], 3), (voters[2], 1), (voters[1], 3)]
+		{
+			assert_ok!(VotingSystem::vote(
+				RuntimeOrigin::signed(voter),
+				commitment,
+				signature_set(voter)
+			));
+			let index = VotingSystem::ballot_log_length() - 1;
+			let ballot = VotingSystem::ballots(voter).unwrap();
+			receipts.push(VotingSystem::ballot_receipt(&voter, &ballot, index));
+			roots.push(VotingSystem::ballot_log_root(index + 1));
+		}
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+
+		// then
+		// The replaced first ballot of voter 5 stays in the log
+		assert_eq!(VotingSystem::ballot_log_length(), 5);
+		let first = VotingSystem::ballot_log_entry(0).unwrap();
+		assert_eq!(first.voter, voters[0]);
+		assert_eq!(Ballot::decode(&mut &first.ballot[..]).unwrap().commitment, 1);
+
+		// Appending never changes the root of an earlier length
+		for (length, root) in roots.iter().enumerate() {
+			assert_eq!(VotingSystem::ballot_log_root(length as u64 + 1), *root);
+		}
+		let root = roots[4];
+		assert_eq!(VotingSystem::published_ballot_log(), Some((5, root)));
+		System::assert_has_event(Event::BallotLogPublished { length: 5, root }.into());
+
+		// Five ballots make a mountain of four and a mountain of one
+		assert_eq!(VotingSystem::ballot_log_mountains(5), vec![(2, 0), (0, 4)]);
+		for receipt in &receipts {
+			let proof = VotingSystem::ballot_inclusion_proof(receipt).unwrap();
+			assert!(VotingSystem::verify_inclusion_proof(receipt, &proof, &root));
+		}
+		let proof = VotingSystem::ballot_inclusion_proof(&receipts[1]).unwrap();
+		assert_eq!((proof.index, proof.length, proof.path.len(), proof.peaks.len()), (1, 5, 2, 1));
+		assert!(!VotingSystem::verify_inclusion_proof(&receipts[0], &proof, &root));
+		assert!(!VotingSystem::verify_inclusion_proof(&receipts[1], &proof, &roots[3]));
+		let mut tampered = proof.clone();
+		tampered.index = 0;
+		assert!(!VotingSystem::verify_inclusion_proof(&receipts[1], &tampered, &root));
+		let mut tampered = proof.clone();
+		tampered.path.pop();
+		assert!(!VotingSystem::verify_inclusion_proof(&receipts[1], &tampered, &root));
+		let mut tampered = proof;
+		tampered.peaks[0] = receipts[0];
+		assert!(!VotingSystem::verify_inclusion_proof(&receipts[1], &tampered, &root));
+		assert_eq!(VotingSystem::ballot_inclusion_proof(&Default::default()), None);
+
+		assert_eq!(
+			VotingSystem::ballot_log_page(3),
+			vec![
+				VotingSystem::ballot_log_entry(3).unwrap(),
+				VotingSystem::ballot_log_entry(4).unwrap()
+			]
+		);
+		assert_eq!(VotingSystem::ballot_log_page(5), vec![]);
+		let summary = VotingSystem::election_summary();
+		assert_eq!((summary.ballot_log_length, summary.credentials), (5, false));
+		assert_eq!(summary.phase, Some(Counting));
+
+		let inclusion = VotingSystem::ballot_inclusion(&receipts[4]).unwrap();
+		assert_eq!(
+			(inclusion.entry.voter, inclusion.root, inclusion.published),
+			(voters[1], root, true)
 		);
 	})
 }
 
 #[test]
-fn can_vote() {
+fn identical_credential_ballots_get_distinct_receipts() {
 	let root_key = 1;
 	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
 	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
 		// with
+		let ca = root_key;
 		let voter = 5;
-		let ca = 1;
-		let blinded_pubkey = vec![1, 2, 3];
-		let signed_blinded_pubkey = vec![4, 5, 6];
-		let is_eligible = true;
-		let personal_data_hash = vec![7, 8, 9];
-		let commitment = vec![1, 2, 3];
-		let signature = vec![4, 5, 6];
+		let mut rng = ChaCha20Rng::seed_from_u64(0);
+		let tally_key = credentials::public_key(&credentials::random_exponent(&mut rng));
+		let credential = credentials::new_credential(&mut rng);
+		let ciphertext = credentials::encrypt(&tally_key, &credential, &mut rng).unwrap();
+		let signature_set = || -> Vec<_> {
+			candidates
+				.iter()
+				.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+				.collect()
+		};
 
 		// when
 		System::set_block_number(1);
-		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
-		assert_ok!(VotingSystem::add_voter(
-			RuntimeOrigin::signed(ca),
-			blinded_pubkey,
-			signed_blinded_pubkey,
-			personal_data_hash,
-			is_eligible,
-		));
-
-		// Registration => BiasedSigning
-		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
-		for candidate in candidates.iter() {
-			let blinded_signature: BoundedVec<u8, SignatureLength> =
-				BoundedVec::try_from(vec![1, 2, 3]).unwrap();
-
-			assert_ok!(VotingSystem::biased_signing(
-				RuntimeOrigin::signed(*candidate),
-				*candidate,
+		assert_ok!(VotingSystem::set_credential_key(RuntimeOrigin::signed(ca), Some(tally_key)));
+		Phase::<Test>::put(Voting);
+		for _ in 0..2 {
+			assert_ok!(VotingSystem::vote_with_credential(
+				RuntimeOrigin::signed(voter),
 				1,
-				blinded_signature
+				ciphertext.clone(),
+				signature_set()
 			));
 		}
 
-		// BiasedSigning => Voting
-		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
-
-		// then vote
-		assert_ok!(VotingSystem::vote(
-			RuntimeOrigin::signed(voter),
-			commitment.clone(),
-			signature.clone()
-		));
-		assert_eq!(
-			VotingSystem::get_ballot(voter),
-			Some(Ballot { commitment, signature, nonce: 1 })
+		// then
+		assert_eq!(VotingSystem::credential_ballot(0), VotingSystem::credential_ballot(1));
+		let ballot = VotingSystem::credential_ballot(0).unwrap();
+		let receipts = [0, 1].map(|index| VotingSystem::ballot_receipt(&voter, &ballot, index));
+		assert_ne!(receipts[0], receipts[1]);
+		System::assert_last_event(
+			Event::CredentialBallotCast { index: 1, receipt: receipts[1] }.into(),
 		);
+		let root = VotingSystem::ballot_log_root(2);
+		for (index, receipt) in receipts.iter().enumerate() {
+			assert_eq!(VotingSystem::ballot_log_index(receipt), Some(index as u64));
+			let proof = VotingSystem::ballot_inclusion_proof(receipt).unwrap();
+			assert!(VotingSystem::verify_inclusion_proof(receipt, &proof, &root));
+		}
 	})
 }
 
 #[test]
-pub fn can_change_vote() {
+fn credential_tally_counts_last_ballot_of_registered_credentials() {
+	let mut rng = ChaCha20Rng::seed_from_u64(0);
+	let tally_secret = credentials::random_exponent(&mut rng);
+	let tally_key = credentials::public_key(&tally_secret);
+	let real = credentials::new_credential(&mut rng);
+	let fake = credentials::new_credential(&mut rng);
+	let mut encrypt = |credential: credentials::GroupElement| {
+		credentials::encrypt(&tally_key, &credential, &mut rng).unwrap()
+	};
+
+	let registered = vec![encrypt(real)];
+	let ballots =
+		vec![(1, encrypt(fake)), (2, encrypt(real)), (3, encrypt(fake)), (4, encrypt(real))];
+	// Encryptions of the same credential differ
+	assert_ne!(ballots[1].1, ballots[3].1);
+
+	// The fake ballots are dropped and the real ballot cast last counts
+	let transcript =
+		credentials::prove_tally(&tally_secret, &registered, &ballots, &mut rng).unwrap();
+	assert_eq!(transcript.mixed_ballots.len(), 2);
+	assert_eq!(transcript.counted.len(), 1);
+	let tally = credentials::verify_tally(&tally_key, &registered, &ballots, &transcript).unwrap();
+	assert_eq!(tally.into_iter().collect::<Vec<_>>(), vec![(4, 1)]);
+
+	// A transcript proves the tally of its own ballots only
+	let mut replaced = ballots.clone();
+	replaced[3].0 = 2;
+	assert_eq!(
+		credentials::verify_tally(&tally_key, &registered, &replaced, &transcript),
+		Err(TranscriptError::BallotShuffle)
+	);
+	let mut forged = transcript.clone();
+	forged.counted[0].commitment = 2;
+	assert_eq!(
+		credentials::verify_tally(&tally_key, &registered, &ballots, &forged),
+		Err(TranscriptError::CountedBallots)
+	);
+	let mut forged = transcript;
+	forged.duplicate_tags.swap(0, 2);
+	assert_eq!(
+		credentials::verify_tally(&tally_key, &registered, &ballots, &forged),
+		Err(TranscriptError::DuplicateTags)
+	);
+}
+
+#[test]
+fn coercion_resistant_election_counts_submitted_tally() {
 	let root_key = 1;
 	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
 	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
 		// with
+		let ca = root_key;
 		let voter = 5;
-		let ca = 1;
-		let blinded_pubkey = vec![1, 2, 3];
-		let signed_blinded_pubkey = vec![4, 5, 6];
-		let is_eligible = true;
-		let commitment = vec![1, 2, 3];
-		let signature = vec![4, 5, 6];
-		let personal_data_hash = vec![7, 8, 9];
+		let mut rng = ChaCha20Rng::seed_from_u64(0);
+		let tally_key = credentials::public_key(&credentials::random_exponent(&mut rng));
+		let real = credentials::new_credential(&mut rng);
+		let fake = credentials::new_credential(&mut rng);
+		let mut encrypt = |credential: credentials::GroupElement| {
+			credentials::encrypt(&tally_key, &credential, &mut rng).unwrap()
+		};
+		let signature_set = || -> Vec<_> {
+			candidates
+				.iter()
+				.map(|candidate| (*candidate, candidate_signature(*candidate, voter)))
+				.collect()
+		};
 
 		// when
 		System::set_block_number(1);
-		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_noop!(
+			VotingSystem::set_credential_key(RuntimeOrigin::signed(ca), Some([0; 256])),
+			Error::<Test>::InvalidCredential
+		);
+		assert_ok!(VotingSystem::set_credential_key(RuntimeOrigin::signed(ca), Some(tally_key)));
+		System::assert_last_event(Event::CredentialKeySet { enabled: true }.into());
+
+		Phase::<Test>::put(Registration);
 		assert_ok!(VotingSystem::add_voter(
 			RuntimeOrigin::signed(ca),
-			blinded_pubkey,
-			signed_blinded_pubkey,
-			personal_data_hash,
-			is_eligible
+			get_default_blinded_pubkey(),
+			vec![],
+			vec![],
+			true
+		));
+		let registered = VotingSystem::voter_id(&get_default_blinded_pubkey());
+		let unknown = VotingSystem::voter_id(&[3, 2, 1]);
+		assert_noop!(
+			VotingSystem::register_credential(RuntimeOrigin::signed(ca), unknown, encrypt(real)),
+			Error::<Test>::VoterDoesNotExist
+		);
+		assert_ok!(VotingSystem::register_credential(
+			RuntimeOrigin::signed(ca),
+			registered,
+			encrypt(real)
 		));
-		// Registration => BiasedSigning
-		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
-		for candidate in candidates.iter() {
-			let blinded_signature: BoundedVec<u8, SignatureLength> =
-				BoundedVec::try_from(vec![1, 2, 3]).unwrap();
-
-			assert_ok!(VotingSystem::biased_signing(
-				RuntimeOrigin::signed(*candidate),
-				*candidate,
-				1,
-				blinded_signature
-			));
-		}
 
-		// BiasedSigning => Voting
-		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
-		assert_ok!(VotingSystem::vote(
+		Phase::<Test>::put(Voting);
+		assert_noop!(
+			VotingSystem::vote(RuntimeOrigin::signed(voter), 1, signature_set()),
+			Error::<Test>::CredentialRequired
+		);
+		// The coercer watches the fake ballot being cast, the real one follows in private
+		assert_ok!(VotingSystem::vote_with_credential(
 			RuntimeOrigin::signed(voter),
-			commitment.clone(),
-			signature.clone()
+			2,
+			encrypt(fake),
+			signature_set()
 		));
-		assert_eq!(
-			VotingSystem::get_ballot(voter),
-			Some(Ballot { commitment, signature, nonce: 1 })
-		);
-
-		// then change vote
-		let new_commitment = vec![1, 2, 3, 4];
-		let new_signature = vec![4, 5, 6, 7];
-		assert_ok!(VotingSystem::vote(
+		let ballot = VotingSystem::credential_ballot(0).unwrap();
+		let receipt = VotingSystem::ballot_receipt(&voter, &ballot, 0);
+		System::assert_last_event(Event::CredentialBallotCast { index: 0, receipt }.into());
+		assert_ok!(VotingSystem::vote_with_credential(
 			RuntimeOrigin::signed(voter),
-			new_commitment.clone(),
-			new_signature.clone()
+			1,
+			encrypt(real),
+			signature_set()
 		));
-		assert_eq!(
-			VotingSystem::get_ballot(voter),
-			Some(Ballot { commitment: new_commitment, signature: new_signature, nonce: 2 })
-		);
-	})
-}
-
-#[test]
-// TODO: Incomplete test
-#[ignore]
-fn can_biased_signing() {
-	let root_key = 1;
-	new_test_ext(root_key).execute_with(|| {
-		// with
-		// let candidate = 2;
-		// let voter: u64 = 15;
-		// let pubkey: Vec<u8> = vec![1, 2, 3];
-		// let blinded_signature: BoundedVec<u8, Get<u32>> = vec![1, 2, 3];
-
-		// when
-		// System::set_block_number(1);
-		// assert_ok!(VotingSystem::biased_signing(
-		// 	RuntimeOrigin::signed(candidate),
-		// 	candidate,
-		// 	voter,
-		// 	blinded_signature
-		// ));
+		assert_eq!(VotingSystem::credential_ballot_count(), 2);
 
 		// then
-		// assert_eq!(
-		// 	VotingSystem::get_candidate(candidate),
-		// 	Some(Candidate { name: name.to_string(), pubkey })
-		// );
+		Phase::<Test>::put(Counting);
+		// The tally is read in casting order
+		let (registered_credentials, ballots) = VotingSystem::credential_tally_inputs();
+		assert_eq!(registered_credentials.len(), 1);
+		assert_eq!(
+			ballots.iter().map(|(commitment, _)| *commitment).collect::<Vec<_>>(),
+			vec![2, 1]
+		);
+		// The fake ballot is dropped, the real one kept. Every registered credential counts once
+		let transcript = H256::repeat_byte(7);
+		let tally = |tally: Vec<(u32, u32)>| BoundedVec::try_from(tally).unwrap();
+		assert_noop!(
+			VotingSystem::submit_credential_tally(
+				RuntimeOrigin::signed(ca),
+				tally(vec![(1, 1), (2, 1)]),
+				transcript
+			),
+			Error::<Test>::InvalidTally
+		);
+		assert_ok!(VotingSystem::submit_credential_tally(
+			RuntimeOrigin::signed(ca),
+			tally(vec![(1, 1)]),
+			transcript
+		));
+		System::assert_last_event(
+			Event::CredentialTallySubmitted { counted: 1, discarded: 1 }.into(),
+		);
+		assert_eq!(VotingSystem::get_winner(), Some(1));
+		assert_eq!(VotingSystem::credential_tally_transcript(), Some(transcript));
+		assert_noop!(
+			VotingSystem::submit_credential_tally(
+				RuntimeOrigin::signed(ca),
+				tally(vec![(1, 1)]),
+				transcript
+			),
+			Error::<Test>::TallyAlreadyCounted
+		);
 	})
 }
 
@@ -346,10 +2153,16 @@ fn can_reveal_ballot_key() {
 		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
 
 		// then reveal ballot key
-		assert_ok!(VotingSystem::reveal_ballot_key(RuntimeOrigin::signed(ca), vec![1, 2, 3]));
+		assert_ok!(VotingSystem::reveal_ballot_key(
+			RuntimeOrigin::signed(ca),
+			test_secret_key(BALLOT_KEY)
+		));
 		assert_eq!(
 			VotingSystem::get_ballot_key(),
-			Some(BallotKey { public: vec![1, 2, 3], private: vec![1, 2, 3] })
+			Some(BallotKey {
+				public: test_public_key(BALLOT_KEY),
+				private: test_secret_key(BALLOT_KEY)
+			})
 		)
 	})
 }
@@ -379,7 +2192,7 @@ fn change_phase_errors_when_no_blinded_signature() {
 			is_eligible
 		));
 		assert_eq!(
-			VotingSystem::voters(1),
+			VotingSystem::voters(VotingSystem::voter_id(&blinded_pubkey)),
 			Some(Voter { blinded_pubkey, signed_blinded_pubkey, is_eligible, personal_data_hash })
 		);
 
@@ -418,16 +2231,18 @@ fn change_phase_errors_when_only_partial_blinded_signature() {
 			is_eligible
 		));
 		assert_eq!(
-			VotingSystem::voters(1),
+			VotingSystem::voters(VotingSystem::voter_id(&blinded_pubkey)),
 			Some(Voter { blinded_pubkey, signed_blinded_pubkey, is_eligible, personal_data_hash })
 		);
 
 		// Registration => BiasedSigning
 		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
 
-		let blinded_signature: BoundedVec<u8, SignatureLength> =
-			BoundedVec::try_from(vec![1, 2, 3]).unwrap();
-		VotingSystem::biased_signing(RuntimeOrigin::signed(2), 2, 1, blinded_signature).unwrap();
+		let voter = VotingSystem::voter_id(&get_default_blinded_pubkey());
+		let blindings = register_blinded_messages(ca, voter, 5);
+		let blinded_signature = blind_sign(2, &blinded_message_of(&blindings, 2));
+		VotingSystem::biased_signing(RuntimeOrigin::signed(2), 2, voter, blinded_signature)
+			.unwrap();
 
 		assert_noop!(
 			VotingSystem::change_phase(RuntimeOrigin::signed(ca)),
@@ -442,7 +2257,7 @@ fn change_phase_errors_when_not_all_voter_receive_blinded_signature() {
 	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
 	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
 		let ca = root_key;
-		let expected_voter_id = 1;
+		let expected_voter_id = VotingSystem::voter_id(&get_default_blinded_pubkey());
 		System::set_block_number(1);
 		// Initialization phase
 		assert_eq!(VotingSystem::phase(), Some(Initialization));
@@ -463,23 +2278,25 @@ fn change_phase_errors_when_not_all_voter_receive_blinded_signature() {
 		));
 		assert_ok!(VotingSystem::add_voter(
 			RuntimeOrigin::signed(ca),
-			blinded_pubkey.clone(),
+			vec![3, 2, 1],
 			signed_blinded_pubkey.clone(),
 			personal_data_hash.clone(),
 			is_eligible
 		));
 		assert_eq!(
-			VotingSystem::voters(1),
+			VotingSystem::voters(VotingSystem::voter_id(&blinded_pubkey)),
 			Some(Voter { blinded_pubkey, signed_blinded_pubkey, is_eligible, personal_data_hash })
 		);
 
 		// Registration => BiasedSigning
 		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		let blindings = register_blinded_messages(ca, expected_voter_id, 5);
+		register_blinded_messages(ca, VotingSystem::voter_id(&[3, 2, 1]), 6);
 
 		// All candidates only signing for one voter
 		for candidate in candidates.iter() {
-			let blinded_signature: BoundedVec<u8, SignatureLength> =
-				BoundedVec::try_from(vec![1, 2, 3]).unwrap();
+			let blinded_signature =
+				blind_sign(*candidate, &blinded_message_of(&blindings, *candidate));
 
 			assert_ok!(VotingSystem::biased_signing(
 				RuntimeOrigin::signed(*candidate),
@@ -504,7 +2321,7 @@ fn change_phase_success_with_all_blinded_signature() {
 	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
 	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
 		let ca = root_key;
-		let expected_voter_id = 1;
+		let expected_voter_id = VotingSystem::voter_id(&get_default_blinded_pubkey());
 		System::set_block_number(1);
 		// Initialization phase
 		assert_eq!(VotingSystem::phase(), Some(Initialization));
@@ -524,16 +2341,17 @@ fn change_phase_success_with_all_blinded_signature() {
 			is_eligible
 		));
 		assert_eq!(
-			VotingSystem::voters(1),
+			VotingSystem::voters(VotingSystem::voter_id(&blinded_pubkey)),
 			Some(Voter { blinded_pubkey, signed_blinded_pubkey, is_eligible, personal_data_hash })
 		);
 
 		// Registration => BiasedSigning
 		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		let blindings = register_blinded_messages(ca, expected_voter_id, 5);
 
 		for candidate in candidates.iter() {
-			let blinded_signature: BoundedVec<u8, SignatureLength> =
-				BoundedVec::try_from(vec![1, 2, 3]).unwrap();
+			let blinded_signature =
+				blind_sign(*candidate, &blinded_message_of(&blindings, *candidate));
 
 			assert_ok!(VotingSystem::biased_signing(
 				RuntimeOrigin::signed(*candidate),
@@ -551,3 +2369,355 @@ fn change_phase_success_with_all_blinded_signature() {
 fn get_default_blinded_pubkey() -> Vec<u8> {
 	return vec![1, 2, 3]
 }
+
+// Blind the voting account `voter` for every candidate key and register the blinded messages of
+// the registered voter `voter_id`. The blinding results are needed to unblind the signatures
+fn register_blinded_messages(
+	ca: u64,
+	voter_id: H256,
+	voter: u64,
+) -> Vec<(u64, blind_rsa_signatures::BlindingResult)> {
+	let blindings: Vec<_> = Candidates::<Test>::iter_keys()
+		.map(|candidate| (candidate, blind(candidate, voter)))
+		.collect();
+	let messages: Vec<_> = blindings
+		.iter()
+		.map(|(candidate, blinding)| {
+			(*candidate, BoundedVec::try_from(blinding.blind_msg.to_vec()).unwrap())
+		})
+		.collect();
+	assert_ok!(VotingSystem::register_blinded_messages(
+		RuntimeOrigin::signed(ca),
+		voter_id,
+		BoundedVec::try_from(messages).unwrap()
+	));
+	blindings
+}
+
+// Register the blinded messages of the registered voter `voter_id` and let every candidate sign
+// the message blinded for their key
+fn sign_voter(ca: u64, voter_id: H256, voter: u64) {
+	let blindings = register_blinded_messages(ca, voter_id, voter);
+	for (candidate, blinding) in blindings.iter() {
+		assert_ok!(VotingSystem::biased_signing(
+			RuntimeOrigin::signed(*candidate),
+			*candidate,
+			voter_id,
+			blind_sign(*candidate, blinding.blind_msg.as_slice())
+		));
+	}
+}
+
+// Message blinded for the key of `candidate`
+fn blinded_message_of(
+	blindings: &[(u64, blind_rsa_signatures::BlindingResult)],
+	candidate: u64,
+) -> Vec<u8> {
+	blindings
+		.iter()
+		.find(|(signer, _)| *signer == candidate)
+		.map(|(_, blinding)| blinding.blind_msg.to_vec())
+		.unwrap()
+}
+
+#[test]
+fn migration_to_v1_backfills_phase_changes_and_key_commitments() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with a chain that was already voting before the upgrade
+		System::set_block_number(10);
+		StorageVersion::new(0).put::<VotingSystem>();
+		Phase::<Test>::put(Voting);
+		let _ = PhaseChanges::<Test>::clear(u32::MAX, None);
+
+		// when
+		MigrateToV1::<Test>::on_runtime_upgrade();
+
+		// then
+		assert_eq!(VotingSystem::on_chain_storage_version(), 1);
+		assert_eq!(VotingSystem::phase_change(Voting), Some(10));
+		for candidate in candidates {
+			assert!(VotingSystem::candidate_key_fingerprint(candidate).is_some());
+		}
+
+		// running it again does nothing
+		System::set_block_number(11);
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(VotingSystem::phase_change(Voting), Some(10));
+	})
+}
+
+#[test]
+fn migration_to_v1_moves_voters_to_their_ids() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates).execute_with(|| {
+		// with voters registered under their index before the upgrade
+		StorageVersion::new(0).put::<VotingSystem>();
+		let voter = |blinded_pubkey: Vec<u8>| Voter {
+			blinded_pubkey,
+			signed_blinded_pubkey: vec![],
+			is_eligible: true,
+			personal_data_hash: vec![],
+		};
+		let blinded_signature: BoundedVec<u8, SignatureLength> =
+			BoundedVec::try_from(vec![1, 2, 3]).unwrap();
+		v1::old::Voters::<Test>::insert(1, voter(vec![1, 2, 3]));
+		v1::old::Voters::<Test>::insert(2, voter(vec![3, 2, 1]));
+		v1::old::VoterCount::<Test>::put(2);
+		v1::old::BlindedSignatures::<Test>::insert(1, 2, blinded_signature.clone());
+		// a signature of a voter index nobody was registered under
+		v1::old::BlindedSignatures::<Test>::insert(7, 2, blinded_signature.clone());
+
+		// when
+		MigrateToV1::<Test>::on_runtime_upgrade();
+
+		// then
+		let first = VotingSystem::voter_id(&[1, 2, 3]);
+		let second = VotingSystem::voter_id(&[3, 2, 1]);
+		assert_eq!(VotingSystem::on_chain_storage_version(), 1);
+		assert_eq!(Voters::<Test>::count(), 2);
+		assert_eq!(VotingSystem::voters(first), Some(voter(vec![1, 2, 3])));
+		assert_eq!(VotingSystem::voters(second), Some(voter(vec![3, 2, 1])));
+		assert_eq!(VotingSystem::blinded_signatures(first, 2), Some(blinded_signature));
+		assert_eq!(crate::BlindedSignatures::<Test>::iter_keys().count(), 1);
+		assert_eq!(VotingSystem::signed_voter_count(2), 1);
+		assert_eq!(v1::old::VoterCount::<Test>::get(), None);
+
+		// running it again does nothing
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(Voters::<Test>::count(), 2);
+		assert_eq!(VotingSystem::signed_voter_count(2), 1);
+	})
+}
+
+#[test]
+fn migration_to_v1_counts_voters_with_duplicate_blinded_keys_once() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates).execute_with(|| {
+		// with the same blinded key registered under two indices, both signed by candidate 2
+		StorageVersion::new(0).put::<VotingSystem>();
+		let voter = Voter {
+			blinded_pubkey: vec![1, 2, 3],
+			signed_blinded_pubkey: vec![],
+			is_eligible: true,
+			personal_data_hash: vec![],
+		};
+		let blinded_signature: BoundedVec<u8, SignatureLength> =
+			BoundedVec::try_from(vec![1, 2, 3]).unwrap();
+		v1::old::Voters::<Test>::insert(1, voter.clone());
+		v1::old::Voters::<Test>::insert(2, voter.clone());
+		v1::old::VoterCount::<Test>::put(2);
+		v1::old::BlindedSignatures::<Test>::insert(1, 2, blinded_signature.clone());
+		v1::old::BlindedSignatures::<Test>::insert(2, 2, blinded_signature.clone());
+
+		// when
+		MigrateToV1::<Test>::on_runtime_upgrade();
+
+		// then
+		let id = VotingSystem::voter_id(&[1, 2, 3]);
+		assert_eq!(VotingSystem::on_chain_storage_version(), 1);
+		assert_eq!(Voters::<Test>::count(), 1);
+		assert_eq!(VotingSystem::voters(id), Some(voter));
+		assert_eq!(VotingSystem::blinded_signatures(id, 2), Some(blinded_signature));
+		assert_eq!(crate::BlindedSignatures::<Test>::iter_keys().count(), 1);
+		assert_eq!(VotingSystem::signed_voter_count(2), 1);
+	})
+}
+
+#[test]
+fn non_cooperating_candidates_are_excluded_after_the_deadline() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2, 3];
+	new_test_ext_w_candidate(root_key, candidates).execute_with(|| {
+		// with
+		let ca = root_key;
+		let candidate = 2;
+		let voter = 5;
+		let voter_id = VotingSystem::voter_id(&get_default_blinded_pubkey());
+
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_ok!(VotingSystem::add_voter(
+			RuntimeOrigin::signed(ca),
+			get_default_blinded_pubkey(),
+			vec![],
+			vec![],
+			true
+		));
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+
+		// when
+		// The candidates have until the deadline to sign, and can only be blamed once they have
+		// the messages of every voter
+		assert_noop!(
+			VotingSystem::exclude_candidate(RuntimeOrigin::signed(ca), 1),
+			Error::<Test>::SigningDeadlineNotReached
+		);
+		System::set_block_number(11);
+		assert_noop!(
+			VotingSystem::exclude_candidate(RuntimeOrigin::signed(ca), 1),
+			Error::<Test>::BlindedMessagesMissing
+		);
+		let blindings = register_blinded_messages(ca, voter_id, voter);
+
+		// Only candidate 2 signs the voter
+		assert_ok!(VotingSystem::biased_signing(
+			RuntimeOrigin::signed(candidate),
+			candidate,
+			voter_id,
+			blind_sign(candidate, &blinded_message_of(&blindings, candidate))
+		));
+		assert_noop!(
+			VotingSystem::change_phase(RuntimeOrigin::signed(ca)),
+			Error::<Test>::InvalidPhaseChange
+		);
+		assert_noop!(
+			VotingSystem::exclude_candidate(RuntimeOrigin::signed(2), 1),
+			Error::<Test>::SenderNotCA
+		);
+		assert_noop!(
+			VotingSystem::exclude_candidate(RuntimeOrigin::signed(ca), candidate),
+			Error::<Test>::CandidateSignedAllVoters
+		);
+		assert_ok!(VotingSystem::exclude_candidate(RuntimeOrigin::signed(ca), 1));
+		System::assert_last_event(
+			Event::CandidateExcluded { candidate: 1, signed: 0, voters: 1 }.into(),
+		);
+		assert_ok!(VotingSystem::exclude_candidate(RuntimeOrigin::signed(ca), 3));
+
+		// then
+		// The exclusion is on record and the excluded candidates no longer sign
+		assert_eq!(
+			VotingSystem::excluded_candidate(1),
+			Some(Exclusion { excluded: 11, signed: 0, voters: 1 })
+		);
+		assert_noop!(
+			VotingSystem::exclude_candidate(RuntimeOrigin::signed(ca), 1),
+			Error::<Test>::CandidateExcluded
+		);
+		assert_noop!(
+			VotingSystem::biased_signing(
+				RuntimeOrigin::signed(1),
+				1,
+				voter_id,
+				BoundedVec::try_from(vec![1, 2, 3]).unwrap()
+			),
+			Error::<Test>::CandidateExcluded
+		);
+
+		// Voting starts and ballots only carry the signature of candidate 2
+		assert_eq!(VotingSystem::signing_candidates_count(), 1);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_noop!(
+			VotingSystem::vote(
+				RuntimeOrigin::signed(voter),
+				1,
+				vec![(1, candidate_signature(1, voter))]
+			),
+			Error::<Test>::InvalidBlindSignatures
+		);
+		assert_ok!(VotingSystem::vote(
+			RuntimeOrigin::signed(voter),
+			1,
+			vec![(candidate, candidate_signature(candidate, voter))]
+		));
+	})
+}
+
+#[test]
+fn voters_without_blinded_messages_are_dropped_after_the_deadline() {
+	let root_key = 1;
+	let candidates: Vec<<Test as frame_system::Config>::AccountId> = vec![1, 2];
+	new_test_ext_w_candidate(root_key, candidates.clone()).execute_with(|| {
+		// with two voters, only the first one supplies their blinded messages
+		let ca = root_key;
+		let voter = 5;
+		let voter_id = VotingSystem::voter_id(&get_default_blinded_pubkey());
+		let absent_id = VotingSystem::voter_id(&[3, 2, 1]);
+
+		System::set_block_number(1);
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		for blinded_pubkey in [get_default_blinded_pubkey(), vec![3, 2, 1]] {
+			assert_ok!(VotingSystem::add_voter(
+				RuntimeOrigin::signed(ca),
+				blinded_pubkey,
+				vec![],
+				vec![],
+				true
+			));
+		}
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		let blindings = register_blinded_messages(ca, voter_id, voter);
+		for candidate in candidates {
+			assert_ok!(VotingSystem::biased_signing(
+				RuntimeOrigin::signed(candidate),
+				candidate,
+				voter_id,
+				blind_sign(candidate, &blinded_message_of(&blindings, candidate))
+			));
+		}
+		assert_noop!(
+			VotingSystem::change_phase(RuntimeOrigin::signed(ca)),
+			Error::<Test>::InvalidPhaseChange
+		);
+
+		// when
+		assert_noop!(
+			VotingSystem::drop_voter(RuntimeOrigin::signed(ca), absent_id),
+			Error::<Test>::SigningDeadlineNotReached
+		);
+		System::set_block_number(11);
+		assert_noop!(
+			VotingSystem::drop_voter(RuntimeOrigin::signed(2), absent_id),
+			Error::<Test>::SenderNotCA
+		);
+		assert_noop!(
+			VotingSystem::drop_voter(RuntimeOrigin::signed(ca), voter_id),
+			Error::<Test>::BlindedMessagesAlreadyRegistered
+		);
+		assert_ok!(VotingSystem::drop_voter(RuntimeOrigin::signed(ca), absent_id));
+
+		// then
+		System::assert_last_event(Event::VoterDropped { voter: absent_id }.into());
+		assert_eq!(VotingSystem::voters(absent_id), None);
+		assert_eq!(Voters::<Test>::count(), 1);
+		assert_noop!(
+			VotingSystem::drop_voter(RuntimeOrigin::signed(ca), absent_id),
+			Error::<Test>::VoterDoesNotExist
+		);
+		// Every remaining voter is signed, voting can start
+		assert_ok!(VotingSystem::change_phase(RuntimeOrigin::signed(ca)));
+		assert_eq!(VotingSystem::get_phase(), Some(Voting));
+	})
+}
+
+#[test]
+fn try_state_detects_broken_invariants() {
+	let root_key = 1;
+	new_test_ext(root_key).execute_with(|| {
+		assert_ok!(VotingSystem::do_try_state());
+
+		CandidatesCount::<Test>::put(42);
+		assert_eq!(
+			VotingSystem::do_try_state(),
+			Err("CandidatesCount does not match the number of candidates")
+		);
+		CandidatesCount::<Test>::put(10);
+
+		let voter = Voter {
+			blinded_pubkey: get_default_blinded_pubkey(),
+			signed_blinded_pubkey: vec![],
+			is_eligible: true,
+			personal_data_hash: vec![],
+		};
+		Voters::<Test>::insert(H256::default(), voter);
+		assert_eq!(
+			VotingSystem::do_try_state(),
+			Err("A voter is not stored under the id of their blinded key")
+		);
+		Voters::<Test>::remove(H256::default());
+	})
+}
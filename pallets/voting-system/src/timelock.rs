@@ -0,0 +1,259 @@
+//! Time-locked ballots.
+//!
+//! Ballots are normally counted once the CA reveals the ballot private key during counting, which
+//! lets the CA delay or withhold the result. An election can instead encrypt its ballots to a
+//! future round of a BLS randomness beacon, such as drand, the way tlock does:
+//!
+//! - The CA picks the beacon round with `set_timelock_round` during initialization.
+//! - Voters encrypt their commitment to the round with [`encrypt`] and cast it with
+//!   `vote_timelocked`. Nobody, the CA included, can read a ballot before the beacon signed the
+//!   round.
+//! - Once the beacon published the signature of the round, anybody submits it with
+//!   `submit_beacon_signature`. The signature is verified against the public key of the beacon and
+//!   is the decryption key of every ballot, `tally_timelocked_step` decrypts and counts the ballots
+//!   on chain. `reveal_ballot_key` is rejected.
+//!
+//! The beacon signs a round with a BLS signature on G1 of BLS12-381 over `sha256(round)`, hashed to
+//! the curve as in RFC 9380, and publishes its public key on G2. This is the scheme of the drand
+//! quicknet. Encryption is the identity based encryption of Boneh and Franklin with the
+//! Fujisaki-Okamoto transform, the identity being the round: for the identity `Q = H(round)`,
+//! the beacon key `K` and a random `sigma`,
+//!
+//! - `r = H3(sigma, m)` and `U = r·G2`,
+//! - `V = sigma xor H2(e(Q, K)^r)`,
+//! - `W = m xor H4(sigma)`.
+//!
+//! The round signature `s = k·Q` gives `e(s, U) = e(Q, K)^r`, which opens `sigma` and then `m`.
+//! Recomputing `U` from them rejects every ciphertext that was not built this way. `H2`, `H3` and
+//! `H4` are SHA-256 with the domain tags of tlock.
+//!
+//! The runtime plugs in the beacon through [`TimelockBeacon`], see [`BlsBeacon`]. Without a
+//! beacon, `()`, the timelock mode can not be switched on.
+
+use ark_bls12_381::{g1, Bls12_381, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{
+	hashing::{curve_maps::wb::WBMap, map_to_curve_hasher::MapToCurveBasedHasher, HashToCurve},
+	pairing::Pairing,
+	AffineRepr, CurveGroup,
+};
+use ark_ff::{field_hashers::DefaultFieldHasher, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use codec::{Decode, Encode, MaxEncodedLen};
+use core::marker::PhantomData;
+use frame_support::{inherent::Vec, traits::Get, RuntimeDebug};
+use rand_chacha::rand_core::RngCore;
+use scale_info::TypeInfo;
+use sha2::{Digest, Sha256};
+
+/// Size of a compressed round signature, a point on G1.
+pub const SIGNATURE_BYTES: usize = 48;
+
+/// Size of a compressed beacon public key, a point on G2.
+pub const PUBLIC_KEY_BYTES: usize = 96;
+
+/// Size of a time-locked message.
+pub const MESSAGE_BYTES: usize = 32;
+
+/// Compressed public key of a beacon.
+pub type BeaconPublicKey = [u8; PUBLIC_KEY_BYTES];
+
+/// Domain separation tag the rounds are hashed to G1 with, as used by the drand quicknet.
+const SIGNATURE_DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// Message encrypted to a beacon round, see the module docs.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct TimelockCiphertext {
+	/// `r·G2`, compressed
+	pub u: [u8; PUBLIC_KEY_BYTES],
+	/// `sigma` masked with the pairing of the round and the beacon key
+	pub v: [u8; MESSAGE_BYTES],
+	/// The message masked with `sigma`
+	pub w: [u8; MESSAGE_BYTES],
+}
+
+/// Randomness beacon the ballots of a timelock election are encrypted to
+pub trait TimelockBeacon {
+	/// Whether a beacon is configured, the timelock mode can only be switched on if there is one
+	fn is_available() -> bool {
+		true
+	}
+
+	/// Public key the ballots are encrypted with
+	fn public_key() -> Option<BeaconPublicKey>;
+
+	/// Check `signature` is the beacon signature of `round`
+	fn verify(round: u64, signature: &[u8]) -> bool;
+}
+
+/// No beacon, the timelock mode is unavailable
+impl TimelockBeacon for () {
+	fn is_available() -> bool {
+		false
+	}
+
+	fn public_key() -> Option<BeaconPublicKey> {
+		None
+	}
+
+	fn verify(_round: u64, _signature: &[u8]) -> bool {
+		false
+	}
+}
+
+/// BLS beacon with the public key `PublicKey`, signing rounds like the drand quicknet
+pub struct BlsBeacon<PublicKey>(PhantomData<PublicKey>);
+
+impl<PublicKey: Get<BeaconPublicKey>> TimelockBeacon for BlsBeacon<PublicKey> {
+	fn public_key() -> Option<BeaconPublicKey> {
+		Some(PublicKey::get())
+	}
+
+	fn verify(round: u64, signature: &[u8]) -> bool {
+		verify(&PublicKey::get(), round, signature)
+	}
+}
+
+/// Check `signature` is the signature of `round` by the beacon with `public_key`:
+/// `e(signature, G2) = e(H(round), public_key)`
+pub fn verify(public_key: &BeaconPublicKey, round: u64, signature: &[u8]) -> bool {
+	let check = || -> Option<bool> {
+		let key = decode_public_key(public_key)?;
+		let signature = decode_signature(signature)?;
+		let identity = round_identity(round)?;
+		Some(
+			Bls12_381::pairing(signature, G2Affine::generator()) ==
+				Bls12_381::pairing(identity, key),
+		)
+	};
+	check().unwrap_or(false)
+}
+
+/// Encrypt `message` to `round` of the beacon with `public_key`
+pub fn encrypt(
+	public_key: &BeaconPublicKey,
+	round: u64,
+	message: &[u8; MESSAGE_BYTES],
+	rng: &mut impl RngCore,
+) -> Option<TimelockCiphertext> {
+	let key = decode_public_key(public_key)?;
+	let identity = round_identity(round)?;
+
+	let mut sigma = [0; MESSAGE_BYTES];
+	rng.fill_bytes(&mut sigma);
+	let r = h3(&sigma, message);
+	let u = (G2Affine::generator() * r).into_affine();
+	let mask = h2(&Bls12_381::pairing(identity, (key * r).into_affine()))?;
+
+	Some(TimelockCiphertext {
+		u: encode_point(&u)?,
+		v: xor(&sigma, &mask),
+		w: xor(message, &h4(&sigma)),
+	})
+}
+
+/// Decrypt `ciphertext` with the beacon signature of the round it was encrypted to. Returns
+/// nothing for any other signature and for ciphertexts that were not built by [`encrypt`]
+pub fn decrypt(signature: &[u8], ciphertext: &TimelockCiphertext) -> Option<[u8; MESSAGE_BYTES]> {
+	let signature = decode_signature(signature)?;
+	let u = G2Affine::deserialize_compressed(&ciphertext.u[..]).ok()?;
+
+	let sigma = xor(&ciphertext.v, &h2(&Bls12_381::pairing(signature, u))?);
+	let message = xor(&ciphertext.w, &h4(&sigma));
+
+	// Fujisaki-Okamoto, the ciphertext must be the encryption of `message` with `sigma`
+	if (G2Affine::generator() * h3(&sigma, &message)).into_affine() == u {
+		Some(message)
+	} else {
+		None
+	}
+}
+
+/// Whether `ciphertext` holds a point of G2, the cheap check of a time-locked ballot
+pub fn is_valid_ciphertext(ciphertext: &TimelockCiphertext) -> bool {
+	G2Affine::deserialize_compressed(&ciphertext.u[..]).is_ok()
+}
+
+/// Message of a time-locked ballot, the little endian commitment padded with zeros
+pub fn commitment_message(commitment: u32) -> [u8; MESSAGE_BYTES] {
+	let mut message = [0; MESSAGE_BYTES];
+	message[..4].copy_from_slice(&commitment.to_le_bytes());
+	message
+}
+
+/// Commitment of a decrypted time-locked ballot, nothing if the padding is not zero
+pub fn message_commitment(message: &[u8; MESSAGE_BYTES]) -> Option<u32> {
+	if message[4..].iter().any(|byte| *byte != 0) {
+		return None
+	}
+	let mut commitment = [0; 4];
+	commitment.copy_from_slice(&message[..4]);
+	Some(u32::from_le_bytes(commitment))
+}
+
+/// Public key of a beacon with the little endian encoded `secret`, for test beacons
+pub fn public_key(secret: &[u8; 32]) -> Option<BeaconPublicKey> {
+	encode_point(&(G2Affine::generator() * Fr::from_le_bytes_mod_order(secret)).into_affine())
+}
+
+/// Signature of `round` by a beacon with the little endian encoded `secret`, for test beacons
+pub fn sign(secret: &[u8; 32], round: u64) -> Option<[u8; SIGNATURE_BYTES]> {
+	let identity = round_identity(round)?;
+	encode_point(&(identity * Fr::from_le_bytes_mod_order(secret)).into_affine())
+}
+
+/// Identity of a round, the message `sha256(round)` the beacon signs hashed to G1
+fn round_identity(round: u64) -> Option<G1Affine> {
+	let message = Sha256::digest(round.to_be_bytes());
+	MapToCurveBasedHasher::<G1Projective, DefaultFieldHasher<Sha256, 128>, WBMap<g1::Config>>::new(
+		SIGNATURE_DST,
+	)
+	.ok()?
+	.hash(&message)
+	.ok()
+}
+
+fn decode_signature(signature: &[u8]) -> Option<G1Affine> {
+	if signature.len() != SIGNATURE_BYTES {
+		return None
+	}
+	G1Affine::deserialize_compressed(signature).ok()
+}
+
+fn decode_public_key(public_key: &BeaconPublicKey) -> Option<G2Affine> {
+	G2Affine::deserialize_compressed(&public_key[..])
+		.ok()
+		.filter(|key| !key.is_zero())
+}
+
+fn encode_point<const N: usize>(point: &impl CanonicalSerialize) -> Option<[u8; N]> {
+	let mut bytes = Vec::new();
+	point.serialize_compressed(&mut bytes).ok()?;
+	bytes.try_into().ok()
+}
+
+fn h2(gid: &impl CanonicalSerialize) -> Option<[u8; MESSAGE_BYTES]> {
+	let mut bytes = Vec::new();
+	gid.serialize_compressed(&mut bytes).ok()?;
+	Some(Sha256::new().chain_update(b"IBE-H2").chain_update(bytes).finalize().into())
+}
+
+fn h3(sigma: &[u8; MESSAGE_BYTES], message: &[u8; MESSAGE_BYTES]) -> Fr {
+	let hash = Sha256::new()
+		.chain_update(b"IBE-H3")
+		.chain_update(sigma)
+		.chain_update(message)
+		.finalize();
+	Fr::from_be_bytes_mod_order(&hash)
+}
+
+fn h4(sigma: &[u8; MESSAGE_BYTES]) -> [u8; MESSAGE_BYTES] {
+	Sha256::new().chain_update(b"IBE-H4").chain_update(sigma).finalize().into()
+}
+
+fn xor(left: &[u8; MESSAGE_BYTES], right: &[u8; MESSAGE_BYTES]) -> [u8; MESSAGE_BYTES] {
+	let mut out = [0; MESSAGE_BYTES];
+	for (out, (left, right)) in out.iter_mut().zip(left.iter().zip(right)) {
+		*out = left ^ right;
+	}
+	out
+}
@@ -0,0 +1,318 @@
+//! Transaction pool checks of the voting system calls.
+//!
+//! The election duties pay no fee, so nothing stops an account from flooding the pool with calls
+//! that fail, some of them only after an expensive RSA verification. [`CheckVotingCall`] rejects
+//! these calls before they reach a block:
+//!
+//! - the call is let through by the call filter, see `Pallet::is_call_allowed`, and allowed in the
+//!   current phase and election mode,
+//! - the sender has the role the call requires: the CA, the candidate or a voter the revote policy
+//!   still accepts a ballot from,
+//! - the variable sized arguments are no longer than `MaxPayloadLength`, and a signature set holds
+//!   one signature of the right size for every candidate,
+//! - the signatures of a ballot are valid signatures of the candidates over the sender.
+//!
+//! The signatures are only verified once the cheap checks passed. A ballot with a forged signature
+//! set never enters the pool and can not be included in a block, so the fee exemption of ballots,
+//! see the `fees` module, and the priority of first ballots only ever apply to ballots that verify.
+//! Ballots replacing an earlier ballot get a lower priority than first ballots, so voters that did
+//! not vote yet go first when blocks are full.
+
+use crate::{
+	credentials, timelock, BlindSignature, Call, Candidates, Config, ElectionPhase,
+	ExcludedCandidates, Pallet, Voter,
+};
+use codec::{Decode, Encode};
+use core::marker::PhantomData;
+use frame_support::{inherent::Vec, pallet_prelude::*, traits::IsSubType};
+use sp_runtime::{
+	traits::{DispatchInfoOf, SignedExtension},
+	transaction_validity::{TransactionPriority, TransactionValidity, TransactionValidityError},
+};
+
+/// Priority of a first ballot over a ballot replacing an earlier one
+pub const FIRST_BALLOT_PRIORITY: TransactionPriority = 1 << 20;
+
+/// Codes of the `InvalidTransaction::Custom` errors of rejected calls
+#[derive(Clone, Copy, Eq, PartialEq, RuntimeDebug)]
+#[repr(u8)]
+pub enum VotingCallError {
+	/// The call is not allowed in the current phase or election mode
+	WrongPhase = 0,
+	/// The sender does not have the role the call requires
+	NotAuthorized = 1,
+	/// A variable sized argument is longer than `MaxPayloadLength`
+	PayloadTooLarge = 2,
+	/// The signature set does not hold one valid signature of every candidate
+	InvalidSignatureSet = 3,
+	/// The revote policy does not accept another ballot of the sender
+	AlreadyVoted = 4,
+	/// The call filter blocks the call in the current phase, or the election is paused
+	Filtered = 5,
+	/// The credential of a ballot is not a valid ciphertext
+	InvalidCredential = 6,
+	/// The time-locked ballot is not a ciphertext of the timelock scheme
+	InvalidTimelockedBallot = 7,
+}
+
+impl From<VotingCallError> for InvalidTransaction {
+	fn from(error: VotingCallError) -> Self {
+		InvalidTransaction::Custom(error as u8)
+	}
+}
+
+/// `SignedExtension` rejecting voting system calls that would fail, see the module docs.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckVotingCall<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> CheckVotingCall<T> {
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> Default for CheckVotingCall<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config + Send + Sync> core::fmt::Debug for CheckVotingCall<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(f, "CheckVotingCall")
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for CheckVotingCall<T>
+where
+	<T as frame_system::Config>::RuntimeCall: IsSubType<Call<T>>,
+{
+	const IDENTIFIER: &'static str = "CheckVotingCall";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::RuntimeCall;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		match call.is_sub_type() {
+			Some(call) => check_call::<T>(who, call)
+				.map(|priority| ValidTransaction { priority, ..Default::default() })
+				.map_err(|error| InvalidTransaction::from(error).into()),
+			None => Ok(ValidTransaction::default()),
+		}
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.validate(who, call, info, len).map(|_| ())
+	}
+}
+
+/// Check a voting system call of `who` and return its priority
+fn check_call<T: Config>(
+	who: &T::AccountId,
+	call: &Call<T>,
+) -> Result<TransactionPriority, VotingCallError> {
+	use ElectionPhase::*;
+
+	// The runtime call filter would fail the call on dispatch, after it paid its fee
+	ensure!(Pallet::<T>::is_call_allowed(call), VotingCallError::Filtered);
+
+	let phase = Pallet::<T>::get_phase();
+	let in_phase = |expected: ElectionPhase| phase.as_ref() == Some(&expected);
+	let is_ca = Some(who.clone()) == Pallet::<T>::get_ca();
+
+	match call {
+		Call::vote { signature_set, .. } => {
+			ensure!(in_phase(Voting), VotingCallError::WrongPhase);
+			ensure!(Pallet::<T>::credential_key().is_none(), VotingCallError::WrongPhase);
+			ensure!(Pallet::<T>::timelock_round().is_none(), VotingCallError::WrongPhase);
+			let previous = Pallet::<T>::get_ballot(who.clone());
+			Pallet::<T>::check_revote_policy(previous.as_ref().map(|ballot| ballot.nonce))
+				.map_err(|_| VotingCallError::AlreadyVoted)?;
+			check_signature_set::<T>(signature_set)?;
+			verify_signature_set::<T>(who, signature_set)?;
+			// Replacing a ballot is never urgent
+			return Ok(if previous.is_some() { 0 } else { FIRST_BALLOT_PRIORITY })
+		},
+		Call::vote_timelocked { ciphertext, signature_set } => {
+			ensure!(in_phase(Voting), VotingCallError::WrongPhase);
+			ensure!(Pallet::<T>::timelock_round().is_some(), VotingCallError::WrongPhase);
+			ensure!(
+				timelock::is_valid_ciphertext(ciphertext),
+				VotingCallError::InvalidTimelockedBallot
+			);
+			let previous = Pallet::<T>::timelocked_ballot(who.clone());
+			Pallet::<T>::check_revote_policy(previous.as_ref().map(|ballot| ballot.nonce))
+				.map_err(|_| VotingCallError::AlreadyVoted)?;
+			check_signature_set::<T>(signature_set)?;
+			verify_signature_set::<T>(who, signature_set)?;
+			return Ok(if previous.is_some() { 0 } else { FIRST_BALLOT_PRIORITY })
+		},
+		Call::vote_with_credential { credential, signature_set, .. } => {
+			ensure!(in_phase(Voting), VotingCallError::WrongPhase);
+			ensure!(Pallet::<T>::credential_key().is_some(), VotingCallError::WrongPhase);
+			ensure!(
+				credentials::is_valid_ciphertext(credential),
+				VotingCallError::InvalidCredential
+			);
+			check_signature_set::<T>(signature_set)?;
+			verify_signature_set::<T>(who, signature_set)?;
+			// Whether a credential ballot replaces another one is only known to the tally
+			return Ok(FIRST_BALLOT_PRIORITY)
+		},
+		Call::update_candidate_info { candidate, name, pubkey } => {
+			ensure!(who == candidate, VotingCallError::NotAuthorized);
+			ensure!(Pallet::<T>::candidates_open(), VotingCallError::WrongPhase);
+			ensure!(Candidates::<T>::contains_key(candidate), VotingCallError::NotAuthorized);
+			check_payload::<T>(&[name.as_bytes(), pubkey])?;
+		},
+		Call::nominate_candidate { name, pubkey } => {
+			ensure!(Pallet::<T>::candidates_open(), VotingCallError::WrongPhase);
+			check_payload::<T>(&[name.as_bytes(), pubkey])?;
+		},
+		Call::withdraw_candidate {} => {
+			ensure!(Pallet::<T>::candidates_open(), VotingCallError::WrongPhase);
+		},
+		Call::biased_signing { candidate, .. } | Call::biased_signing_batch { candidate, .. } => {
+			ensure!(who == candidate, VotingCallError::NotAuthorized);
+			ensure!(in_phase(BiasedSigner), VotingCallError::WrongPhase);
+			ensure!(Candidates::<T>::contains_key(candidate), VotingCallError::NotAuthorized);
+			ensure!(
+				!ExcludedCandidates::<T>::contains_key(candidate),
+				VotingCallError::NotAuthorized
+			);
+		},
+		Call::add_voter { blinded_pubkey, signed_blinded_pubkey, personal_data_hash, .. } => {
+			ensure!(is_ca, VotingCallError::NotAuthorized);
+			ensure!(in_phase(Registration), VotingCallError::WrongPhase);
+			check_payload::<T>(&[blinded_pubkey, signed_blinded_pubkey, personal_data_hash])?;
+		},
+		Call::add_voters { voters } => {
+			ensure!(is_ca, VotingCallError::NotAuthorized);
+			ensure!(in_phase(Registration), VotingCallError::WrongPhase);
+			voters.iter().try_for_each(|voter: &Voter| {
+				check_payload::<T>(&[
+					&voter.blinded_pubkey,
+					&voter.signed_blinded_pubkey,
+					&voter.personal_data_hash,
+				])
+			})?;
+		},
+		Call::change_phase {} | Call::pause {} | Call::unpause {} => {
+			ensure!(is_ca, VotingCallError::NotAuthorized);
+		},
+		Call::approve_candidate { .. } | Call::set_revote_policy { .. } => {
+			ensure!(is_ca, VotingCallError::NotAuthorized);
+			ensure!(Pallet::<T>::candidates_open(), VotingCallError::WrongPhase);
+		},
+		Call::set_credential_key { .. } => {
+			ensure!(is_ca, VotingCallError::NotAuthorized);
+			ensure!(in_phase(Initialization), VotingCallError::WrongPhase);
+		},
+		Call::register_blinded_messages { .. } => {
+			ensure!(is_ca, VotingCallError::NotAuthorized);
+			ensure!(in_phase(BiasedSigner), VotingCallError::WrongPhase);
+		},
+		Call::exclude_candidate { .. } | Call::drop_voter { .. } => {
+			ensure!(is_ca, VotingCallError::NotAuthorized);
+			ensure!(in_phase(BiasedSigner), VotingCallError::WrongPhase);
+		},
+		Call::set_timelock_round { .. } => {
+			ensure!(is_ca, VotingCallError::NotAuthorized);
+			ensure!(in_phase(Initialization), VotingCallError::WrongPhase);
+		},
+		Call::register_credential { .. } => {
+			ensure!(is_ca, VotingCallError::NotAuthorized);
+			ensure!(in_phase(Registration), VotingCallError::WrongPhase);
+		},
+		Call::cancel_election { .. } => {
+			ensure!(is_ca, VotingCallError::NotAuthorized);
+			ensure!(!in_phase(Cancelled), VotingCallError::WrongPhase);
+		},
+		Call::tally_step { .. } => {
+			ensure!(in_phase(Counting), VotingCallError::WrongPhase);
+			ensure!(Pallet::<T>::tally_open(), VotingCallError::WrongPhase);
+			ensure!(Pallet::<T>::winner().is_none(), VotingCallError::WrongPhase);
+		},
+		Call::submit_beacon_signature { round, signature } => {
+			ensure!(in_phase(Counting), VotingCallError::WrongPhase);
+			ensure!(Pallet::<T>::timelock_round() == Some(*round), VotingCallError::WrongPhase);
+			ensure!(Pallet::<T>::beacon_signature().is_none(), VotingCallError::WrongPhase);
+			check_payload::<T>(&[signature])?;
+		},
+		Call::file_dispute { .. } => {
+			ensure!(in_phase(Counting), VotingCallError::WrongPhase);
+		},
+		Call::reveal_ballot_key { .. } |
+		Call::submit_credential_tally { .. } |
+		Call::resolve_dispute { .. } => {
+			ensure!(is_ca, VotingCallError::NotAuthorized);
+			ensure!(in_phase(Counting), VotingCallError::WrongPhase);
+		},
+		_ => {},
+	}
+	Ok(0)
+}
+
+/// Check every payload is at most `MaxPayloadLength` bytes long
+fn check_payload<T: Config>(payloads: &[&[u8]]) -> Result<(), VotingCallError> {
+	let max = T::MaxPayloadLength::get() as usize;
+	ensure!(payloads.iter().all(|payload| payload.len() <= max), VotingCallError::PayloadTooLarge);
+	Ok(())
+}
+
+/// Check the shape of a signature set without verifying any signature: one signature of at most
+/// `SignatureLength` bytes by every candidate that was not excluded
+pub(crate) fn check_signature_set<T: Config>(
+	signature_set: &[(T::AccountId, BlindSignature)],
+) -> Result<(), VotingCallError> {
+	let candidate_count = Pallet::<T>::signing_candidates_count();
+	ensure!(signature_set.len() as u64 == candidate_count, VotingCallError::InvalidSignatureSet);
+
+	let max = T::SignatureLength::get() as usize;
+	ensure!(
+		signature_set.iter().all(|(_, signature)| signature.signature.len() <= max),
+		VotingCallError::InvalidSignatureSet
+	);
+
+	let mut signers: Vec<_> = signature_set.iter().map(|(candidate, _)| candidate).collect();
+	signers.sort();
+	signers.dedup();
+	ensure!(signers.len() == signature_set.len(), VotingCallError::InvalidSignatureSet);
+	ensure!(
+		signers.into_iter().all(|candidate| {
+			Candidates::<T>::contains_key(candidate) &&
+				!ExcludedCandidates::<T>::contains_key(candidate)
+		}),
+		VotingCallError::InvalidSignatureSet
+	);
+	Ok(())
+}
+
+/// Verify every signature of a well formed signature set over the account of `voter`, the same
+/// verification the dispatch of the ballot runs
+fn verify_signature_set<T: Config>(
+	voter: &T::AccountId,
+	signature_set: &[(T::AccountId, BlindSignature)],
+) -> Result<(), VotingCallError> {
+	Pallet::<T>::verify_signature_set(voter, signature_set.to_vec())
+		.map(|_| ())
+		.map_err(|_| VotingCallError::InvalidSignatureSet)
+}
@@ -0,0 +1,607 @@
+//! Weights for pallet_voting_system
+//!
+//! Every call is benchmarked in the `benchmarking` module, regenerate this file from the runtime
+//! with `node-template benchmark pallet --chain dev --pallet pallet_voting_system --extrinsic '*'
+//! --output pallets/voting-system/src/weights.rs`.
+
+use core::marker::PhantomData;
+use frame_support::{
+	traits::Get,
+	weights::{constants::RocksDbWeight, Weight},
+};
+
+/// Weight functions needed for pallet_voting_system.
+pub trait WeightInfo {
+	fn add_voters(n: u32) -> Weight;
+	fn biased_signing_batch(n: u32) -> Weight;
+	fn tally_step(n: u32) -> Weight;
+	fn file_dispute() -> Weight;
+	fn register_blinded_messages(n: u32) -> Weight;
+	fn drop_voter() -> Weight;
+	fn vote(n: u32) -> Weight;
+	fn vote_timelocked(n: u32) -> Weight;
+	fn tally_timelocked_step(n: u32) -> Weight;
+	fn vote_with_credential(n: u32) -> Weight;
+	fn submit_credential_tally(n: u32) -> Weight;
+	fn change_phase(c: u32) -> Weight;
+	fn add_voter() -> Weight;
+	fn update_candidate_info(c: u32) -> Weight;
+	fn biased_signing() -> Weight;
+	fn nominate_candidate(c: u32) -> Weight;
+	fn approve_candidate(c: u32) -> Weight;
+	fn withdraw_candidate() -> Weight;
+	fn set_revote_policy() -> Weight;
+	fn set_credential_key() -> Weight;
+	fn register_credential() -> Weight;
+	fn reveal_ballot_key() -> Weight;
+	fn set_timelock_round() -> Weight;
+	fn submit_beacon_signature() -> Weight;
+	fn pause() -> Weight;
+	fn unpause() -> Weight;
+	fn cancel_election() -> Weight;
+	fn resolve_dispute() -> Weight;
+	fn exclude_candidate() -> Weight;
+}
+
+/// Weights for pallet_voting_system using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem CounterForVoters (r:n w:n)
+	// Storage: VotingSystem Voters (r:n w:n)
+	fn add_voters(n: u32) -> Weight {
+		Weight::from_ref_time(10_000_000)
+			.saturating_add(Weight::from_ref_time(5_000_000).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().reads(2 * n as u64))
+			.saturating_add(T::DbWeight::get().writes(2 * n as u64))
+	}
+	// Storage: VotingSystem Candidates (r:1 w:0)
+	// Storage: VotingSystem Voters (r:n w:0)
+	// Storage: VotingSystem BlindedSignatures (r:n w:n)
+	// Storage: VotingSystem SignedVoterCount (r:n w:n)
+	// Every item also pays for one RSA signature verification.
+	fn biased_signing_batch(n: u32) -> Weight {
+		Weight::from_ref_time(10_000_000)
+			.saturating_add(Weight::from_ref_time(400_000_000).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().reads(3 * n as u64))
+			.saturating_add(T::DbWeight::get().writes(2 * n as u64))
+	}
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem CredentialKey (r:1 w:0)
+	// Storage: VotingSystem BallotKeys (r:1 w:0)
+	// Storage: VotingSystem VoterWinner (r:1 w:1)
+	// Storage: VotingSystem TallyCursor (r:1 w:1)
+	// Storage: VotingSystem Ballots (r:n+1 w:0)
+	// Storage: VotingSystem CandidateVoteCount (r:n w:n)
+	// Storage: VotingSystem TallyLeader (r:n w:n)
+	fn tally_step(n: u32) -> Weight {
+		Weight::from_ref_time(10_000_000)
+			.saturating_add(Weight::from_ref_time(2_000_000).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().reads(3 * n as u64))
+			.saturating_add(T::DbWeight::get().writes(2))
+			.saturating_add(T::DbWeight::get().writes(2 * n as u64))
+	}
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem TallyCountedAt (r:1 w:0)
+	// Storage: VotingSystem Candidates (r:1 w:0)
+	// Storage: VotingSystem Ballots (r:1 w:0)
+	// Storage: VotingSystem CredentialKey (r:1 w:0)
+	// Storage: VotingSystem BallotLogVoters (r:1 w:0)
+	// Storage: VotingSystem Disputes (r:1 w:1)
+	// Storage: VotingSystem OpenDisputes (r:1 w:1)
+	fn file_dispute() -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(T::DbWeight::get().reads(8))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem Voters (r:1 w:0)
+	// Storage: VotingSystem BlindedMessages (r:1 w:n)
+	// Storage: VotingSystem CandidatesCount (r:1 w:0)
+	// Storage: VotingSystem Candidates (r:n w:0)
+	// Storage: VotingSystem BlindedMessageCount (r:1 w:1)
+	fn register_blinded_messages(n: u32) -> Weight {
+		Weight::from_ref_time(12_000_000)
+			.saturating_add(Weight::from_ref_time(1_000_000).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().reads(n.into()))
+			.saturating_add(T::DbWeight::get().writes(1))
+			.saturating_add(T::DbWeight::get().writes(n.into()))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem PhaseChanges (r:1 w:0)
+	// Storage: VotingSystem Voters (r:1 w:1)
+	// Storage: VotingSystem CounterForVoters (r:1 w:1)
+	// Storage: VotingSystem BlindedMessages (r:1 w:0)
+	// Storage: VotingSystem VoterCredentials (r:1 w:1)
+	// Storage: VotingSystem CounterForVoterCredentials (r:1 w:1)
+	fn drop_voter() -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(T::DbWeight::get().reads(8))
+			.saturating_add(T::DbWeight::get().writes(4))
+	}
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem CredentialKey (r:1 w:0)
+	// Storage: VotingSystem TimelockRound (r:1 w:0)
+	// Storage: VotingSystem Ballots (r:1 w:1)
+	// Storage: VotingSystem RevotingPolicy (r:1 w:0)
+	// Storage: VotingSystem CandidatesCount (r:1 w:0)
+	// Storage: VotingSystem CounterForExcludedCandidates (r:1 w:0)
+	// Storage: VotingSystem ExcludedCandidates (r:n w:0)
+	// Storage: VotingSystem Candidates (r:n w:0)
+	// Storage: VotingSystem BallotLogLength (r:1 w:1)
+	// Storage: VotingSystem BallotLog (r:0 w:1)
+	// Storage: VotingSystem BallotLogIndex (r:0 w:1)
+	// Storage: VotingSystem BallotLogVoters (r:0 w:1)
+	// Storage: VotingSystem BallotLogNodes (r:32 w:33)
+	// Every signature of the set also pays for one RSA signature verification. The log nodes are
+	// bounded by the height of a log of up to 2^32 ballots.
+	fn vote(n: u32) -> Weight {
+		Weight::from_ref_time(20_000_000)
+			.saturating_add(Weight::from_ref_time(400_000_000).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(40))
+			.saturating_add(T::DbWeight::get().reads(2 * n as u64))
+			.saturating_add(T::DbWeight::get().writes(38))
+	}
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem TimelockRound (r:1 w:0)
+	// Storage: VotingSystem TimelockedBallots (r:1 w:1)
+	// Storage: VotingSystem RevotingPolicy (r:1 w:0)
+	// Storage: VotingSystem CandidatesCount (r:1 w:0)
+	// Storage: VotingSystem CounterForExcludedCandidates (r:1 w:0)
+	// Storage: VotingSystem ExcludedCandidates (r:n w:0)
+	// Storage: VotingSystem Candidates (r:n w:0)
+	// Storage: VotingSystem BallotLogLength (r:1 w:1)
+	// Storage: VotingSystem BallotLog (r:0 w:1)
+	// Storage: VotingSystem BallotLogIndex (r:0 w:1)
+	// Storage: VotingSystem BallotLogVoters (r:0 w:1)
+	// Storage: VotingSystem BallotLogNodes (r:32 w:33)
+	// As `vote`, and the ciphertext is decompressed to a point of G2.
+	fn vote_timelocked(n: u32) -> Weight {
+		Weight::from_ref_time(120_000_000)
+			.saturating_add(Weight::from_ref_time(400_000_000).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(39))
+			.saturating_add(T::DbWeight::get().reads(2 * n as u64))
+			.saturating_add(T::DbWeight::get().writes(38))
+	}
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem TimelockRound (r:1 w:0)
+	// Storage: VotingSystem BeaconSignature (r:1 w:0)
+	// Storage: VotingSystem VoterWinner (r:1 w:1)
+	// Storage: VotingSystem TallyCursor (r:1 w:1)
+	// Storage: VotingSystem TimelockedBallots (r:n+1 w:0)
+	// Storage: VotingSystem CandidateVoteCount (r:n w:n)
+	// Storage: VotingSystem TallyLeader (r:n w:n)
+	// Storage: VotingSystem RejectedTimelockedBallots (r:n w:n)
+	// Every ballot pays for a pairing and a multiplication in G2 to decrypt it.
+	fn tally_timelocked_step(n: u32) -> Weight {
+		Weight::from_ref_time(10_000_000)
+			.saturating_add(Weight::from_ref_time(10_000_000_000).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().reads(3 * n as u64))
+			.saturating_add(T::DbWeight::get().writes(2))
+			.saturating_add(T::DbWeight::get().writes(2 * n as u64))
+	}
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem CredentialKey (r:1 w:0)
+	// Storage: VotingSystem CandidatesCount (r:1 w:0)
+	// Storage: VotingSystem CounterForExcludedCandidates (r:1 w:0)
+	// Storage: VotingSystem ExcludedCandidates (r:n w:0)
+	// Storage: VotingSystem Candidates (r:n w:0)
+	// Storage: VotingSystem CredentialBallotCount (r:1 w:1)
+	// Storage: VotingSystem CredentialBallots (r:0 w:1)
+	// Storage: VotingSystem BallotLogLength (r:1 w:1)
+	// Storage: VotingSystem BallotLog (r:0 w:1)
+	// Storage: VotingSystem BallotLogIndex (r:0 w:1)
+	// Storage: VotingSystem BallotLogVoters (r:0 w:1)
+	// Storage: VotingSystem BallotLogNodes (r:32 w:33)
+	// Every signature of the set also pays for one RSA signature verification. The log nodes are
+	// bounded by the height of a log of up to 2^32 ballots.
+	fn vote_with_credential(n: u32) -> Weight {
+		Weight::from_ref_time(25_000_000)
+			.saturating_add(Weight::from_ref_time(400_000_000).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(38))
+			.saturating_add(T::DbWeight::get().reads(2 * n as u64))
+			.saturating_add(T::DbWeight::get().writes(39))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem CredentialKey (r:1 w:0)
+	// Storage: VotingSystem VoterWinner (r:1 w:1)
+	// Storage: VotingSystem CredentialBallotCount (r:1 w:0)
+	// Storage: VotingSystem CounterForVoterCredentials (r:1 w:0)
+	// Storage: VotingSystem CredentialTallyTranscript (r:0 w:1)
+	// Storage: VotingSystem CandidateVoteCount (r:n w:n)
+	// Storage: VotingSystem TallyLeader (r:n w:n)
+	// Storage: VotingSystem TallyCountedAt (r:0 w:1)
+	fn submit_credential_tally(n: u32) -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(Weight::from_ref_time(2_000_000).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().reads(2 * n as u64))
+			.saturating_add(T::DbWeight::get().writes(3))
+			.saturating_add(T::DbWeight::get().writes(2 * n as u64))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Phase (r:1 w:1)
+	// Storage: VotingSystem PhaseChanges (r:0 w:1)
+	// Storage: VotingSystem CandidatesCount (r:1 w:0)
+	// Storage: VotingSystem Candidates (r:c w:0)
+	// Storage: VotingSystem CounterForVoters (r:1 w:0)
+	// Storage: VotingSystem ExcludedCandidates (r:c w:0)
+	// Storage: VotingSystem SignedVoterCount (r:c w:0)
+	// Storage: VotingSystem CandidateKeyFingerprints (r:0 w:c)
+	// Storage: VotingSystem TallyCountedAt (r:1 w:0)
+	// Storage: VotingSystem OpenDisputes (r:1 w:0)
+	// Storage: VotingSystem BallotLogLength (r:1 w:0)
+	// Storage: VotingSystem BallotLogNodes (r:32 w:0)
+	// Storage: VotingSystem PublishedBallotLog (r:0 w:1)
+	// The candidates are walked when biased signing starts and ends, the log root is published when
+	// voting ends.
+	fn change_phase(c: u32) -> Weight {
+		Weight::from_ref_time(20_000_000)
+			.saturating_add(Weight::from_ref_time(3_000_000).saturating_mul(c.into()))
+			.saturating_add(T::DbWeight::get().reads(41))
+			.saturating_add(T::DbWeight::get().reads(3 * c as u64))
+			.saturating_add(T::DbWeight::get().writes(3))
+			.saturating_add(T::DbWeight::get().writes(c.into()))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem Voters (r:1 w:1)
+	// Storage: VotingSystem CounterForVoters (r:1 w:1)
+	fn add_voter() -> Weight {
+		Weight::from_ref_time(12_000_000)
+			.saturating_add(T::DbWeight::get().reads(4))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem Candidates (r:c w:1)
+	// The new key is parsed and compared with the key of every candidate.
+	fn update_candidate_info(c: u32) -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(Weight::from_ref_time(1_000_000).saturating_mul(c.into()))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().reads(c.into()))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: VotingSystem ExcludedCandidates (r:1 w:0)
+	// Storage: VotingSystem Candidates (r:1 w:0)
+	// Storage: VotingSystem CandidateKeyFingerprints (r:1 w:0)
+	// Storage: VotingSystem Voters (r:1 w:0)
+	// Storage: VotingSystem BlindedMessages (r:1 w:0)
+	// Storage: VotingSystem BlindedSignatures (r:1 w:1)
+	// Storage: VotingSystem SignedVoterCount (r:1 w:1)
+	// Pays for one RSA signature verification.
+	fn biased_signing() -> Weight {
+		Weight::from_ref_time(400_000_000)
+			.saturating_add(T::DbWeight::get().reads(7))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem Candidates (r:c w:0)
+	// Storage: VotingSystem CandidateNominations (r:1 w:1)
+	// The key is parsed and compared with the key of every candidate.
+	fn nominate_candidate(c: u32) -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(Weight::from_ref_time(1_000_000).saturating_mul(c.into()))
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().reads(c.into()))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem CandidateNominations (r:1 w:1)
+	// Storage: VotingSystem Candidates (r:c w:1)
+	// Storage: VotingSystem CandidatesCount (r:1 w:1)
+	// The key is parsed and compared with the key of every candidate.
+	fn approve_candidate(c: u32) -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(Weight::from_ref_time(1_000_000).saturating_mul(c.into()))
+			.saturating_add(T::DbWeight::get().reads(4))
+			.saturating_add(T::DbWeight::get().reads(c.into()))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem Candidates (r:1 w:1)
+	// Storage: VotingSystem CandidatesCount (r:1 w:1)
+	fn withdraw_candidate() -> Weight {
+		Weight::from_ref_time(12_000_000)
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem RevotingPolicy (r:0 w:1)
+	fn set_revote_policy() -> Weight {
+		Weight::from_ref_time(8_000_000)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem TimelockRound (r:1 w:0)
+	// Storage: VotingSystem CredentialKey (r:0 w:1)
+	// Checking the key is a group element takes one modular exponentiation.
+	fn set_credential_key() -> Weight {
+		Weight::from_ref_time(100_000_000)
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem CredentialKey (r:1 w:0)
+	// Storage: VotingSystem Voters (r:1 w:0)
+	// Storage: VotingSystem VoterCredentials (r:0 w:1)
+	// Storage: VotingSystem CounterForVoterCredentials (r:1 w:1)
+	// Checking the ciphertext holds group elements takes two modular exponentiations.
+	fn register_credential() -> Weight {
+		Weight::from_ref_time(200_000_000)
+			.saturating_add(T::DbWeight::get().reads(5))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem TimelockRound (r:1 w:0)
+	// Storage: VotingSystem BallotKeys (r:1 w:1)
+	// The private key is parsed and validated against the public key.
+	fn reveal_ballot_key() -> Weight {
+		Weight::from_ref_time(50_000_000)
+			.saturating_add(T::DbWeight::get().reads(4))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem CredentialKey (r:1 w:0)
+	// Storage: VotingSystem TimelockRound (r:0 w:1)
+	fn set_timelock_round() -> Weight {
+		Weight::from_ref_time(8_000_000)
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem TimelockRound (r:1 w:0)
+	// Storage: VotingSystem BeaconSignature (r:1 w:1)
+	// Verifying the signature hashes the round to G1 and takes two pairings.
+	fn submit_beacon_signature() -> Weight {
+		Weight::from_ref_time(20_000_000_000)
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Paused (r:1 w:1)
+	fn pause() -> Weight {
+		Weight::from_ref_time(8_000_000)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Paused (r:1 w:1)
+	fn unpause() -> Weight {
+		Weight::from_ref_time(8_000_000)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Phase (r:1 w:1)
+	// Storage: VotingSystem PhaseChanges (r:0 w:1)
+	// Storage: VotingSystem CancellationReason (r:0 w:1)
+	fn cancel_election() -> Weight {
+		Weight::from_ref_time(12_000_000)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Phase (r:1 w:1)
+	// Storage: VotingSystem Disputes (r:1 w:1)
+	// Storage: VotingSystem OpenDisputes (r:1 w:1)
+	// Storage: VotingSystem PhaseChanges (r:0 w:1)
+	// Storage: VotingSystem CancellationReason (r:0 w:1)
+	// Upholding the dispute cancels the election.
+	fn resolve_dispute() -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(T::DbWeight::get().reads(4))
+			.saturating_add(T::DbWeight::get().writes(5))
+	}
+	// Storage: VotingSystem CentralAuthority (r:1 w:0)
+	// Storage: VotingSystem Phase (r:1 w:0)
+	// Storage: VotingSystem PhaseChanges (r:1 w:0)
+	// Storage: VotingSystem BlindedMessageCount (r:1 w:0)
+	// Storage: VotingSystem CounterForVoters (r:1 w:0)
+	// Storage: VotingSystem Candidates (r:1 w:0)
+	// Storage: VotingSystem ExcludedCandidates (r:1 w:1)
+	// Storage: VotingSystem CounterForExcludedCandidates (r:1 w:1)
+	// Storage: VotingSystem SignedVoterCount (r:1 w:0)
+	// Storage: VotingSystem CandidatesCount (r:1 w:0)
+	fn exclude_candidate() -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(T::DbWeight::get().reads(10))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn add_voters(n: u32) -> Weight {
+		Weight::from_ref_time(10_000_000)
+			.saturating_add(Weight::from_ref_time(5_000_000).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().reads(2 * n as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 * n as u64))
+	}
+	fn biased_signing_batch(n: u32) -> Weight {
+		Weight::from_ref_time(10_000_000)
+			.saturating_add(Weight::from_ref_time(400_000_000).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().reads(3 * n as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 * n as u64))
+	}
+	fn tally_step(n: u32) -> Weight {
+		Weight::from_ref_time(10_000_000)
+			.saturating_add(Weight::from_ref_time(2_000_000).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(6))
+			.saturating_add(RocksDbWeight::get().reads(3 * n as u64))
+			.saturating_add(RocksDbWeight::get().writes(2))
+			.saturating_add(RocksDbWeight::get().writes(2 * n as u64))
+	}
+	fn file_dispute() -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(RocksDbWeight::get().reads(8))
+			.saturating_add(RocksDbWeight::get().writes(2))
+	}
+	fn register_blinded_messages(n: u32) -> Weight {
+		Weight::from_ref_time(12_000_000)
+			.saturating_add(Weight::from_ref_time(1_000_000).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(6))
+			.saturating_add(RocksDbWeight::get().reads(n.into()))
+			.saturating_add(RocksDbWeight::get().writes(1))
+			.saturating_add(RocksDbWeight::get().writes(n.into()))
+	}
+	fn drop_voter() -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(RocksDbWeight::get().reads(8))
+			.saturating_add(RocksDbWeight::get().writes(4))
+	}
+	fn vote(n: u32) -> Weight {
+		Weight::from_ref_time(20_000_000)
+			.saturating_add(Weight::from_ref_time(400_000_000).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(40))
+			.saturating_add(RocksDbWeight::get().reads(2 * n as u64))
+			.saturating_add(RocksDbWeight::get().writes(38))
+	}
+	fn vote_timelocked(n: u32) -> Weight {
+		Weight::from_ref_time(120_000_000)
+			.saturating_add(Weight::from_ref_time(400_000_000).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(39))
+			.saturating_add(RocksDbWeight::get().reads(2 * n as u64))
+			.saturating_add(RocksDbWeight::get().writes(38))
+	}
+	fn tally_timelocked_step(n: u32) -> Weight {
+		Weight::from_ref_time(10_000_000)
+			.saturating_add(Weight::from_ref_time(10_000_000_000).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(6))
+			.saturating_add(RocksDbWeight::get().reads(3 * n as u64))
+			.saturating_add(RocksDbWeight::get().writes(2))
+			.saturating_add(RocksDbWeight::get().writes(2 * n as u64))
+	}
+	fn vote_with_credential(n: u32) -> Weight {
+		Weight::from_ref_time(25_000_000)
+			.saturating_add(Weight::from_ref_time(400_000_000).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(38))
+			.saturating_add(RocksDbWeight::get().reads(2 * n as u64))
+			.saturating_add(RocksDbWeight::get().writes(39))
+	}
+	fn submit_credential_tally(n: u32) -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(Weight::from_ref_time(2_000_000).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(6))
+			.saturating_add(RocksDbWeight::get().reads(2 * n as u64))
+			.saturating_add(RocksDbWeight::get().writes(3))
+			.saturating_add(RocksDbWeight::get().writes(2 * n as u64))
+	}
+	fn change_phase(c: u32) -> Weight {
+		Weight::from_ref_time(20_000_000)
+			.saturating_add(Weight::from_ref_time(3_000_000).saturating_mul(c.into()))
+			.saturating_add(RocksDbWeight::get().reads(41))
+			.saturating_add(RocksDbWeight::get().reads(3 * c as u64))
+			.saturating_add(RocksDbWeight::get().writes(3))
+			.saturating_add(RocksDbWeight::get().writes(c.into()))
+	}
+	fn add_voter() -> Weight {
+		Weight::from_ref_time(12_000_000)
+			.saturating_add(RocksDbWeight::get().reads(4))
+			.saturating_add(RocksDbWeight::get().writes(2))
+	}
+	fn update_candidate_info(c: u32) -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(Weight::from_ref_time(1_000_000).saturating_mul(c.into()))
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().reads(c.into()))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	fn biased_signing() -> Weight {
+		Weight::from_ref_time(400_000_000)
+			.saturating_add(RocksDbWeight::get().reads(7))
+			.saturating_add(RocksDbWeight::get().writes(2))
+	}
+	fn nominate_candidate(c: u32) -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(Weight::from_ref_time(1_000_000).saturating_mul(c.into()))
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().reads(c.into()))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	fn approve_candidate(c: u32) -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(Weight::from_ref_time(1_000_000).saturating_mul(c.into()))
+			.saturating_add(RocksDbWeight::get().reads(4))
+			.saturating_add(RocksDbWeight::get().reads(c.into()))
+			.saturating_add(RocksDbWeight::get().writes(3))
+	}
+	fn withdraw_candidate() -> Weight {
+		Weight::from_ref_time(12_000_000)
+			.saturating_add(RocksDbWeight::get().reads(3))
+			.saturating_add(RocksDbWeight::get().writes(2))
+	}
+	fn set_revote_policy() -> Weight {
+		Weight::from_ref_time(8_000_000)
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	fn set_credential_key() -> Weight {
+		Weight::from_ref_time(100_000_000)
+			.saturating_add(RocksDbWeight::get().reads(3))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	fn register_credential() -> Weight {
+		Weight::from_ref_time(200_000_000)
+			.saturating_add(RocksDbWeight::get().reads(5))
+			.saturating_add(RocksDbWeight::get().writes(2))
+	}
+	fn reveal_ballot_key() -> Weight {
+		Weight::from_ref_time(50_000_000)
+			.saturating_add(RocksDbWeight::get().reads(4))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	fn set_timelock_round() -> Weight {
+		Weight::from_ref_time(8_000_000)
+			.saturating_add(RocksDbWeight::get().reads(3))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	fn submit_beacon_signature() -> Weight {
+		Weight::from_ref_time(20_000_000_000)
+			.saturating_add(RocksDbWeight::get().reads(3))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	fn pause() -> Weight {
+		Weight::from_ref_time(8_000_000)
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	fn unpause() -> Weight {
+		Weight::from_ref_time(8_000_000)
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	fn cancel_election() -> Weight {
+		Weight::from_ref_time(12_000_000)
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(3))
+	}
+	fn resolve_dispute() -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(RocksDbWeight::get().reads(4))
+			.saturating_add(RocksDbWeight::get().writes(5))
+	}
+	fn exclude_candidate() -> Weight {
+		Weight::from_ref_time(15_000_000)
+			.saturating_add(RocksDbWeight::get().reads(10))
+			.saturating_add(RocksDbWeight::get().writes(2))
+	}
+}
@@ -6,6 +6,7 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+use codec::Encode;
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
@@ -14,9 +15,12 @@ use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
-	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, Verify},
+	traits::{
+		self, AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor,
+		StaticLookup, Verify,
+	},
 	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, MultiSignature,
+	ApplyExtrinsicResult, MultiSignature, SaturatedConversion,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -27,7 +31,8 @@ use sp_version::RuntimeVersion;
 pub use frame_support::{
 	construct_runtime, parameter_types,
 	traits::{
-		ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem, Randomness, StorageInfo,
+		ConstU128, ConstU32, ConstU64, ConstU8, Contains, KeyOwnerProofSystem, Randomness,
+		StorageInfo,
 	},
 	weights::{
 		constants::{
@@ -38,9 +43,9 @@ pub use frame_support::{
 	StorageValue,
 };
 pub use frame_system::Call as SystemCall;
-// pub use pallet_balances::Call as BalancesCall;
+pub use pallet_balances::Call as BalancesCall;
 pub use pallet_timestamp::Call as TimestampCall;
-// use pallet_transaction_payment::{ConstFeeMultiplier, CurrencyAdapter, Multiplier};
+use pallet_transaction_payment::{ConstFeeMultiplier, CurrencyAdapter, Multiplier};
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
 pub use sp_runtime::{Perbill, Permill};
@@ -103,10 +108,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 105,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 3,
 	state_version: 1,
 };
 
@@ -149,11 +154,25 @@ parameter_types! {
 	pub const SS58Prefix: u8 = 42;
 }
 
+/// Blocks the voting system calls the current election phase does not allow, or all of them while
+/// the election is paused, see `pallet_voting_system::Pallet::is_call_allowed`. Root calls are not
+/// filtered.
+pub struct ElectionCallFilter;
+
+impl Contains<RuntimeCall> for ElectionCallFilter {
+	fn contains(call: &RuntimeCall) -> bool {
+		match call {
+			RuntimeCall::VotingSystem(call) => VotingSystem::is_call_allowed(call),
+			_ => true,
+		}
+	}
+}
+
 // Configure FRAME pallets to include in runtime.
 
 impl frame_system::Config for Runtime {
 	/// The basic call filter to use in dispatchable.
-	type BaseCallFilter = frame_support::traits::Everything;
+	type BaseCallFilter = ElectionCallFilter;
 	/// Block & extrinsics weights: base values and limits.
 	type BlockWeights = BlockWeights;
 	/// The maximum length of a block (in bytes).
@@ -193,7 +212,7 @@ impl frame_system::Config for Runtime {
 	/// What to do if an account is fully reaped from the system.
 	type OnKilledAccount = ();
 	/// The data to be stored in an account.
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<Balance>;
 	/// Weight information for the extrinsics of this pallet.
 	type SystemWeightInfo = ();
 	/// This is used as an identifier of the chain. 42 is the generic substrate prefix.
@@ -241,32 +260,35 @@ impl pallet_timestamp::Config for Runtime {
 /// Existential deposit.
 pub const EXISTENTIAL_DEPOSIT: u128 = 500;
 
-// impl pallet_balances::Config for Runtime {
-// 	type MaxLocks = ConstU32<50>;
-// 	type MaxReserves = ();
-// 	type ReserveIdentifier = [u8; 8];
-// 	/// The type for recording an account's balance.
-// 	type Balance = Balance;
-// 	/// The ubiquitous event type.
-// 	type RuntimeEvent = RuntimeEvent;
-// 	type DustRemoval = ();
-// 	type ExistentialDeposit = ConstU128<EXISTENTIAL_DEPOSIT>;
-// 	type AccountStore = System;
-// 	type WeightInfo = pallet_balances::weights::SubstrateWeight<Runtime>;
-// }
-
-// parameter_types! {
-// 	pub FeeMultiplier: Multiplier = Multiplier::one();
-// }
-
-// impl pallet_transaction_payment::Config for Runtime {
-// 	type RuntimeEvent = RuntimeEvent;
-// 	type OnChargeTransaction = CurrencyAdapter<Balances, ()>;
-// 	type OperationalFeeMultiplier = ConstU8<5>;
-// 	type WeightToFee = IdentityFee<Balance>;
-// 	type LengthToFee = IdentityFee<Balance>;
-// 	type FeeMultiplierUpdate = ConstFeeMultiplier<FeeMultiplier>;
-// }
+impl pallet_balances::Config for Runtime {
+	type MaxLocks = ConstU32<50>;
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	/// The type for recording an account's balance.
+	type Balance = Balance;
+	/// The ubiquitous event type.
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU128<EXISTENTIAL_DEPOSIT>;
+	type AccountStore = System;
+	type WeightInfo = pallet_balances::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub FeeMultiplier: Multiplier = Multiplier::one();
+}
+
+impl pallet_transaction_payment::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	/// Voting, biased signing and registering voters are free for the accounts doing them, every
+	/// other transaction pays its fee from the senders balance.
+	type OnChargeTransaction =
+		pallet_voting_system::fees::FeeExemptCalls<Runtime, CurrencyAdapter<Balances, ()>>;
+	type OperationalFeeMultiplier = ConstU8<5>;
+	type WeightToFee = IdentityFee<Balance>;
+	type LengthToFee = IdentityFee<Balance>;
+	type FeeMultiplierUpdate = ConstFeeMultiplier<FeeMultiplier>;
+}
 
 impl pallet_sudo::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
@@ -274,13 +296,89 @@ impl pallet_sudo::Config for Runtime {
 }
 
 parameter_types! {
-	pub const SignatureLength: u32 = 32;
+	/// Large enough for blinded signatures of up to 4096 bit candidate keys.
+	pub const SignatureLength: u32 = 512;
+	pub const MinKeyBits: u32 = 2048;
+	pub const MaxBatchSize: u32 = 256;
+	/// Large enough for DER encoded 4096 bit RSA public keys.
+	pub const MaxPayloadLength: u32 = 1024;
+	pub const BiasedSignerPeriod: BlockNumber = 3 * DAYS;
+	pub const DisputePeriod: BlockNumber = 2 * DAYS;
+	/// Public key of the drand quicknet beacon, which signs a round every 3 seconds.
+	pub const DrandQuicknetPublicKey: pallet_voting_system::timelock::BeaconPublicKey = [
+		0x83, 0xcf, 0x0f, 0x28, 0x96, 0xad, 0xee, 0x7e, 0xb8, 0xb5, 0xf0, 0x1f,
+		0xca, 0xd3, 0x91, 0x22, 0x12, 0xc4, 0x37, 0xe0, 0x07, 0x3e, 0x91, 0x1f,
+		0xb9, 0x00, 0x22, 0xd3, 0xe7, 0x60, 0x18, 0x3c, 0x8c, 0x4b, 0x45, 0x0b,
+		0x6a, 0x0a, 0x6c, 0x3a, 0xc6, 0xa5, 0x77, 0x6a, 0x2d, 0x10, 0x64, 0x51,
+		0x0d, 0x1f, 0xec, 0x75, 0x8c, 0x92, 0x1c, 0xc2, 0x2b, 0x0e, 0x17, 0xe6,
+		0x3a, 0xaf, 0x4b, 0xcb, 0x5e, 0xd6, 0x63, 0x04, 0xde, 0x9c, 0xf8, 0x09,
+		0xbd, 0x27, 0x4c, 0xa7, 0x3b, 0xab, 0x4a, 0xf5, 0xa6, 0xe9, 0xc7, 0x6a,
+		0x4b, 0xc0, 0x9e, 0x76, 0xea, 0xe8, 0x99, 0x1e, 0xf5, 0xec, 0xe4, 0x5a,
+	];
 }
 
 /// Configure the pallet-template in pallets/template.
 impl pallet_voting_system::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
+	type AuthorityId = pallet_voting_system::crypto::CandidateAuthId;
 	type SignatureLength = SignatureLength;
+	type MinKeyBits = MinKeyBits;
+	type MaxBatchSize = MaxBatchSize;
+	type MaxPayloadLength = MaxPayloadLength;
+	type BiasedSignerPeriod = BiasedSignerPeriod;
+	type DisputePeriod = DisputePeriod;
+	// Timelock elections encrypt their ballots to a round of the drand quicknet
+	type Beacon = pallet_voting_system::timelock::BlsBeacon<DrandQuicknetPublicKey>;
+	type WeightInfo = pallet_voting_system::weights::SubstrateWeight<Runtime>;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
+where
+	RuntimeCall: From<LocalCall>,
+{
+	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: RuntimeCall,
+		public: <Signature as Verify>::Signer,
+		account: AccountId,
+		nonce: Index,
+	) -> Option<(RuntimeCall, <UncheckedExtrinsic as traits::Extrinsic>::SignaturePayload)> {
+		let period =
+			BlockHashCount::get().checked_next_power_of_two().map(|c| c / 2).unwrap_or(2) as u64;
+		let current_block = System::block_number()
+			.saturated_into::<u64>()
+			// The `System::block_number` is initialized with `n+1`,
+			// so the actual block number is `n`.
+			.saturating_sub(1);
+		let extra: SignedExtra = (
+			frame_system::CheckNonZeroSender::<Runtime>::new(),
+			frame_system::CheckSpecVersion::<Runtime>::new(),
+			frame_system::CheckTxVersion::<Runtime>::new(),
+			frame_system::CheckGenesis::<Runtime>::new(),
+			frame_system::CheckEra::<Runtime>::from(generic::Era::mortal(period, current_block)),
+			frame_system::CheckNonce::<Runtime>::from(nonce),
+			frame_system::CheckWeight::<Runtime>::new(),
+			pallet_voting_system::validation::CheckVotingCall::<Runtime>::new(),
+			pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+		);
+		let raw_payload = SignedPayload::new(call, extra).ok()?;
+		let signature = raw_payload.using_encoded(|payload| C::sign(payload, public))?;
+		let address = AccountIdLookup::<AccountId, ()>::unlookup(account);
+		let (call, extra, _) = raw_payload.deconstruct();
+		Some((call, (address, signature, extra)))
+	}
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	RuntimeCall: From<C>,
+{
+	type Extrinsic = UncheckedExtrinsic;
+	type OverarchingCall = RuntimeCall;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -296,8 +394,8 @@ construct_runtime!(
 		Timestamp: pallet_timestamp,
 		Aura: pallet_aura,
 		Grandpa: pallet_grandpa,
-		// Balances: pallet_balances,
-		// TransactionPayment: pallet_transaction_payment,
+		Balances: pallet_balances,
+		TransactionPayment: pallet_transaction_payment,
 		Sudo: pallet_sudo,
 		// Include the custom logic from the pallet-template in the runtime.
 		VotingSystem: pallet_voting_system,
@@ -319,7 +417,10 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	// pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	// Before the fee is charged, so calls that would fail are rejected cheaply and ballots are
+	// only exempt from the fee with verified signatures
+	pallet_voting_system::validation::CheckVotingCall<Runtime>,
+	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -327,6 +428,45 @@ pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
+/// Storage migrations run on the next runtime upgrade.
+pub type Migrations = (
+	pallet_voting_system::migrations::v1::MigrateToV1<Runtime>,
+	EnableBalances,
+);
+
+/// The account data of `frame_system` was `()` before balances were enabled. Existing accounts
+/// keep their nonce and reference counts and start with an empty balance.
+pub struct EnableBalances;
+
+impl frame_support::traits::OnRuntimeUpgrade for EnableBalances {
+	fn on_runtime_upgrade() -> Weight {
+		use frame_support::traits::{Get, GetStorageVersion, StorageVersion};
+
+		// Chains started with balances already have the account data, and the translation must
+		// never run twice
+		if Balances::on_chain_storage_version() != StorageVersion::new(0) {
+			return RocksDbWeight::get().reads(1)
+		}
+
+		let mut accounts = 0;
+		frame_system::Account::<Runtime>::translate::<frame_system::AccountInfo<Index, ()>, _>(
+			|_, account| {
+				accounts += 1;
+				Some(frame_system::AccountInfo {
+					nonce: account.nonce,
+					consumers: account.consumers,
+					providers: account.providers,
+					sufficients: account.sufficients,
+					data: Default::default(),
+				})
+			},
+		);
+		Balances::current_storage_version().put::<Balances>();
+
+		RocksDbWeight::get().reads_writes(accounts + 1, accounts + 1)
+	}
+}
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -334,6 +474,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -345,7 +486,7 @@ mod benches {
 	define_benchmarks!(
 		[frame_benchmarking, BaselineBench::<Runtime>]
 		[frame_system, SystemBench::<Runtime>]
-		// [pallet_balances, Balances]
+		[pallet_balances, Balances]
 		[pallet_timestamp, Timestamp]
 		[pallet_voting_system, VotingSystem]
 	);
@@ -467,37 +608,57 @@ impl_runtime_apis! {
 		}
 	}
 
-	// impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
-	// 	fn query_info(
-	// 		uxt: <Block as BlockT>::Extrinsic,
-	// 		len: u32,
-	// 	) -> pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo<Balance> {
-	// 		TransactionPayment::query_info(uxt, len)
-	// 	}
-	// 	fn query_fee_details(
-	// 		uxt: <Block as BlockT>::Extrinsic,
-	// 		len: u32,
-	// 	) -> pallet_transaction_payment::FeeDetails<Balance> {
-	// 		TransactionPayment::query_fee_details(uxt, len)
-	// 	}
-	// }
-
-	// impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentCallApi<Block, Balance, RuntimeCall>
-	// 	for Runtime
-	// {
-	// 	fn query_call_info(
-	// 		call: RuntimeCall,
-	// 		len: u32,
-	// 	) -> pallet_transaction_payment::RuntimeDispatchInfo<Balance> {
-	// 		TransactionPayment::query_call_info(call, len)
-	// 	}
-	// 	fn query_call_fee_details(
-	// 		call: RuntimeCall,
-	// 		len: u32,
-	// 	) -> pallet_transaction_payment::FeeDetails<Balance> {
-	// 		TransactionPayment::query_call_fee_details(call, len)
-	// 	}
-	// }
+	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
+		fn query_info(
+			uxt: <Block as BlockT>::Extrinsic,
+			len: u32,
+		) -> pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo<Balance> {
+			TransactionPayment::query_info(uxt, len)
+		}
+		fn query_fee_details(
+			uxt: <Block as BlockT>::Extrinsic,
+			len: u32,
+		) -> pallet_transaction_payment::FeeDetails<Balance> {
+			TransactionPayment::query_fee_details(uxt, len)
+		}
+	}
+
+	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentCallApi<Block, Balance, RuntimeCall>
+		for Runtime
+	{
+		fn query_call_info(
+			call: RuntimeCall,
+			len: u32,
+		) -> pallet_transaction_payment::RuntimeDispatchInfo<Balance> {
+			TransactionPayment::query_call_info(call, len)
+		}
+		fn query_call_fee_details(
+			call: RuntimeCall,
+			len: u32,
+		) -> pallet_transaction_payment::FeeDetails<Balance> {
+			TransactionPayment::query_call_fee_details(call, len)
+		}
+	}
+
+	impl pallet_voting_system::runtime_api::VotingSystemApi<Block, AccountId, Hash, BlockNumber>
+		for Runtime
+	{
+		fn ballot_inclusion(
+			receipt: Hash,
+		) -> Option<pallet_voting_system::BallotInclusion<AccountId, Hash, BlockNumber>> {
+			VotingSystem::ballot_inclusion(&receipt)
+		}
+
+		fn election_summary() -> pallet_voting_system::ElectionSummary<AccountId, BlockNumber> {
+			VotingSystem::election_summary()
+		}
+
+		fn ballot_log_page(
+			start: u64,
+		) -> Vec<pallet_voting_system::BallotLogEntry<AccountId, Hash, BlockNumber>> {
+			VotingSystem::ballot_log_page(start)
+		}
+	}
 
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {